@@ -0,0 +1,100 @@
+use smogfish::datagen::{play_game, DatagenConfig, Sample};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+const USAGE: &str = "usage: datagen --output <file> [options]
+
+Plays self-play games from random openings and writes the quiet positions
+with their search score and the game result. The output only depends on
+the options, not on the number of threads.
+
+options:
+  --games <n>          number of games, 100 by default
+  --threads <n>        1 by default
+  --depth <n>          search depth per move, 4 by default
+  --random-plies <n>   random moves at the start of a game, 8 by default
+  --seed <n>           0 by default
+  --format <text|binary>
+                       `<fen> | <score> | <result>` lines or 32 byte records";
+
+fn number<T: std::str::FromStr>(s: String) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number '{}'", s))
+}
+
+fn run() -> Result<(), String> {
+    let mut config = DatagenConfig::default();
+    let (mut games, mut threads, mut binary, mut output) = (100u64, 1usize, false, None);
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--games" => games = number(value()?)?,
+            "--threads" => threads = number::<usize>(value()?)?.max(1),
+            "--depth" => config.depth = number(value()?)?,
+            "--random-plies" => config.random_plies = number(value()?)?,
+            "--seed" => config.seed = number(value()?)?,
+            "--format" => {
+                binary = match value()?.as_str() {
+                    "text" => false,
+                    "binary" => true,
+                    f => return Err(format!("unknown format '{}'", f)),
+                }
+            }
+            "--output" => output = Some(value()?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let path = output.ok_or(USAGE)?;
+    let file = File::create(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut out = BufWriter::new(file);
+
+    let next_game = AtomicU64::new(0);
+    let (tx, rx) = mpsc::channel::<(u64, Vec<Sample>)>();
+    thread::scope(|s| -> Result<(), String> {
+        for _ in 0..threads {
+            let (tx, next_game, config) = (tx.clone(), &next_game, &config);
+            s.spawn(move || loop {
+                let index = next_game.fetch_add(1, Ordering::Relaxed);
+                if index >= games || tx.send((index, play_game(config, index))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        // games finish out of order, they are written in order of their index
+        let mut finished = BTreeMap::new();
+        let (mut written, mut positions) = (0, 0);
+        for (index, samples) in rx {
+            finished.insert(index, samples);
+            while let Some(samples) = finished.remove(&written) {
+                for sample in &samples {
+                    let res = match binary {
+                        true => out.write_all(&sample.to_bytes()),
+                        false => writeln!(out, "{}", sample.to_text()),
+                    };
+                    res.map_err(|e| format!("{}: {}", path, e))?;
+                }
+                positions += samples.len();
+                written += 1;
+                if written % 10 == 0 || written == games {
+                    eprintln!("{} of {} games, {} positions", written, games, positions);
+                }
+            }
+        }
+        Ok(())
+    })?;
+    out.flush().map_err(|e| format!("{}: {}", path, e))
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,137 @@
+use smogfish::epd::Epd;
+use smogfish::notation::to_san;
+use smogfish::search::{search, SearchLimits};
+use std::time::Duration;
+
+const USAGE: &str = "usage: epd <file> [--depth <n>] [--movetime <ms>] [--json]
+
+Searches every position of the file and checks the best move against the
+bm and am operations. Without limits every position gets one second.";
+
+fn json_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+fn run() -> Result<bool, String> {
+    let mut file = None;
+    let mut limits = SearchLimits::default();
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => {
+                let d = args.next().ok_or("--depth needs a value")?;
+                limits.depth = Some(d.parse().map_err(|_| format!("invalid depth '{}'", d))?);
+            }
+            "--movetime" => {
+                let t = args.next().ok_or("--movetime needs a value")?;
+                let ms = t.parse().map_err(|_| format!("invalid movetime '{}'", t))?;
+                limits.movetime = Some(Duration::from_millis(ms));
+            }
+            "--json" => json = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if file.is_none() => file = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let file = file.ok_or(USAGE)?;
+    if limits.depth.is_none() && limits.movetime.is_none() {
+        limits.movetime = Some(Duration::from_secs(1));
+    }
+
+    let content = std::fs::read_to_string(&file).map_err(|e| format!("{}: {}", file, e))?;
+    let (mut solved, mut failed, mut skipped) = (0, 0, 0);
+    let mut entries = vec![];
+
+    for (line_nr, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let epd = Epd::parse(line).map_err(|e| format!("line {}: {}", line_nr + 1, e))?;
+        let id = epd.id().map(|s| s.to_string()).unwrap_or(format!("line {}", line_nr + 1));
+
+        let result = search(&epd.board, &limits);
+        let best = match &result.best_move {
+            Some(m) => to_san(&epd.board, m),
+            None => "-".to_string(),
+        };
+        let status = match &result.best_move {
+            Some(m) => epd.is_solved_by(m).map_err(|e| format!("line {}: {}", line_nr + 1, e))?,
+            None => Some(false),
+        };
+        let status = match status {
+            Some(true) => {
+                solved += 1;
+                "solved"
+            }
+            Some(false) => {
+                failed += 1;
+                "failed"
+            }
+            None => {
+                skipped += 1;
+                "skipped"
+            }
+        };
+        let expected = match (epd.operation("bm"), epd.operation("am")) {
+            (Some(bm), _) => format!("bm {}", bm.join(" ")),
+            (None, Some(am)) => format!("am {}", am.join(" ")),
+            (None, None) => "-".to_string(),
+        };
+
+        if json {
+            entries.push(format!(
+                "{{\"id\":{},\"status\":\"{}\",\"best\":{},\"expected\":{},\"score\":{},\"depth\":{},\"nodes\":{}}}",
+                json_string(&id),
+                status,
+                json_string(&best),
+                json_string(&expected),
+                result.score,
+                result.depth,
+                result.nodes
+            ));
+        } else {
+            println!(
+                "{:<16} {:<8} {:<8} ({}) score {} depth {}",
+                id, status, best, expected, result.score, result.depth
+            );
+        }
+    }
+
+    if json {
+        println!(
+            "{{\"total\":{},\"solved\":{},\"failed\":{},\"skipped\":{},\"positions\":[{}]}}",
+            solved + failed + skipped,
+            solved,
+            failed,
+            skipped,
+            entries.join(",")
+        );
+    } else {
+        println!();
+        println!("solved {} of {} ({} failed, {} skipped)", solved, solved + failed, failed, skipped);
+    }
+    Ok(failed == 0)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => (),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,331 @@
+mod player;
+
+use player::{Clock, EngineSpec, GoError, Player};
+use smogfish::board::helper::Color;
+use smogfish::board::Board;
+use smogfish::epd::Epd;
+use smogfish::game::{Game, GameResult, Termination};
+use smogfish::sprt::{MatchScore, Sprt, SprtStatus};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: match --engine <spec> --engine <spec> [options]
+
+Plays the two engines against each other, every opening twice with
+swapped colors. An engine spec is a comma separated list like
+`cmd=./engine,name=new,option.Hash=64` for a UCI engine or
+`builtin,name=old,depth=5` for smogfish in this process. depth, nodes
+and movetime replace the time control for that engine.
+
+options:
+  --games <n>                 number of games, 2 by default
+  --openings <file>           FEN or EPD positions to start from
+  --tc <seconds>[+<inc>]      time control, 10+0.1 by default
+  --movetime <ms>             fixed time per move instead of a clock
+  --resign <moves> <cp>       adjudicate a loss after both engines agreed
+                              on a score of at least cp for that many moves
+  --draw <move> <moves> <cp>  adjudicate a draw from move number on if
+                              both scores stayed within cp for that many moves
+  --pgn <file>                append the games to a PGN file
+  --sprt <elo0> <elo1> [<alpha> <beta>]
+                              stop when the SPRT accepts one of the hypotheses";
+
+#[derive(Default)]
+struct Options {
+    games: u32,
+    openings: Vec<Board>,
+    clock: Option<Clock>,
+    movetime: Option<Duration>,
+    resign: Option<(usize, i32)>,
+    draw: Option<(u16, usize, i32)>,
+    pgn: Option<String>,
+    sprt: Option<Sprt>,
+}
+
+fn number<T: std::str::FromStr>(s: String) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number '{}'", s))
+}
+
+fn parse_args() -> Result<(Vec<EngineSpec>, Options), String> {
+    let mut specs = vec![];
+    let mut options = Options {
+        games: 2,
+        clock: Some(Clock {
+            time_left: [Duration::from_secs(10); 2],
+            increment: Duration::from_millis(100),
+        }),
+        ..Default::default()
+    };
+
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--engine" => specs.push(EngineSpec::parse(&value()?)?),
+            "--games" => options.games = number(value()?)?,
+            "--openings" => {
+                let file = value()?;
+                let content = std::fs::read_to_string(&file).map_err(|e| format!("{}: {}", file, e))?;
+                for (i, line) in content.lines().enumerate() {
+                    if line.trim().is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    let epd = Epd::parse(line).map_err(|e| format!("{} line {}: {}", file, i + 1, e))?;
+                    options.openings.push(epd.board);
+                }
+            }
+            "--tc" => {
+                let tc = value()?;
+                let (base, inc) = tc.split_once('+').unwrap_or((&tc, "0"));
+                let seconds = |s: &str| number::<f64>(s.to_string()).map(Duration::from_secs_f64);
+                options.clock = Some(Clock {
+                    time_left: [seconds(base)?; 2],
+                    increment: seconds(inc)?,
+                });
+                options.movetime = None;
+            }
+            "--movetime" => {
+                options.movetime = Some(Duration::from_millis(number(value()?)?));
+                options.clock = None;
+            }
+            "--resign" => options.resign = Some((number(value()?)?, number(value()?)?)),
+            "--draw" => options.draw = Some((number(value()?)?, number(value()?)?, number(value()?)?)),
+            "--pgn" => options.pgn = Some(value()?),
+            "--sprt" => {
+                let mut sprt = Sprt::new(number(value()?)?, number(value()?)?);
+                // alpha and beta are optional
+                if let Some(alpha) = args.next_if(|a| !a.starts_with("--")) {
+                    let beta = args.next().ok_or("--sprt needs both alpha and beta")?;
+                    sprt.alpha = number(alpha)?;
+                    sprt.beta = number(beta)?;
+                }
+                options.sprt = Some(sprt);
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    if specs.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+    if options.openings.is_empty() {
+        options.openings.push(Game::default().start_position().clone());
+    }
+    Ok((specs, options))
+}
+
+/// checks the score based adjudication rules, `scores` holds the score
+/// reported for every ply from the view of the side that moved
+fn adjudicate(game: &Game, scores: &[Option<i32>], options: &Options) -> Option<(GameResult, Termination)> {
+    let recent = |plies: usize| scores.len().checked_sub(plies).map(|start| &scores[start..]);
+
+    if let Some((moves, cp)) = options.resign {
+        if let Some(last) = recent(moves * 2) {
+            // the side that just moved is at odd distances from the end
+            let mover_lost = last.iter().rev().enumerate().all(|(i, s)| match (i % 2, s) {
+                (0, Some(s)) => *s <= -cp,
+                (_, Some(s)) => *s >= cp,
+                _ => false,
+            });
+            let mover_won = last.iter().rev().enumerate().all(|(i, s)| match (i % 2, s) {
+                (0, Some(s)) => *s >= cp,
+                (_, Some(s)) => *s <= -cp,
+                _ => false,
+            });
+            // the side to move is not the one that moved last
+            let mover = !game.board().current_player();
+            let winner = match (mover_lost, mover_won) {
+                (true, _) => Some(!mover),
+                (_, true) => Some(mover),
+                _ => None,
+            };
+            if let Some(winner) = winner {
+                let result = match winner {
+                    Color::White => GameResult::WhiteWins,
+                    Color::Black => GameResult::BlackWins,
+                };
+                return Some((result, Termination::Adjudication));
+            }
+        }
+    }
+
+    if let Some((move_number, moves, cp)) = options.draw {
+        if game.board().fullmove_number() >= move_number {
+            if let Some(last) = recent(moves * 2) {
+                if last.iter().all(|s| s.is_some_and(|s| s.abs() <= cp)) {
+                    return Some((GameResult::Draw, Termination::Adjudication));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// plays one game, `players[0]` has white
+fn play_game(players: [&mut dyn Player; 2], opening: &Board, options: &Options) -> Result<Game, String> {
+    let mut game = Game::new(opening.clone());
+    game.set_tag("White", players[0].name());
+    game.set_tag("Black", players[1].name());
+    let mut clock = options.clock;
+    let mut scores = vec![];
+    let [white, black] = players;
+    white.new_game()?;
+    black.new_game()?;
+
+    while game.result() == GameResult::Ongoing {
+        let us = game.board().current_player();
+        let player: &mut dyn Player = match us {
+            Color::White => &mut *white,
+            Color::Black => &mut *black,
+        };
+
+        let start = Instant::now();
+        let reply = player.go(&game, clock.as_ref());
+        let elapsed = start.elapsed();
+
+        if let Some(clock) = &mut clock {
+            let left = &mut clock.time_left[us as usize];
+            if elapsed > *left {
+                game.set_result(loss_of(us), Termination::TimeForfeit);
+                break;
+            }
+            *left = *left - elapsed + clock.increment;
+        }
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(e) => {
+                eprintln!("{}", e);
+                let termination = match e {
+                    GoError::Failed(_) => Termination::Abandoned,
+                    GoError::IllegalMove(_) => Termination::RulesInfraction,
+                };
+                game.set_result(loss_of(us), termination);
+                break;
+            }
+        };
+        game.push(&reply.mov)?;
+        scores.push(reply.score);
+
+        if game.result() == GameResult::Ongoing {
+            if let Some((result, termination)) = adjudicate(&game, &scores, options) {
+                game.set_result(result, termination);
+            }
+        }
+    }
+    Ok(game)
+}
+
+fn loss_of(color: Color) -> GameResult {
+    match color {
+        Color::White => GameResult::BlackWins,
+        Color::Black => GameResult::WhiteWins,
+    }
+}
+
+fn termination_tag(termination: Option<Termination>) -> &'static str {
+    match termination {
+        Some(Termination::TimeForfeit) => "time forfeit",
+        Some(Termination::Adjudication) => "adjudication",
+        Some(Termination::Abandoned) => "abandoned",
+        Some(Termination::RulesInfraction) => "rules infraction",
+        Some(_) => "normal",
+        None => "unterminated",
+    }
+}
+
+fn run() -> Result<(), String> {
+    let (mut specs, options) = parse_args()?;
+    // a match wide movetime is a fixed limit for every engine without its own
+    if let Some(movetime) = options.movetime {
+        for spec in specs.iter_mut().filter(|s| !s.has_limits()) {
+            spec.limits.movetime = Some(movetime);
+        }
+    }
+    let names: Vec<String> = specs.iter().map(|s| s.name.clone()).collect();
+    let mut players: Vec<Box<dyn Player>> = specs.into_iter().map(|s| s.start()).collect::<Result<_, _>>()?;
+
+    let mut score = MatchScore::default();
+    for round in 0..options.games {
+        let opening = &options.openings[(round / 2) as usize % options.openings.len()];
+        // the first engine has white in even games
+        let first_is_white = round % 2 == 0;
+        let (a, b) = players.split_at_mut(1);
+        let (first, second) = (a[0].as_mut(), b[0].as_mut());
+        let pair: [&mut dyn Player; 2] = if first_is_white { [first, second] } else { [second, first] };
+        let mut game = play_game(pair, opening, &options)?;
+
+        let first_won = match game.result() {
+            GameResult::WhiteWins => Some(first_is_white),
+            GameResult::BlackWins => Some(!first_is_white),
+            _ => None,
+        };
+        match first_won {
+            Some(true) => score.wins += 1,
+            Some(false) => score.losses += 1,
+            None => score.draws += 1,
+        }
+
+        game.set_tag("Event", "smogfish match");
+        game.set_tag("Round", &(round + 1).to_string());
+        game.set_tag("Termination", termination_tag(game.termination()));
+        if let Some(path) = &options.pgn {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("{}: {}", path, e))?;
+            writeln!(file, "{}", game.to_pgn()).map_err(|e| format!("{}: {}", path, e))?;
+        }
+
+        println!(
+            "game {} ({} vs {}): {} {{{}}}",
+            round + 1,
+            game.tag("White").unwrap_or("?"),
+            game.tag("Black").unwrap_or("?"),
+            game.result(),
+            termination_tag(game.termination())
+        );
+        println!(
+            "score of {} vs {}: {} - {} - {} [{:.3}] {}",
+            names[0],
+            names[1],
+            score.wins,
+            score.losses,
+            score.draws,
+            score.score(),
+            score.games()
+        );
+        println!("elo difference: {:.1} +/- {:.1}, LOS: {:.1} %", score.elo(), score.elo_margin(), score.los() * 100.0);
+        if let Some(sprt) = &options.sprt {
+            let (lower, upper) = sprt.bounds();
+            println!(
+                "SPRT: llr {:.2} ({:.2}, {:.2}) [{}, {}]",
+                sprt.llr(&score),
+                lower,
+                upper,
+                sprt.elo0,
+                sprt.elo1
+            );
+            match sprt.status(&score) {
+                SprtStatus::AcceptH0 => {
+                    println!("H0 was accepted");
+                    break;
+                }
+                SprtStatus::AcceptH1 => {
+                    println!("H1 was accepted");
+                    break;
+                }
+                SprtStatus::Continue => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
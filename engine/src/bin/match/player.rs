@@ -0,0 +1,308 @@
+use smogfish::board::Move;
+use smogfish::game::Game;
+use smogfish::notation::parse_uci;
+use smogfish::search::{search, time_for_move, SearchLimits, MATE_SCORE};
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// the clocks of both players in a game with a time control
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    pub time_left: [Duration; 2],
+    pub increment: Duration,
+}
+
+/// why a player did not come up with a move
+#[derive(Debug)]
+pub enum GoError {
+    /// the player crashed, hung or could not be talked to
+    Failed(String),
+    /// the player answered with a move that is not legal in the position
+    IllegalMove(String),
+}
+
+impl From<String> for GoError {
+    fn from(e: String) -> Self {
+        GoError::Failed(e)
+    }
+}
+
+impl fmt::Display for GoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoError::Failed(e) | GoError::IllegalMove(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+pub struct Reply {
+    pub mov: Move,
+    /// centipawns from the view of the side that moved, if the player told
+    pub score: Option<i32>,
+}
+
+pub trait Player {
+    fn name(&self) -> &str;
+    fn new_game(&mut self) -> Result<(), String>;
+    /// picks a move in the current position of the game
+    fn go(&mut self, game: &Game, clock: Option<&Clock>) -> Result<Reply, GoError>;
+}
+
+/// how a player should be set up, parsed from `--engine` arguments like
+/// `cmd=./other,name=old,option.Hash=64` or `builtin,depth=6`
+pub struct EngineSpec {
+    pub name: String,
+    /// command of a UCI engine, smogfish itself without one
+    pub cmd: Option<String>,
+    /// fixed limits that replace the time control
+    pub limits: SearchLimits,
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineSpec {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut spec = EngineSpec {
+            name: String::new(),
+            cmd: None,
+            limits: SearchLimits::default(),
+            options: vec![],
+        };
+        let mut builtin = false;
+        for part in s.split(',') {
+            if part == "builtin" {
+                builtin = true;
+                continue;
+            }
+            let (key, value) = part.split_once('=').ok_or(format!("'{}' is not key=value", part))?;
+            let number = || value.parse::<u64>().map_err(|_| format!("invalid value '{}' for {}", value, key));
+            match key {
+                "cmd" => spec.cmd = Some(value.to_string()),
+                "name" => spec.name = value.to_string(),
+                "depth" => spec.limits.depth = Some(number()?.min(u8::MAX as u64) as u8),
+                "nodes" => spec.limits.nodes = Some(number()?),
+                "movetime" => spec.limits.movetime = Some(Duration::from_millis(number()?)),
+                _ => match key.strip_prefix("option.") {
+                    Some(option) => spec.options.push((option.to_string(), value.to_string())),
+                    None => return Err(format!("unknown engine setting '{}'", key)),
+                },
+            }
+        }
+        if builtin == spec.cmd.is_some() {
+            return Err(format!("'{}' needs either cmd=<path> or builtin", s));
+        }
+        if spec.name.is_empty() {
+            spec.name = spec.cmd.clone().unwrap_or("smogfish".to_string());
+        }
+        Ok(spec)
+    }
+
+    pub fn has_limits(&self) -> bool {
+        self.limits.depth.is_some() || self.limits.nodes.is_some() || self.limits.movetime.is_some()
+    }
+
+    pub fn start(self) -> Result<Box<dyn Player>, String> {
+        match self.cmd.clone() {
+            Some(cmd) => Ok(Box::new(UciPlayer::start(self, &cmd)?)),
+            None if !self.options.is_empty() => Err("the builtin engine has no options".to_string()),
+            None => Ok(Box::new(Builtin { spec: self })),
+        }
+    }
+}
+
+/// smogfish searching in this process
+pub struct Builtin {
+    spec: EngineSpec,
+}
+
+impl Player for Builtin {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn new_game(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn go(&mut self, game: &Game, clock: Option<&Clock>) -> Result<Reply, GoError> {
+        let mut limits = self.spec.limits.clone();
+        if let (false, Some(clock)) = (self.spec.has_limits(), clock) {
+            let us = game.board().current_player() as usize;
+            limits.movetime = Some(time_for_move(clock.time_left[us], clock.increment, None));
+        }
+        let result = search(game.board(), &limits);
+        let mov = result.best_move.ok_or(GoError::Failed("no legal move to play".to_string()))?;
+        Ok(Reply {
+            mov,
+            score: Some(result.score),
+        })
+    }
+}
+
+/// an engine in another process that speaks UCI
+pub struct UciPlayer {
+    spec: EngineSpec,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+/// how long an engine may take to answer anything that is not `go`
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl UciPlayer {
+    fn start(spec: EngineSpec, cmd: &str) -> Result<Self, String> {
+        let mut child = Command::new(cmd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("could not start {}: {}", cmd, e))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // read on a thread so a hanging engine can be detected
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut player = UciPlayer {
+            spec,
+            child,
+            stdin,
+            lines,
+        };
+        player.send("uci")?;
+        player.wait_for("uciok", ANSWER_TIMEOUT)?;
+        for (name, value) in player.spec.options.clone() {
+            player.send(&format!("setoption name {} value {}", name, value))?;
+        }
+        player.send("isready")?;
+        player.wait_for("readyok", ANSWER_TIMEOUT)?;
+        Ok(player)
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("{}: {}", self.spec.name, e))
+    }
+
+    fn read_line(&mut self, deadline: Instant) -> Result<String, String> {
+        match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(format!("{} did not answer in time", self.spec.name)),
+            Err(RecvTimeoutError::Disconnected) => Err(format!("{} exited", self.spec.name)),
+        }
+    }
+
+    fn wait_for(&mut self, answer: &str, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        while self.read_line(deadline)?.trim() != answer {}
+        Ok(())
+    }
+}
+
+/// score of an `info` line from the view of the engine
+fn parse_info_score(line: &str) -> Option<i32> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let idx = tokens.iter().position(|&t| t == "score")?;
+    let value: i32 = tokens.get(idx + 2)?.parse().ok()?;
+    match *tokens.get(idx + 1)? {
+        "cp" => Some(value),
+        // mate in n moves is n * 2 - 1 plies away
+        "mate" if value > 0 => Some(MATE_SCORE - (value * 2 - 1)),
+        "mate" => Some(-MATE_SCORE - value * 2),
+        _ => None,
+    }
+}
+
+impl Player for UciPlayer {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn new_game(&mut self) -> Result<(), String> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok", ANSWER_TIMEOUT)
+    }
+
+    fn go(&mut self, game: &Game, clock: Option<&Clock>) -> Result<Reply, GoError> {
+        let moves: Vec<String> = game.moves().iter().map(|p| p.mov.to_string()).collect();
+        let mut position = format!("position fen {}", game.start_position().to_fen());
+        if !moves.is_empty() {
+            position = format!("{} moves {}", position, moves.join(" "));
+        }
+        self.send(&position)?;
+
+        let limits = &self.spec.limits;
+        let mut go = String::from("go");
+        if let Some(depth) = limits.depth {
+            go.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(nodes) = limits.nodes {
+            go.push_str(&format!(" nodes {}", nodes));
+        }
+        if let Some(movetime) = limits.movetime {
+            go.push_str(&format!(" movetime {}", movetime.as_millis()));
+        }
+        // without a clock the engine gets as much time as it wants
+        let mut timeout = Duration::from_secs(3600);
+        if let (false, Some(clock)) = (self.spec.has_limits(), clock) {
+            let [white, black] = clock.time_left;
+            let inc = clock.increment.as_millis();
+            go.push_str(&format!(
+                " wtime {} btime {} winc {} binc {}",
+                white.as_millis(),
+                black.as_millis(),
+                inc,
+                inc
+            ));
+            let us = game.board().current_player();
+            timeout = clock.time_left[us as usize] + Duration::from_secs(1);
+        } else if let Some(movetime) = limits.movetime {
+            timeout = movetime * 2 + Duration::from_secs(1);
+        }
+        self.send(&go)?;
+
+        let deadline = Instant::now() + timeout;
+        let mut score = None;
+        loop {
+            let line = self.read_line(deadline)?;
+            if line.starts_with("info") {
+                score = parse_info_score(&line).or(score);
+            } else if let Some(rest) = line.strip_prefix("bestmove") {
+                let best = rest.split_whitespace().next().unwrap_or("");
+                let mov = parse_uci(game.board(), best)
+                    .map_err(|e| GoError::IllegalMove(format!("{} played {}", self.spec.name, e)))?;
+                return Ok(Reply { mov, score });
+            }
+        }
+    }
+}
+
+impl Drop for UciPlayer {
+    fn drop(&mut self) {
+        self.send("quit").ok();
+        // give the engine a moment to exit on its own
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
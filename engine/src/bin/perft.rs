@@ -0,0 +1,152 @@
+use smogfish::board::Board;
+use smogfish::perft::{divide, divide_hashed, perft, perft_hashed, PerftTable};
+use std::time::Instant;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const USAGE: &str = "usage:
+    perft [--divide] [--hash <mb>] <depth> [fen]
+    perft [--hash <mb>] --epd <file> [max depth]
+
+EPD lines look like `<fen> ;D1 20 ;D2 400`, the FEN can leave out the move counters.";
+
+struct Options {
+    divide: bool,
+    hash_mb: Option<usize>,
+    epd: Option<String>,
+    rest: Vec<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut opts = Options {
+        divide: false,
+        hash_mb: None,
+        epd: None,
+        rest: vec![],
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--divide" => opts.divide = true,
+            "--hash" => {
+                let mb = args.next().ok_or("--hash needs a size in mb")?;
+                opts.hash_mb = Some(mb.parse().map_err(|_| format!("invalid hash size '{}'", mb))?);
+            }
+            "--epd" => opts.epd = Some(args.next().ok_or("--epd needs a file")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => opts.rest.push(arg),
+        }
+    }
+    Ok(opts)
+}
+
+fn count(board: &Board, depth: u8, table: &mut Option<PerftTable>) -> u64 {
+    match table {
+        Some(t) => perft_hashed(board, depth, t),
+        None => perft(board, depth),
+    }
+}
+
+fn run_single(opts: &Options, table: &mut Option<PerftTable>) -> Result<(), String> {
+    let depth: u8 = match opts.rest.first() {
+        Some(d) => d.parse().map_err(|_| format!("invalid depth '{}'", d))?,
+        None => return Err(USAGE.to_string()),
+    };
+    let fen = if opts.rest.len() > 1 {
+        opts.rest[1..].join(" ")
+    } else {
+        START_FEN.to_string()
+    };
+    let board = Board::from_fen(&fen)?;
+
+    let start = Instant::now();
+    let nodes = if opts.divide {
+        let mut total = 0;
+        let moves = match table {
+            Some(t) => divide_hashed(&board, depth, t),
+            None => divide(&board, depth),
+        };
+        for (m, n) in moves {
+            println!("{}: {}", m, n);
+            total += n;
+        }
+        println!();
+        total
+    } else {
+        count(&board, depth, table)
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("{} nodes in {:.3}s ({:.0} nps)", nodes, elapsed, nodes as f64 / elapsed.max(1e-9));
+    Ok(())
+}
+
+/// parses `<fen> ;D1 20 ;D2 400` into the board and the expected node counts
+fn parse_epd_line(line: &str) -> Result<(Board, Vec<(u8, u64)>), String> {
+    let mut parts = line.split(';');
+    let fen = parts.next().unwrap_or("").trim();
+    let fen = match fen.split_whitespace().count() {
+        4 => format!("{} 0 1", fen),
+        _ => fen.to_string(),
+    };
+    let board = Board::from_fen(&fen)?;
+
+    let mut expected = vec![];
+    for part in parts {
+        let mut fields = part.split_whitespace();
+        let (Some(d), Some(n)) = (fields.next(), fields.next()) else {
+            return Err(format!("invalid depth entry '{}'", part.trim()));
+        };
+        let depth = d
+            .strip_prefix('D')
+            .and_then(|d| d.parse().ok())
+            .ok_or(format!("invalid depth '{}'", d))?;
+        let nodes = n.parse().map_err(|_| format!("invalid node count '{}'", n))?;
+        expected.push((depth, nodes));
+    }
+    Ok((board, expected))
+}
+
+fn run_epd(file: &str, opts: &Options, table: &mut Option<PerftTable>) -> Result<bool, String> {
+    let max_depth: u8 = match opts.rest.first() {
+        Some(d) => d.parse().map_err(|_| format!("invalid depth '{}'", d))?,
+        None => u8::MAX,
+    };
+    let content = std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+
+    let (mut passed, mut failed) = (0, 0);
+    for (line_nr, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (board, expected) = parse_epd_line(line).map_err(|e| format!("line {}: {}", line_nr + 1, e))?;
+        for (depth, nodes) in expected.into_iter().filter(|(d, _)| *d <= max_depth) {
+            let got = count(&board, depth, table);
+            if got == nodes {
+                passed += 1;
+            } else {
+                failed += 1;
+                println!("FAIL line {} depth {}: expected {}, got {}", line_nr + 1, depth, nodes, got);
+            }
+        }
+    }
+    println!("{} passed, {} failed", passed, failed);
+    Ok(failed == 0)
+}
+
+fn main() {
+    let result = parse_args().and_then(|opts| {
+        let mut table = opts.hash_mb.map(PerftTable::new);
+        match &opts.epd {
+            Some(file) => run_epd(file, &opts, &mut table),
+            None => run_single(&opts, &mut table).map(|_| true),
+        }
+    });
+    match result {
+        Ok(true) => (),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+}
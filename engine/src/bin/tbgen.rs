@@ -0,0 +1,23 @@
+use smogfish::syzygy::generate::generate;
+
+const USAGE: &str = "usage:
+    tbgen <dir> <table>...
+
+Writes the syzygy files of each table, like `KQvKR`, to <dir>. Tables are
+made in the order given, the ones captures and promotions lead to have to
+be in <dir> or come first.";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 || args.iter().any(|a| a == "-h" || a == "--help") {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    }
+    for name in &args[1..] {
+        if let Err(e) = generate(&args[0], name) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        println!("{}", name);
+    }
+}
@@ -0,0 +1,79 @@
+use smogfish::datagen::{Sample, RECORD_SIZE};
+use smogfish::eval::DEFAULT_PARAMS;
+use smogfish::tune::{params_to_rust, Tuner};
+
+const USAGE: &str = "usage: tune <data file> [--binary] [--epochs <n>] [--lr <rate>] [--output <file>]
+
+Fits the evaluation parameters to positions written by datagen, starting
+from the current ones, and writes them as the source of eval/params.rs
+to the output file or stdout.";
+
+fn number<T: std::str::FromStr>(s: String) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number '{}'", s))
+}
+
+fn run() -> Result<(), String> {
+    let (mut file, mut binary, mut epochs, mut lr, mut output) = (None, false, 500, 1.0, None);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--binary" => binary = true,
+            "--epochs" => epochs = number(value()?)?,
+            "--lr" => lr = number(value()?)?,
+            "--output" => output = Some(value()?),
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let file = file.ok_or(USAGE)?;
+
+    let mut tuner = Tuner::new(&DEFAULT_PARAMS);
+    if binary {
+        let bytes = std::fs::read(&file).map_err(|e| format!("{}: {}", file, e))?;
+        if bytes.len() % RECORD_SIZE != 0 {
+            return Err(format!("{} is not a multiple of {} bytes", file, RECORD_SIZE));
+        }
+        for (i, record) in bytes.chunks_exact(RECORD_SIZE).enumerate() {
+            let sample = Sample::from_bytes(record.try_into().unwrap()).map_err(|e| format!("record {}: {}", i, e))?;
+            tuner.add(&sample);
+        }
+    } else {
+        let content = std::fs::read_to_string(&file).map_err(|e| format!("{}: {}", file, e))?;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample = Sample::from_text(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            tuner.add(&sample);
+        }
+    }
+    if tuner.is_empty() {
+        return Err(format!("{} has no positions", file));
+    }
+
+    tuner.fit_k();
+    eprintln!("{} positions, k {:.3}, error {:.6}", tuner.len(), tuner.k, tuner.error());
+    for epoch in 1..=epochs {
+        tuner.step(lr);
+        if epoch % 50 == 0 || epoch == epochs {
+            eprintln!("epoch {}: error {:.6}", epoch, tuner.error());
+        }
+    }
+
+    let src = params_to_rust(&tuner.params());
+    match output {
+        Some(path) => std::fs::write(&path, src).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            print!("{}", src);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
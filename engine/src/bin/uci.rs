@@ -0,0 +1,12 @@
+use smogfish::uci::Uci;
+use std::io::{self, BufRead};
+
+fn main() -> io::Result<()> {
+    let mut uci = Uci::new(io::stdout());
+    for line in io::stdin().lock().lines() {
+        if !uci.handle(&line?)? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -1,19 +1,29 @@
 pub mod bitboard;
+pub mod builder;
 pub mod helper;
 pub mod king;
 pub mod knight;
 pub mod pawn;
 pub mod precompute;
+#[cfg(feature = "serde")]
+mod serialize;
 pub mod sliding_pieces;
+pub mod square;
+pub mod validate;
 
 use self::{
-    helper::{Color, GameState, PieceBoards, Position},
+    helper::{CastlingRights, Color, GameState, PieceBoards, Position},
     precompute::{ZOBRIST_HASH_TABLE, ZOBRIST_SPECIAL_KEYS},
 };
 use bitboard::BitBoard;
 use helper::Piece;
+use crate::eval::nnue::{Accumulator, Network};
+use std::fmt;
+use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialize::MoveRepr"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Move {
     pub from: Position,
     pub to: Position,
@@ -38,6 +48,20 @@ impl Move {
     }
 }
 
+/// long algebraic notation as used by UCI, e.g. `e2e4` or `e7e8q`
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        match self.flag {
+            5 => write!(f, "q"),
+            6 => write!(f, "r"),
+            7 => write!(f, "b"),
+            8 => write!(f, "n"),
+            _ => Ok(()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Board {
     pub game_state: GameState,
@@ -48,7 +72,8 @@ pub struct Board {
     /// is attacking. This bitboard ignores the king of the player whos turn
     /// it is
     check_mask: BitBoard,
-    king_attacker_count: u8,
+    /// pieces giving check to the player whos turn it is
+    pub(crate) king_attacker_count: u8,
     king_attacker_mask: BitBoard,
     king_attacker_block_mask: BitBoard,
     pinned_pieces: BitBoard,
@@ -69,12 +94,34 @@ pub struct Board {
     full_moves: u16,
     /// for threefold repetition, indexed by halfmoves
     zobrist_history: [u64; 101],
+    /// zobrist key of the position, updated incrementally whenever pieces
+    /// or flags change
+    hash: u64,
+    /// zobrist key of the pawns alone, for the pawn hash table of the evaluation
+    pawn_hash: u64,
+    /// hidden layer of the network, if the board is evaluated by one
+    accumulator: Option<Accumulator>,
     pub move_list: Vec<Move>,
 }
 
 impl Board {
     pub fn new(fen: &str) -> Self {
-        let mut b: Self = Board {
+        Self::from_fen(fen).unwrap()
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let mut b = Self::empty();
+        helper::load_board_from_fen(&mut b, fen)?;
+        Ok(b)
+    }
+
+    pub fn to_fen(&self) -> String {
+        helper::board_to_fen(self)
+    }
+
+    /// a board without any pieces, used as the base for setting up positions
+    fn empty() -> Self {
+        Board {
             pieces: Default::default(),
             game_state: GameState::Playing,
             white_total: BitBoard(0),
@@ -90,14 +137,51 @@ impl Board {
             half_moves: 0,
             full_moves: 0,
             zobrist_history: [0; 101],
+            hash: 0,
+            pawn_hash: 0,
+            accumulator: None,
             move_list: vec![],
-        };
-        helper::load_board_from_fen(&mut b, fen).unwrap();
-        return b;
+        }
+    }
+
+    /// computes everything that is derived from the pieces and flags, after
+    /// a position got set up. Needs exactly one king per side.
+    fn finish_setup(&mut self) {
+        self.hash = self.compute_zobrist_hash();
+        self.pawn_hash = self.compute_pawn_hash();
+        self.zobrist_history = [0; 101];
+        if (self.half_moves as usize) < self.zobrist_history.len() {
+            self.zobrist_history[self.half_moves as usize] = self.hash;
+        }
+        self.generate_total_bitboard(Color::White);
+        self.generate_total_bitboard(Color::Black);
+        self.generate_check_mask(!self.current_player());
+
+        self.generate_move_list();
+
+        self.game_state = GameState::Playing;
+        if self.move_list.is_empty() {
+            self.game_state = match (self.king_attacker_count, self.current_player()) {
+                (0, _) => GameState::Draw,
+                (_, Color::White) => GameState::BlackWins,
+                (_, Color::Black) => GameState::WhiteWins,
+            };
+        } else if self.half_moves >= 100 {
+            self.game_state = GameState::Draw;
+        }
     }
 
     fn set(&mut self, pos: Position, piece: Piece, color: Color) {
-        self.pieces[(color, piece)] += pos;
+        if !self.pieces[(color, piece)].has(pos) {
+            self.pieces[(color, piece)] += pos;
+            self.hash ^= ZOBRIST_HASH_TABLE[pos.0 as usize][piece as usize + 6 * color as usize];
+            if piece == Piece::Pawn {
+                self.pawn_hash ^= ZOBRIST_HASH_TABLE[pos.0 as usize][6 * color as usize];
+            }
+            if let Some(acc) = &mut self.accumulator {
+                acc.add(pos, piece, color);
+            }
+        }
     }
 
     #[inline]
@@ -127,6 +211,91 @@ impl Board {
         self.pieces[(color, piece)].has(idx)
     }
 
+    #[inline]
+    pub fn pieces(&self, color: Color, piece: Piece) -> BitBoard {
+        self.pieces[(color, piece)]
+    }
+
+    /// true if the side to move is in check
+    #[inline]
+    pub fn in_check(&self) -> bool {
+        self.king_attacker_count != 0
+    }
+
+    /// pieces that give check to the side to move
+    #[inline]
+    pub fn checkers(&self) -> BitBoard {
+        self.king_attacker_mask
+    }
+
+    #[inline]
+    pub fn occupied(&self) -> BitBoard {
+        self.white_total | self.black_total
+    }
+
+    #[inline]
+    pub fn color_occupancy(&self, color: Color) -> BitBoard {
+        match color {
+            Color::White => self.white_total,
+            Color::Black => self.black_total,
+        }
+    }
+
+    pub fn piece_at(&self, pos: impl Into<Position>) -> Option<(Piece, Color)> {
+        let pos = pos.into();
+        let color = if self.white_total.has(pos) {
+            Color::White
+        } else if self.black_total.has(pos) {
+            Color::Black
+        } else {
+            return None;
+        };
+        Piece::ALL_NONEMPTY
+            .into_iter()
+            .find(|p| self.pieces[(color, *p)].has(pos))
+            .map(|p| (p, color))
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        CastlingRights {
+            white_short: self.castle_white_short(),
+            white_long: self.castle_white_long(),
+            black_short: self.castle_black_short(),
+            black_long: self.castle_black_long(),
+        }
+    }
+
+    /// the square a pawn can move to when taking en passant. This is set
+    /// after every double pawn push, even if no pawn can take.
+    pub fn en_passant_square(&self) -> Option<Position> {
+        let file = self.get_en_passant();
+        if file > 7 {
+            return None;
+        }
+        match self.current_player() {
+            Color::White => Some(Position::new(5, file as u8)),
+            Color::Black => Some(Position::new(2, file as u8)),
+        }
+    }
+
+    /// half moves since the last capture or pawn move, for the fifty move rule
+    #[inline]
+    pub fn halfmove_clock(&self) -> u8 {
+        self.half_moves
+    }
+
+    /// true if the position was already on the board since the last
+    /// capture or pawn move
+    pub fn repeated(&self) -> bool {
+        let seen = (self.half_moves as usize).min(self.zobrist_history.len());
+        self.zobrist_history[..seen].contains(&self.hash)
+    }
+
+    #[inline]
+    pub fn fullmove_number(&self) -> u16 {
+        self.full_moves
+    }
+
     /// dont use in engine, only for showing
     /// the board, not really efficient
     pub fn get_by_idx(&self, idx: Position) -> (Piece, Color) {
@@ -147,11 +316,49 @@ impl Board {
         (Piece::Empty, Color::White)
     }
 
+    /// number of pieces on the board, kings included. Used to decide
+    /// if a position is small enough for endgame tablebases
+    #[inline]
+    pub fn piece_count(&self) -> u8 {
+        (self.white_total | self.black_total).count_set_bits()
+    }
+
+    /// tablebases do not know about castling, so positions where any side
+    /// can still castle can not be probed
+    #[inline]
+    pub fn has_castling_rights(&self) -> bool {
+        self.flags & 0b11110 != 0
+    }
+
     pub fn clear_bit(&mut self, pos: Position, piece: Piece, color: Color) {
-        self.pieces[(color, piece)] -= pos;
+        if self.pieces[(color, piece)].has(pos) {
+            self.pieces[(color, piece)] -= pos;
+            self.hash ^= ZOBRIST_HASH_TABLE[pos.0 as usize][piece as usize + 6 * color as usize];
+            if piece == Piece::Pawn {
+                self.pawn_hash ^= ZOBRIST_HASH_TABLE[pos.0 as usize][6 * color as usize];
+            }
+            if let Some(acc) = &mut self.accumulator {
+                acc.remove(pos, piece, color);
+            }
+        }
+    }
+
+    /// evaluates this board and every board made from it with the network,
+    /// or with the handcrafted evaluation for `None`
+    pub fn set_network(&mut self, net: Option<Arc<Network>>) {
+        self.accumulator = net.map(|net| Accumulator::new(net, self));
+    }
+
+    /// the hidden layer of the network, that is updated with every move
+    #[inline]
+    pub fn accumulator(&self) -> Option<&Accumulator> {
+        self.accumulator.as_ref()
     }
 
     fn set_color_to_move(&mut self, color: Color) {
+        if color != self.current_player() {
+            self.hash ^= ZOBRIST_SPECIAL_KEYS[0];
+        }
         if color == Color::White {
             self.flags &= !1;
         } else {
@@ -159,19 +366,17 @@ impl Board {
         }
     }
 
+    /// index of the castling right in `flags` and `ZOBRIST_SPECIAL_KEYS`
+    #[inline]
+    fn castling_right_idx(color: Color, is_long: bool) -> u16 {
+        1 + 2 * color as u16 + is_long as u16
+    }
+
     fn set_castling_right(&mut self, color: Color, is_long: bool) {
-        if color == Color::White {
-            if is_long {
-                self.flags |= 1 << 2;
-            } else {
-                self.flags |= 1 << 1;
-            }
-        } else {
-            if is_long {
-                self.flags |= 1 << 4;
-            } else {
-                self.flags |= 1 << 3;
-            }
+        let idx = Self::castling_right_idx(color, is_long);
+        if self.flags & (1 << idx) == 0 {
+            self.flags |= 1 << idx;
+            self.hash ^= ZOBRIST_SPECIAL_KEYS[idx as usize];
         }
     }
 
@@ -196,22 +401,21 @@ impl Board {
     }
 
     fn remove_castling_right(&mut self, color: Color, is_long: bool) {
-        if color == Color::White {
-            if is_long {
-                self.flags &= !(1 << 2);
-            } else {
-                self.flags &= !(1 << 1);
-            }
-        } else {
-            if is_long {
-                self.flags &= !(1 << 4);
-            } else {
-                self.flags &= !(1 << 3);
-            }
+        let idx = Self::castling_right_idx(color, is_long);
+        if self.flags & (1 << idx) != 0 {
+            self.flags &= !(1 << idx);
+            self.hash ^= ZOBRIST_SPECIAL_KEYS[idx as usize];
         }
     }
 
     fn set_en_passant(&mut self, pos: u16) {
+        let old = self.get_en_passant();
+        if old < 8 {
+            self.hash ^= ZOBRIST_SPECIAL_KEYS[5 + old as usize];
+        }
+        if pos < 8 {
+            self.hash ^= ZOBRIST_SPECIAL_KEYS[5 + pos as usize];
+        }
         self.flags &= !(15 << 5); // set the four bits to 0
         self.flags |= pos << 5; // set the pos to the four bits
     }
@@ -246,10 +450,38 @@ impl Board {
         if self.castle_black_long() {
             hash_value ^= ZOBRIST_SPECIAL_KEYS[4];
         }
+        let en_passant = self.get_en_passant();
+        if en_passant < 8 {
+            hash_value ^= ZOBRIST_SPECIAL_KEYS[5 + en_passant as usize];
+        }
+
+        hash_value
+    }
 
+    fn compute_pawn_hash(&self) -> u64 {
+        let mut hash_value = 0;
+        for color in [Color::White, Color::Black] {
+            for i in self.pieces[(color, Piece::Pawn)] {
+                hash_value ^= ZOBRIST_HASH_TABLE[i.0 as usize][6 * color as usize];
+            }
+        }
         hash_value
     }
 
+    /// zobrist key of the position, including the en passant file, so
+    /// positions with the same key also have the same legal moves
+    #[inline]
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// zobrist key of the pawns only, positions with the same key have the
+    /// same pawn structure
+    #[inline]
+    pub fn pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
     pub fn generate_move_list(&mut self) {
         self.move_list.clear();
         let side_to_play = self.current_player();
@@ -350,7 +582,15 @@ impl Board {
             self.clear_bit(mov.to, target_piece.0, target_piece.1);
         }
 
-        self.set(mov.to, p.0, p.1);
+        // if promotion, set new piece on target instead of the pawn
+        let placed_piece = match mov.flag {
+            5 => Piece::Queen,
+            6 => Piece::Rook,
+            7 => Piece::Bishop,
+            8 => Piece::Knight,
+            _ => p.0,
+        };
+        self.set(mov.to, placed_piece, p.1);
 
         match mov.flag {
             3 => {
@@ -379,10 +619,6 @@ impl Board {
                     self.remove_castling_right(Color::Black, true);
                 }
             }
-            5 => self.set(mov.to, Piece::Queen, p.1), // if promotion, set new piece on target instead of old one
-            6 => self.set(mov.to, Piece::Rook, p.1),
-            7 => self.set(mov.to, Piece::Bishop, p.1),
-            8 => self.set(mov.to, Piece::Knight, p.1),
             _ => (),
         }
 
@@ -427,17 +663,31 @@ impl Board {
             }
         }
 
+        let mut next_color_to_move = Color::Black;
+        if self.current_player() == Color::Black {
+            self.full_moves += 1;
+            next_color_to_move = Color::White;
+        }
+        self.set_color_to_move(next_color_to_move);
+        if mov.flag != 2 {
+            // if en passant didnt just get triggered, reset it
+            self.set_en_passant(15);
+        }
+
         if (p.0 != Piece::Pawn) && !move_is_capture {
-            self.half_moves += 1;
+            self.half_moves = self.half_moves.saturating_add(1);
 
             if self.half_moves >= 100 {
                 self.game_state = GameState::Draw;
             }
 
-            let hash = self.compute_zobrist_hash();
+            // a position set up with a high halfmove clock can go past the
+            // end of the history, those moves are not remembered
+            let hash = self.hash;
+            let seen = (self.half_moves as usize).min(self.zobrist_history.len());
             let mut repetition_count = 0;
-            for i in 0..self.half_moves {
-                if self.zobrist_history[i as usize] == hash {
+            for i in 0..seen {
+                if self.zobrist_history[i] == hash {
                     repetition_count += 1;
                     if repetition_count == 2 {
                         self.game_state = GameState::Draw;
@@ -445,7 +695,9 @@ impl Board {
                     }
                 }
             }
-            self.zobrist_history[self.half_moves as usize] = hash;
+            if let Some(entry) = self.zobrist_history.get_mut(self.half_moves as usize) {
+                *entry = hash;
+            }
         } else {
             self.half_moves = 0;
             self.zobrist_history = [0; 101];
@@ -454,21 +706,10 @@ impl Board {
                 // to the zobrist history
                 self.zobrist_history[0] = 0;
             } else {
-                self.zobrist_history[0] = self.compute_zobrist_hash();
+                self.zobrist_history[0] = self.hash;
             }
         }
 
-        let mut next_color_to_move = Color::Black;
-        if self.current_player() == Color::Black {
-            self.full_moves += 1;
-            next_color_to_move = Color::White;
-        }
-        self.set_color_to_move(next_color_to_move);
-        if mov.flag != 2 {
-            // if en passant didnt just get triggered, reset it
-            self.set_en_passant(15);
-        }
-
         self.generate_total_bitboard(side_to_play);
         if move_is_capture || mov.flag == 1 {
             self.generate_total_bitboard(next_color_to_move);
@@ -487,5 +728,10 @@ impl Board {
                 }
             }
         }
+
+        #[cfg(all(debug_assertions, feature = "validate"))]
+        if let Err(e) = self.validate() {
+            panic!("invalid board after {}: {}", mov, e);
+        }
     }
 }
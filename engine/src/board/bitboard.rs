@@ -1,14 +1,177 @@
 use super::helper::Position;
-use std::ops::{AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Mul, Not, SubAssign, Shr, Shl};
+use super::precompute::{BETWEEN, LINE};
+use super::square::Square;
+use std::fmt;
+use std::ops::{AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not, SubAssign, Shr, Shl};
 
 #[derive(PartialEq, Eq, PartialOrd, Clone, Copy, Debug, Default, Hash)]
 pub struct BitBoard(pub u64);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = {
+        use Direction::*;
+        [North, South, East, West, NorthEast, NorthWest, SouthEast, SouthWest]
+    };
+
+    /// (files, ranks) moved by one step in this direction
+    pub const fn offset(self) -> (i8, i8) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::South => (0, -1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::NorthWest => (-1, 1),
+            Direction::SouthEast => (1, -1),
+            Direction::SouthWest => (-1, -1),
+        }
+    }
+}
+
 impl BitBoard {
+    pub const EMPTY: BitBoard = BitBoard(0);
+    pub const FULL: BitBoard = BitBoard(u64::MAX);
+    pub const FILES: [BitBoard; 8] = {
+        let mut ret = [BitBoard(0); 8];
+        let mut i = 0;
+        while i < 8 {
+            ret[i] = BitBoard(0x0101010101010101 << i);
+            i += 1;
+        }
+        ret
+    };
+    pub const RANKS: [BitBoard; 8] = {
+        let mut ret = [BitBoard(0); 8];
+        let mut i = 0;
+        while i < 8 {
+            ret[i] = BitBoard(0xff << (8 * i));
+            i += 1;
+        }
+        ret
+    };
+    pub const LIGHT_SQUARES: BitBoard = BitBoard(0x55aa55aa55aa55aa);
+    pub const DARK_SQUARES: BitBoard = BitBoard(0xaa55aa55aa55aa55);
+
     #[inline(always)]
     pub fn has(self, index: Position) -> bool {
         (self & BitBoard(1 << index.0)) != BitBoard(0)
     }
+
+    #[inline(always)]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline(always)]
+    pub fn more_than_one(self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    /// lowest set square
+    #[inline(always)]
+    pub fn lsb(self) -> Option<Position> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Position(self.0.trailing_zeros() as u8))
+        }
+    }
+
+    /// highest set square
+    #[inline(always)]
+    pub fn msb(self) -> Option<Position> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Position(63 - self.0.leading_zeros() as u8))
+        }
+    }
+
+    #[inline(always)]
+    pub fn pop_lsb(&mut self) -> Option<Position> {
+        let ret = self.lsb();
+        self.0 &= self.0.wrapping_sub(1);
+        ret
+    }
+
+    #[inline(always)]
+    pub fn pop_msb(&mut self) -> Option<Position> {
+        let ret = self.msb();
+        if let Some(pos) = ret {
+            *self -= pos;
+        }
+        ret
+    }
+
+    /// moves every square one step into the direction, squares that would
+    /// wrap around to the other side of the board are dropped
+    #[inline]
+    pub fn shift(self, dir: Direction) -> BitBoard {
+        let not_a_mask = !Self::FILES[0].0;
+        let not_h_mask = !Self::FILES[7].0;
+        BitBoard(match dir {
+            Direction::North => self.0 << 8,
+            Direction::South => self.0 >> 8,
+            Direction::East => (self.0 << 1) & not_a_mask,
+            Direction::West => (self.0 >> 1) & not_h_mask,
+            Direction::NorthEast => (self.0 << 9) & not_a_mask,
+            Direction::NorthWest => (self.0 << 7) & not_h_mask,
+            Direction::SouthEast => (self.0 >> 7) & not_a_mask,
+            Direction::SouthWest => (self.0 >> 9) & not_h_mask,
+        })
+    }
+
+    /// the a1-h8 direction diagonal through the square
+    pub fn diagonal(sq: Square) -> BitBoard {
+        let main = 0x8040201008040201u64;
+        let d = sq.rank() as i8 - sq.file() as i8;
+        if d >= 0 {
+            BitBoard(main << (8 * d))
+        } else {
+            BitBoard(main >> (-8 * d))
+        }
+    }
+
+    /// the a8-h1 direction diagonal through the square
+    pub fn anti_diagonal(sq: Square) -> BitBoard {
+        let main = 0x0102040810204080u64;
+        let d = sq.rank() as i8 + sq.file() as i8 - 7;
+        if d >= 0 {
+            BitBoard(main << (8 * d))
+        } else {
+            BitBoard(main >> (-8 * d))
+        }
+    }
+
+    /// squares strictly between two aligned squares, empty if not aligned
+    #[inline(always)]
+    pub fn between(a: Square, b: Square) -> BitBoard {
+        BETWEEN[a as usize][b as usize]
+    }
+
+    /// the whole rank, file or diagonal through two squares, empty if not aligned
+    #[inline(always)]
+    pub fn line(a: Square, b: Square) -> BitBoard {
+        LINE[a as usize][b as usize]
+    }
+
+    /// iterates the set squares from a1 to h8
+    pub fn squares(self) -> impl Iterator<Item = Square> {
+        let mut bb = self;
+        std::iter::from_fn(move || bb.pop_lsb().and_then(|p| Square::from_index(p.0)))
+    }
 }
 impl AddAssign<Position> for BitBoard {
     #[inline(always)]
@@ -76,6 +239,21 @@ impl BitOrAssign for BitBoard {
     }
 }
 
+impl BitXor for BitBoard {
+    type Output = BitBoard;
+    #[inline]
+    fn bitxor(self, other: BitBoard) -> BitBoard {
+        BitBoard(self.0 ^ other.0)
+    }
+}
+
+impl BitXorAssign for BitBoard {
+    #[inline]
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
 impl Not for BitBoard {
     type Output = BitBoard;
     #[inline]
@@ -100,28 +278,27 @@ impl Shr<u8> for BitBoard {
     }
 }
 
-impl BitBoard {
-    pub fn print(&self) {
+/// one row per rank with rank 8 on top, `1` for set squares and `0` for the others
+impl fmt::Display for BitBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for row in (0..8).rev() {
             for col in 0..8 {
-                print!(
-                    "{} ",
-                    (*self & BitBoard(1 << row * 8 + col) != BitBoard(0)) as u8
-                );
+                write!(f, "{} ", self.has(Position::new(row, col)) as u8)?;
             }
-            println!();
+            writeln!(f)?;
         }
-        println!();
+        Ok(())
     }
+}
 
+impl BitBoard {
+    pub fn print(&self) {
+        println!("{}", self);
+    }
+
+    #[inline(always)]
     pub fn count_set_bits(&self) -> u8 {
-        let mut ret: u8 = 0;
-        let mut n = self.0;
-        while n != 0 {
-            n &= n - 1;
-            ret += 1;
-        }
-        ret
+        self.0.count_ones() as u8
     }
 }
 
@@ -0,0 +1,154 @@
+use super::helper::{CastlingRights, Color, Piece, Position};
+use super::validate::BoardError;
+use super::Board;
+
+/// sets up a position piece by piece. The position is only checked for
+/// legality when it gets turned into a `Board` with `build`.
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    squares: [Option<(Piece, Color)>; 64],
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Position>,
+    halfmove_clock: u8,
+    fullmove_number: u16,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardBuilder {
+    /// an empty board with white to move
+    pub fn new() -> Self {
+        BoardBuilder {
+            squares: [None; 64],
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// starts from an existing position
+    pub fn from_board(board: &Board) -> Self {
+        let mut builder = BoardBuilder {
+            squares: [None; 64],
+            side_to_move: board.current_player(),
+            castling_rights: board.castling_rights(),
+            en_passant: board.en_passant_square(),
+            halfmove_clock: board.halfmove_clock(),
+            fullmove_number: board.fullmove_number(),
+        };
+        for i in 0..64 {
+            builder.squares[i as usize] = board.piece_at(Position(i));
+        }
+        builder
+    }
+
+    pub fn piece_at(&self, pos: Position) -> Option<(Piece, Color)> {
+        self.squares[pos.0 as usize]
+    }
+
+    /// places a piece, replacing whatever was on the square before
+    pub fn set_piece(&mut self, pos: Position, piece: Piece, color: Color) -> &mut Self {
+        self.squares[pos.0 as usize] = match piece {
+            Piece::Empty => None,
+            _ => Some((piece, color)),
+        };
+        self
+    }
+
+    pub fn remove_piece(&mut self, pos: Position) -> &mut Self {
+        self.squares[pos.0 as usize] = None;
+        self
+    }
+
+    /// removes all pieces, but keeps the other settings
+    pub fn clear(&mut self) -> &mut Self {
+        self.squares = [None; 64];
+        self
+    }
+
+    pub fn side_to_move(&mut self, color: Color) -> &mut Self {
+        self.side_to_move = color;
+        self
+    }
+
+    pub fn castling_rights(&mut self, rights: CastlingRights) -> &mut Self {
+        self.castling_rights = rights;
+        self
+    }
+
+    /// the square behind the pawn that just moved two squares
+    pub fn en_passant(&mut self, square: Option<Position>) -> &mut Self {
+        self.en_passant = square;
+        self
+    }
+
+    pub fn halfmove_clock(&mut self, half_moves: u8) -> &mut Self {
+        self.halfmove_clock = half_moves;
+        self
+    }
+
+    pub fn fullmove_number(&mut self, full_moves: u16) -> &mut Self {
+        self.fullmove_number = full_moves;
+        self
+    }
+
+    /// checks the position and computes check masks and the move list
+    pub fn build(&self) -> Result<Board, BoardError> {
+        // these would make the move generation fail, so they have to be
+        // checked before the board gets finished
+        for color in [Color::White, Color::Black] {
+            let kings = self.squares.iter().filter(|s| **s == Some((Piece::King, color))).count();
+            if kings != 1 {
+                return Err(BoardError::KingCount(color, kings as u8));
+            }
+        }
+        for (i, square) in self.squares.iter().enumerate() {
+            let pos = Position(i as u8);
+            if matches!(square, Some((Piece::Pawn, _))) && (pos.rank() == 0 || pos.rank() == 7) {
+                return Err(BoardError::PawnOnBackRank(pos));
+            }
+        }
+
+        let mut board = Board::empty();
+        for (i, square) in self.squares.iter().enumerate() {
+            if let Some((piece, color)) = square {
+                board.set(Position(i as u8), *piece, *color);
+            }
+        }
+
+        board.set_color_to_move(self.side_to_move);
+        for color in [Color::White, Color::Black] {
+            for is_long in [false, true] {
+                if self.castling_rights.can_castle(color, is_long) {
+                    board.set_castling_right(color, is_long);
+                }
+            }
+        }
+        match self.en_passant {
+            Some(square) => {
+                let expected_rank = match self.side_to_move {
+                    Color::White => 5,
+                    Color::Black => 2,
+                };
+                if square.rank() != expected_rank {
+                    return Err(BoardError::EnPassant(square.file()));
+                }
+                board.set_en_passant(square.file() as u16);
+            }
+            None => board.set_en_passant(15),
+        }
+        board.half_moves = self.halfmove_clock;
+        board.full_moves = self.fullmove_number;
+
+        board.finish_setup();
+        board.validate()?;
+        Ok(board)
+    }
+}
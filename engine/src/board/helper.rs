@@ -1,6 +1,8 @@
 use super::{BitBoard, Board};
+use std::fmt;
 use std::ops::{Index, IndexMut, Not};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Color {
     White,
@@ -18,6 +20,7 @@ impl Not for Color {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Piece {
@@ -59,6 +62,8 @@ impl IndexMut<(Color, Piece)> for PieceBoards {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Position(pub u8);
 impl Position {
@@ -73,6 +78,64 @@ impl Position {
     }
 }
 
+/// a square from 0 (a1) to 63 (h8)
+impl TryFrom<u8> for Position {
+    type Error = String;
+    fn try_from(square: u8) -> Result<Self, Self::Error> {
+        match square {
+            0..=63 => Ok(Position(square)),
+            _ => Err(format!("square {} is not on the board", square)),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CastlingRights {
+    pub white_short: bool,
+    pub white_long: bool,
+    pub black_short: bool,
+    pub black_long: bool,
+}
+
+impl CastlingRights {
+    pub fn can_castle(self, color: Color, is_long: bool) -> bool {
+        match (color, is_long) {
+            (Color::White, false) => self.white_short,
+            (Color::White, true) => self.white_long,
+            (Color::Black, false) => self.black_short,
+            (Color::Black, true) => self.black_long,
+        }
+    }
+}
+
+/// castling rights as in a FEN string, e.g. `KQkq` or `-`
+impl fmt::Display for CastlingRights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == CastlingRights::default() {
+            return write!(f, "-");
+        }
+        for (right, c) in [
+            (self.white_short, 'K'),
+            (self.white_long, 'Q'),
+            (self.black_short, 'k'),
+            (self.black_long, 'q'),
+        ] {
+            if right {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy)]
 pub enum GameState {
     Playing,
@@ -103,6 +166,12 @@ pub fn load_board_from_fen(board: &mut Board, fen: &str) -> Result<(), String> {
             col += n as u8;
             continue;
         }
+        if c == '/' && row == 0 {
+            return Err("FEN parse error: too many ranks in group 1".to_string());
+        }
+        if c != '/' && col > 7 {
+            return Err("FEN parse error: too many squares in a rank in group 1".to_string());
+        }
         let pos = Position::new(row, col);
         #[rustfmt::skip] match c {
             '/' => {
@@ -147,7 +216,7 @@ pub fn load_board_from_fen(board: &mut Board, fen: &str) -> Result<(), String> {
 
     // fourth group: en passant
     if groups[3] != "-" {
-        let c = groups[3].chars().next().unwrap() as u32 - 97; // a -> 0; h -> 7
+        let c = (groups[3].chars().next().unwrap() as u32).wrapping_sub(97); // a -> 0; h -> 7
         if c > 7 {
             return Err("FEN parse error: illegal symbol in group 4".to_string());
         }
@@ -170,11 +239,60 @@ pub fn load_board_from_fen(board: &mut Board, fen: &str) -> Result<(), String> {
         Err(_) => return Err("FEN parse error: illegal symbol in group 6".to_string()),
     }
 
-    board.generate_total_bitboard(Color::White);
-    board.generate_total_bitboard(Color::Black);
-    board.generate_check_mask(!board.current_player());
+    for color in [Color::White, Color::Black] {
+        if board.pieces[(color, Piece::King)] == BitBoard(0) {
+            return Err(format!("FEN parse error: {:?} has no king", color));
+        }
+    }
 
-    board.generate_move_list();
+    board.finish_setup();
 
     return Ok(());
 }
+
+pub fn board_to_fen(board: &Board) -> String {
+    let mut fen = String::new();
+    for row in (0..8).rev() {
+        let mut empty = 0;
+        for col in 0..8 {
+            match board.piece_at(Position::new(row, col)) {
+                Some((piece, color)) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    let c = ['p', 'n', 'b', 'r', 'q', 'k'][piece as usize];
+                    fen.push(match color {
+                        Color::White => c.to_ascii_uppercase(),
+                        Color::Black => c,
+                    });
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if row > 0 {
+            fen.push('/');
+        }
+    }
+
+    let side = match board.current_player() {
+        Color::White => "w",
+        Color::Black => "b",
+    };
+    let en_passant = match board.en_passant_square() {
+        Some(pos) => pos.to_string(),
+        None => "-".to_string(),
+    };
+    format!(
+        "{} {} {} {} {} {}",
+        fen,
+        side,
+        board.castling_rights(),
+        en_passant,
+        board.halfmove_clock(),
+        board.fullmove_number()
+    )
+}
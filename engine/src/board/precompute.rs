@@ -9,7 +9,8 @@ pub const KING_PAWN_ATTACKS: [[BitBoard; 64]; 2] = king_pawn_attacks();
 /// 0: for short castle checks and pieces, 1: for long castle checks, 2: for long caslte_pieces
 pub const KING_CASTLE_CHECKS: [[BitBoard; 3]; 2] = king_castle_checks();
 pub const ZOBRIST_HASH_TABLE: [[u64; 12]; 64] = zobrist_hash_table();
-pub const ZOBRIST_SPECIAL_KEYS: [u64; 5] = zobrist_special_keys();
+/// 0: black to move, 1..5: castling rights, 5..13: en passant file
+pub const ZOBRIST_SPECIAL_KEYS: [u64; 13] = zobrist_special_keys();
 
 /// stupid for-range implemention because const_trait_impl and iter are not usuable yet.
 macro_rules! const_for {
@@ -150,15 +151,81 @@ const fn zobrist_hash_table() -> [[u64; 12]; 64] {
     ret
 }
 
-const fn zobrist_special_keys() -> [u64; 5] {
-    let mut ret = [0; 5];
+const fn zobrist_special_keys() -> [u64; 13] {
+    let mut ret = [0; 13];
     let mut x = 31415;
     const_for!(for _x in 0..80 {
         x = xorshift(x)
     });
-    const_for!(for i in 0..5 {
+    const_for!(for i in 0..13 {
         x = xorshift(x);
         ret[i] = x;
     });
     ret
 }
+
+/// squares strictly between two squares on a common rank, file or diagonal,
+/// empty if the squares are not aligned
+pub static BETWEEN: [[BitBoard; 64]; 64] = between();
+/// the whole line through two squares on a common rank, file or diagonal
+/// from edge to edge, empty if the squares are not aligned
+pub static LINE: [[BitBoard; 64]; 64] = line();
+
+const fn signum(x: i8) -> i8 {
+    if x > 0 {
+        1
+    } else if x < 0 {
+        -1
+    } else {
+        0
+    }
+}
+
+/// step in rank and file direction from a to b, if they are aligned
+const fn alignment(a: i8, b: i8) -> Option<(i8, i8)> {
+    let (dr, df) = (b / 8 - a / 8, b % 8 - a % 8);
+    if a == b || !(dr == 0 || df == 0 || dr == df || dr == -df) {
+        return None;
+    }
+    Some((signum(dr), signum(df)))
+}
+
+const fn between() -> [[BitBoard; 64]; 64] {
+    let mut ret = [[BitBoard(0); 64]; 64];
+    const_for!(for a in 0..64 {
+        const_for!(for b in 0..64 {
+            if let Some((dr, df)) = alignment(a, b) {
+                let (mut r, mut f) = (a / 8 + dr, a % 8 + df);
+                while r * 8 + f != b {
+                    ret[a as usize][b as usize].0 |= 1 << (r * 8 + f);
+                    r += dr;
+                    f += df;
+                }
+            }
+        })
+    });
+    ret
+}
+
+const fn line() -> [[BitBoard; 64]; 64] {
+    let mut ret = [[BitBoard(0); 64]; 64];
+    const_for!(for a in 0..64 {
+        const_for!(for b in 0..64 {
+            if let Some((dr, df)) = alignment(a, b) {
+                let mut mask = 1 << a;
+                const_for!(for dir in [1, -1] {
+                    {
+                        let (mut r, mut f) = (a / 8 + dr * dir, a % 8 + df * dir);
+                        while r >= 0 && r < 8 && f >= 0 && f < 8 {
+                            mask |= 1 << (r * 8 + f);
+                            r += dr * dir;
+                            f += df * dir;
+                        }
+                    }
+                });
+                ret[a as usize][b as usize].0 = mask;
+            }
+        })
+    });
+    ret
+}
@@ -0,0 +1,78 @@
+use super::helper::{GameState, Position};
+use super::{Board, Move};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// a board is stored as its FEN plus the zobrist keys of the positions
+/// since the last capture or pawn move, so repetitions are still detected
+/// after loading it again. Whether the game is over follows from those.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Board")]
+struct BoardRepr {
+    fen: String,
+    history: Vec<u64>,
+}
+
+/// the number of history entries stored for the board, the last one being
+/// the position itself
+fn history_len(board: &Board) -> usize {
+    (board.half_moves as usize + 1).min(board.zobrist_history.len())
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardRepr {
+            fen: self.to_fen(),
+            history: self.zobrist_history[..history_len(self)].to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BoardRepr::deserialize(deserializer)?;
+        let mut board = Board::from_fen(&repr.fen).map_err(D::Error::custom)?;
+        let len = history_len(&board);
+        if repr.history.len() != len {
+            return Err(D::Error::custom(format!(
+                "history has {} positions, the halfmove clock needs {}",
+                repr.history.len(),
+                len
+            )));
+        }
+        // past the 50 move rule the position itself is not remembered, and
+        // right after a double pawn push it is stored as 0
+        let last = repr.history[len - 1];
+        if board.half_moves <= 100 && last != board.hash && !(board.half_moves == 0 && last == 0) {
+            return Err(D::Error::custom("history does not end with the position of the FEN"));
+        }
+        board.zobrist_history[..len].copy_from_slice(&repr.history);
+
+        // mate, stalemate and the 50 move rule are known from the FEN alone
+        let repetitions = repr.history[..len - 1].iter().filter(|&&h| h == board.hash).count();
+        if matches!(board.game_state, GameState::Playing) && repetitions >= 2 {
+            board.game_state = GameState::Draw;
+        }
+        Ok(board)
+    }
+}
+
+/// a move as it is read, before its squares and flag are checked
+#[derive(Deserialize)]
+#[serde(rename = "Move")]
+pub(super) struct MoveRepr {
+    from: Position,
+    to: Position,
+    flag: u8,
+}
+
+impl TryFrom<MoveRepr> for Move {
+    type Error = String;
+    fn try_from(repr: MoveRepr) -> Result<Self, Self::Error> {
+        match repr.flag {
+            0..=8 => Ok(Move::new_with_flags(repr.from, repr.to, repr.flag)),
+            flag => Err(format!("unknown move flag {}", flag)),
+        }
+    }
+}
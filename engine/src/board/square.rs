@@ -0,0 +1,232 @@
+use super::bitboard::BitBoard;
+use super::helper::Position;
+use std::fmt;
+use std::str::FromStr;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A = 0,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    First = 0,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl File {
+    pub const ALL: [File; 8] = {
+        use File::*;
+        [A, B, C, D, E, F, G, H]
+    };
+
+    pub const fn from_index(idx: u8) -> Option<File> {
+        if idx < 8 {
+            Some(Self::ALL[idx as usize])
+        } else {
+            None
+        }
+    }
+
+    /// the file `n` files to the right (east), if it is on the board
+    pub const fn offset(self, n: i8) -> Option<File> {
+        let idx = self as i8 + n;
+        if idx < 0 {
+            return None;
+        }
+        Self::from_index(idx as u8)
+    }
+
+    pub const fn bitboard(self) -> BitBoard {
+        BitBoard(0x0101010101010101 << self as u8)
+    }
+
+    pub const fn to_char(self) -> char {
+        (b'a' + self as u8) as char
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = {
+        use Rank::*;
+        [First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth]
+    };
+
+    pub const fn from_index(idx: u8) -> Option<Rank> {
+        if idx < 8 {
+            Some(Self::ALL[idx as usize])
+        } else {
+            None
+        }
+    }
+
+    /// the rank `n` ranks up (north), if it is on the board
+    pub const fn offset(self, n: i8) -> Option<Rank> {
+        let idx = self as i8 + n;
+        if idx < 0 {
+            return None;
+        }
+        Self::from_index(idx as u8)
+    }
+
+    pub const fn bitboard(self) -> BitBoard {
+        BitBoard(0xff << (8 * self as u8))
+    }
+
+    pub const fn to_char(self) -> char {
+        (b'1' + self as u8) as char
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// a square on the board, a1 = 0, b1 = 1, ..., h8 = 63. Same indexing as
+/// `Position`, but can never be off the board
+#[rustfmt::skip]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Square {
+    A1 = 0, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = {
+        use Square::*;
+        [
+            A1, B1, C1, D1, E1, F1, G1, H1,
+            A2, B2, C2, D2, E2, F2, G2, H2,
+            A3, B3, C3, D3, E3, F3, G3, H3,
+            A4, B4, C4, D4, E4, F4, G4, H4,
+            A5, B5, C5, D5, E5, F5, G5, H5,
+            A6, B6, C6, D6, E6, F6, G6, H6,
+            A7, B7, C7, D7, E7, F7, G7, H7,
+            A8, B8, C8, D8, E8, F8, G8, H8,
+        ]
+    };
+
+    pub const fn new(file: File, rank: Rank) -> Square {
+        Self::ALL[rank as usize * 8 + file as usize]
+    }
+
+    pub const fn from_index(idx: u8) -> Option<Square> {
+        if idx < 64 {
+            Some(Self::ALL[idx as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub const fn file(self) -> File {
+        File::ALL[self as usize % 8]
+    }
+
+    pub const fn rank(self) -> Rank {
+        Rank::ALL[self as usize / 8]
+    }
+
+    /// the square `files` to the right and `ranks` up, if it is on the board
+    pub const fn offset(self, files: i8, ranks: i8) -> Option<Square> {
+        match (self.file().offset(files), self.rank().offset(ranks)) {
+            (Some(f), Some(r)) => Some(Square::new(f, r)),
+            _ => None,
+        }
+    }
+
+    pub const fn up(self) -> Option<Square> {
+        self.offset(0, 1)
+    }
+
+    pub const fn down(self) -> Option<Square> {
+        self.offset(0, -1)
+    }
+
+    pub const fn left(self) -> Option<Square> {
+        self.offset(-1, 0)
+    }
+
+    pub const fn right(self) -> Option<Square> {
+        self.offset(1, 0)
+    }
+
+    pub const fn bitboard(self) -> BitBoard {
+        BitBoard(1 << self as u8)
+    }
+}
+
+impl From<Square> for Position {
+    #[inline(always)]
+    fn from(sq: Square) -> Self {
+        Position(sq as u8)
+    }
+}
+
+impl TryFrom<Position> for Square {
+    type Error = String;
+    fn try_from(pos: Position) -> Result<Self, Self::Error> {
+        Square::from_index(pos.0).ok_or(format!("{} is not a square on the board", pos.0))
+    }
+}
+
+impl From<Square> for BitBoard {
+    #[inline(always)]
+    fn from(sq: Square) -> Self {
+        sq.bitboard()
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(f @ 'a'..='h'), Some(r @ '1'..='8'), None) => Ok(Square::new(
+                File::ALL[f as usize - 'a' as usize],
+                Rank::ALL[r as usize - '1' as usize],
+            )),
+            _ => Err(format!("'{}' is not a square", s)),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::bitboard::BitBoard;
+use super::helper::{Color, Piece, Position};
+use super::precompute::{KING_ATTACKS, KNIGHT_ATTACKS};
+use super::{sliding_pieces, Board};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// a square is occupied by more than one piece
+    OverlappingPieces(Position),
+    /// the total bitboard of a color does not match its piece bitboards
+    TotalMismatch(Color),
+    /// a side does not have exactly one king
+    KingCount(Color, u8),
+    PawnOnBackRank(Position),
+    /// the side that is not to move is in check
+    OpponentInCheck,
+    /// a castling right is set, but king or rook are not on their start squares
+    CastlingRights { color: Color, is_long: bool },
+    /// the en passant file is set, but no pawn can have just moved two squares there
+    EnPassant(u8),
+    /// check mask, king attackers or pins differ from freshly computed ones
+    StaleAttackInfo,
+    HashMismatch { cached: u64, computed: u64 },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::OverlappingPieces(pos) => write!(f, "more than one piece on {}", pos),
+            BoardError::TotalMismatch(color) => write!(f, "total bitboard of {:?} does not match its pieces", color),
+            BoardError::KingCount(color, n) => write!(f, "{:?} has {} kings", color, n),
+            BoardError::PawnOnBackRank(pos) => write!(f, "pawn on back rank square {}", pos),
+            BoardError::OpponentInCheck => write!(f, "the side not to move is in check"),
+            BoardError::CastlingRights { color, is_long } => write!(
+                f,
+                "{:?} can castle {}, but king or rook have moved",
+                color,
+                if *is_long { "long" } else { "short" }
+            ),
+            BoardError::EnPassant(file) => write!(f, "en passant on file {} is not possible", file),
+            BoardError::StaleAttackInfo => write!(f, "check and pin information is out of date"),
+            BoardError::HashMismatch { cached, computed } => {
+                write!(f, "cached hash {:#018x} differs from computed hash {:#018x}", cached, computed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl Board {
+    /// every square attacked by `color`, sliding pieces see through the enemy king
+    fn attacks_of(&self, color: Color) -> BitBoard {
+        let mut attacks = BitBoard(0);
+        for i in self.pieces[(color, Piece::Knight)] {
+            attacks |= KNIGHT_ATTACKS[i.0 as usize];
+        }
+        for i in self.pieces[(color, Piece::King)] {
+            attacks |= KING_ATTACKS[i.0 as usize];
+        }
+
+        let not_a_mask = 0xfefefefefefefefe;
+        let not_h_mask = 0x7f7f7f7f7f7f7f7f;
+        let pawns = self.pieces[(color, Piece::Pawn)];
+        attacks |= match color {
+            Color::White => ((pawns << 9) & not_a_mask) | ((pawns << 7) & not_h_mask),
+            Color::Black => ((pawns >> 7) & not_a_mask) | ((pawns >> 9) & not_h_mask),
+        };
+
+        let queens = self.pieces[(color, Piece::Queen)];
+        attacks |= sliding_pieces::get_all_attacks_rook(self, self.pieces[(color, Piece::Rook)] | queens, color);
+        attacks |= sliding_pieces::get_all_attacks_bishop(self, self.pieces[(color, Piece::Bishop)] | queens, color);
+        attacks
+    }
+
+    /// checks that all the redundant state of the board agrees with itself
+    pub fn validate(&self) -> Result<(), BoardError> {
+        let mut occupied = BitBoard(0);
+        for color in [Color::White, Color::Black] {
+            let mut total = BitBoard(0);
+            for p in Piece::ALL_NONEMPTY {
+                let pieces = self.pieces[(color, p)];
+                if let Some(pos) = (occupied & pieces).into_iter().next() {
+                    return Err(BoardError::OverlappingPieces(pos));
+                }
+                occupied |= pieces;
+                total |= pieces;
+            }
+
+            let cached_total = match color {
+                Color::White => self.white_total,
+                Color::Black => self.black_total,
+            };
+            if cached_total != total {
+                return Err(BoardError::TotalMismatch(color));
+            }
+
+            let kings = self.pieces[(color, Piece::King)].count_set_bits();
+            if kings != 1 {
+                return Err(BoardError::KingCount(color, kings));
+            }
+
+            let back_ranks = BitBoard(0xff000000000000ff);
+            if let Some(pos) = (self.pieces[(color, Piece::Pawn)] & back_ranks).into_iter().next() {
+                return Err(BoardError::PawnOnBackRank(pos));
+            }
+        }
+
+        let side_to_move = self.current_player();
+        if self.attacks_of(side_to_move) & self.pieces[(!side_to_move, Piece::King)] != BitBoard(0) {
+            return Err(BoardError::OpponentInCheck);
+        }
+
+        for (color, rank) in [(Color::White, 0), (Color::Black, 7)] {
+            for (is_long, rook_file) in [(false, 7), (true, 0)] {
+                let has_right = self.flags & (1 << Self::castling_right_idx(color, is_long)) != 0;
+                if has_right
+                    && !(self.pieces[(color, Piece::King)].has(Position::new(rank, 4))
+                        && self.pieces[(color, Piece::Rook)].has(Position::new(rank, rook_file)))
+                {
+                    return Err(BoardError::CastlingRights { color, is_long });
+                }
+            }
+        }
+
+        let en_passant = self.get_en_passant();
+        if en_passant != 15 {
+            let file = en_passant as u8;
+            // ranks of the pawn that just moved and the two squares it passed
+            let (pawn_rank, passed_ranks) = match side_to_move {
+                Color::White => (4, [5, 6]),
+                Color::Black => (3, [2, 1]),
+            };
+            if file > 7
+                || !self.pieces[(!side_to_move, Piece::Pawn)].has(Position::new(pawn_rank, file))
+                || passed_ranks.iter().any(|r| occupied.has(Position::new(*r, file)))
+            {
+                return Err(BoardError::EnPassant(file));
+            }
+        }
+
+        let mut fresh = self.clone();
+        fresh.generate_check_mask(!side_to_move);
+        if fresh.check_mask != self.check_mask
+            || fresh.king_attacker_count != self.king_attacker_count
+            || fresh.king_attacker_mask != self.king_attacker_mask
+            || fresh.pinned_pieces != self.pinned_pieces
+            || fresh.en_passant_pinned_piece != self.en_passant_pinned_piece
+        {
+            return Err(BoardError::StaleAttackInfo);
+        }
+
+        let computed = self.compute_zobrist_hash();
+        if computed != self.hash {
+            return Err(BoardError::HashMismatch {
+                cached: self.hash,
+                computed,
+            });
+        }
+        let computed = self.compute_pawn_hash();
+        if computed != self.pawn_hash {
+            return Err(BoardError::HashMismatch {
+                cached: self.pawn_hash,
+                computed,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+mod keys;
+
+use crate::board::helper::{Color, Piece, Position};
+use crate::board::precompute::KING_PAWN_ATTACKS;
+use crate::board::{Board, Move};
+use crate::rng::Rng;
+use keys::RANDOM64;
+
+/// the key of a position in polyglot opening books. The en passant file
+/// only counts if a pawn can actually take.
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for color in [Color::White, Color::Black] {
+        for piece in Piece::ALL_NONEMPTY {
+            // black pawn, white pawn, black knight, ...
+            let kind = 2 * piece as usize + (color == Color::White) as usize;
+            for pos in board.pieces(color, piece) {
+                key ^= RANDOM64[64 * kind + pos.0 as usize];
+            }
+        }
+    }
+
+    let rights = board.castling_rights();
+    for (i, right) in [rights.white_short, rights.white_long, rights.black_short, rights.black_long]
+        .into_iter()
+        .enumerate()
+    {
+        if right {
+            key ^= RANDOM64[768 + i];
+        }
+    }
+
+    let us = board.current_player();
+    if let Some(ep) = board.en_passant_square() {
+        let takers = KING_PAWN_ATTACKS[!us as usize][ep.0 as usize] & board.pieces(us, Piece::Pawn);
+        if !takers.is_empty() {
+            key ^= RANDOM64[772 + ep.file() as usize];
+        }
+    }
+    if us == Color::White {
+        key ^= RANDOM64[780];
+    }
+    key
+}
+
+/// one 16 byte record of a polyglot book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    /// from, to and promotion piece packed as in the polyglot format
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// the legal move the entry stands for. Castling is stored as the king
+    /// taking its own rook.
+    pub fn to_move(&self, board: &Board) -> Option<Move> {
+        let to = Position(self.raw_move as u8 & 63);
+        let from = Position((self.raw_move >> 6) as u8 & 63);
+        let flag = match self.raw_move >> 12 & 7 {
+            0 => None,
+            1 => Some(8),
+            2 => Some(7),
+            3 => Some(6),
+            4 => Some(5),
+            _ => return None,
+        };
+
+        let us = board.current_player();
+        let to = match board.piece_is_type(from, us, Piece::King) && board.piece_is_type(to, us, Piece::Rook) {
+            true if to.file() > from.file() => Position::new(to.rank(), 6),
+            true => Position::new(to.rank(), 2),
+            false => to,
+        };
+        board
+            .move_list
+            .iter()
+            .find(|m| {
+                m.from == from
+                    && m.to == to
+                    && match m.flag {
+                        5..=8 => Some(m.flag) == flag,
+                        _ => flag.is_none(),
+                    }
+            })
+            .cloned()
+    }
+}
+
+/// an opening book in the polyglot format
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    /// sorted by key
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.len().is_multiple_of(16) {
+            return Err("the length of a book file has to be a multiple of 16".to_string());
+        }
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(16)
+            .map(|c| BookEntry {
+                key: u64::from_be_bytes(c[0..8].try_into().unwrap()),
+                raw_move: u16::from_be_bytes(c[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(c[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(c[12..16].try_into().unwrap()),
+            })
+            .collect();
+        // books are sorted already, but a stable sort keeps them as they are
+        entries.sort_by_key(|e| e.key);
+        Ok(Book { entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 * self.entries.len());
+        for e in &self.entries {
+            bytes.extend_from_slice(&e.key.to_be_bytes());
+            bytes.extend_from_slice(&e.raw_move.to_be_bytes());
+            bytes.extend_from_slice(&e.weight.to_be_bytes());
+            bytes.extend_from_slice(&e.learn.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the entries for a position, in the order of the file
+    pub fn entries(&self, board: &Board) -> &[BookEntry] {
+        let key = polyglot_key(board);
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = self.entries.partition_point(|e| e.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// the legal book moves for a position with their weights, entries with
+    /// illegal moves are left out
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        self.entries(board)
+            .iter()
+            .filter_map(|e| Some((e.to_move(board)?, e.weight)))
+            .collect()
+    }
+
+    /// a random book move, more likely the higher its weight. Moves with
+    /// weight 0 are only played if all have weight 0.
+    pub fn pick(&self, board: &Board, rng: &mut Rng) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: usize = moves.iter().map(|(_, w)| *w as usize).sum();
+        if total == 0 {
+            return (!moves.is_empty()).then(|| moves[rng.below(moves.len())].0.clone());
+        }
+        let mut r = rng.below(total);
+        for (m, w) in moves {
+            if r < w as usize {
+                return Some(m);
+            }
+            r -= w as usize;
+        }
+        unreachable!()
+    }
+}
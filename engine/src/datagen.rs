@@ -0,0 +1,221 @@
+use crate::board::builder::BoardBuilder;
+use crate::board::helper::{CastlingRights, Color, Piece, Position};
+use crate::board::square::Square;
+use crate::board::Board;
+use crate::game::{Game, GameResult, Termination};
+use crate::rng::Rng;
+use crate::search::{is_capture, is_mate_score, search, SearchLimits};
+
+#[derive(Debug, Clone)]
+pub struct DatagenConfig {
+    /// search depth of every move
+    pub depth: u8,
+    /// random moves at the start of every game
+    pub random_plies: u8,
+    /// games longer than this are adjudicated as a draw
+    pub max_plies: u16,
+    pub seed: u64,
+}
+
+impl Default for DatagenConfig {
+    fn default() -> Self {
+        DatagenConfig {
+            depth: 4,
+            random_plies: 8,
+            max_plies: 400,
+            seed: 0,
+        }
+    }
+}
+
+/// a position labeled with the search score and the outcome of its game
+#[derive(Clone)]
+pub struct Sample {
+    pub board: Board,
+    /// centipawns from white's view
+    pub score: i16,
+    pub result: GameResult,
+}
+
+/// size of a sample in the binary format
+pub const RECORD_SIZE: usize = 32;
+
+fn result_value(result: GameResult) -> &'static str {
+    match result {
+        GameResult::WhiteWins => "1.0",
+        GameResult::BlackWins => "0.0",
+        _ => "0.5",
+    }
+}
+
+impl Sample {
+    /// `<fen> | <score> | <result>` with the result 1.0, 0.5 or 0.0 from white's view
+    pub fn to_text(&self) -> String {
+        format!("{} | {} | {}", self.board.to_fen(), self.score, result_value(self.result))
+    }
+
+    pub fn from_text(line: &str) -> Result<Self, String> {
+        let mut parts = line.split('|').map(str::trim);
+        let (Some(fen), Some(score), Some(result), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("'{}' is not <fen> | <score> | <result>", line));
+        };
+        let result = match result {
+            "1.0" | "1" => GameResult::WhiteWins,
+            "0.5" => GameResult::Draw,
+            "0.0" | "0" => GameResult::BlackWins,
+            _ => return Err(format!("invalid result '{}'", result)),
+        };
+        Ok(Sample {
+            board: Board::from_fen(fen)?,
+            score: score.parse().map_err(|_| format!("invalid score '{}'", score))?,
+            result,
+        })
+    }
+
+    /// packs the sample into 32 bytes: occupancy (8), a nibble per occupied
+    /// square (16), side to move and en passant square (1), castling rights
+    /// (1), halfmove clock (1), fullmove number (2), score (2) and result
+    /// (1), all little endian
+    pub fn to_bytes(&self) -> [u8; RECORD_SIZE] {
+        let board = &self.board;
+        let mut bytes = [0; RECORD_SIZE];
+        let mut occupancy = 0u64;
+        let mut nibbles = [0u8; 16];
+        let mut count = 0;
+        for sq in Square::ALL {
+            if let Some((piece, color)) = board.piece_at(sq) {
+                occupancy |= 1 << sq.index();
+                let nibble = piece as u8 | if color == Color::Black { 8 } else { 0 };
+                nibbles[count / 2] |= nibble << (4 * (count % 2));
+                count += 1;
+            }
+        }
+        bytes[0..8].copy_from_slice(&occupancy.to_le_bytes());
+        bytes[8..24].copy_from_slice(&nibbles);
+
+        let ep = board.en_passant_square().map(|p| p.0).unwrap_or(64);
+        bytes[24] = ep | if board.current_player() == Color::Black { 0x80 } else { 0 };
+        let rights = board.castling_rights();
+        bytes[25] = rights.white_short as u8
+            | (rights.white_long as u8) << 1
+            | (rights.black_short as u8) << 2
+            | (rights.black_long as u8) << 3;
+        bytes[26] = board.halfmove_clock();
+        bytes[27..29].copy_from_slice(&board.fullmove_number().to_le_bytes());
+        bytes[29..31].copy_from_slice(&self.score.to_le_bytes());
+        bytes[31] = match self.result {
+            GameResult::BlackWins => 0,
+            GameResult::WhiteWins => 2,
+            _ => 1,
+        };
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; RECORD_SIZE]) -> Result<Self, String> {
+        let mut builder = BoardBuilder::new();
+        let mut occupancy = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        if occupancy.count_ones() > 32 {
+            return Err("more than 32 pieces".to_string());
+        }
+        let mut count = 0;
+        while occupancy != 0 {
+            let idx = occupancy.trailing_zeros() as u8;
+            occupancy &= occupancy - 1;
+            let nibble = (bytes[8 + count / 2] >> (4 * (count % 2))) & 0xf;
+            let piece = *Piece::ALL_NONEMPTY
+                .get((nibble & 7) as usize)
+                .ok_or(format!("invalid piece {}", nibble))?;
+            let color = if nibble & 8 != 0 { Color::Black } else { Color::White };
+            builder.set_piece(Position(idx), piece, color);
+            count += 1;
+        }
+
+        let ep = bytes[24] & 0x7f;
+        builder
+            .side_to_move(if bytes[24] & 0x80 != 0 { Color::Black } else { Color::White })
+            .en_passant(if ep < 64 { Some(Position(ep)) } else { None })
+            .castling_rights(CastlingRights {
+                white_short: bytes[25] & 1 != 0,
+                white_long: bytes[25] & 2 != 0,
+                black_short: bytes[25] & 4 != 0,
+                black_long: bytes[25] & 8 != 0,
+            })
+            .halfmove_clock(bytes[26])
+            .fullmove_number(u16::from_le_bytes([bytes[27], bytes[28]]));
+        let result = match bytes[31] {
+            0 => GameResult::BlackWins,
+            1 => GameResult::Draw,
+            2 => GameResult::WhiteWins,
+            r => return Err(format!("invalid result {}", r)),
+        };
+        Ok(Sample {
+            board: builder.build().map_err(|e| e.to_string())?,
+            score: i16::from_le_bytes([bytes[29], bytes[30]]),
+            result,
+        })
+    }
+}
+
+/// plays random moves from the start position until a position is reached
+/// in which the game is not over yet
+fn random_opening(rng: &mut Rng, plies: u8) -> Game {
+    loop {
+        let mut game = Game::default();
+        for _ in 0..plies {
+            let moves = &game.board().move_list;
+            if moves.is_empty() {
+                break;
+            }
+            let m = moves[rng.below(moves.len())].clone();
+            game.push(&m).unwrap();
+            if game.result() != GameResult::Ongoing {
+                break;
+            }
+        }
+        if game.result() == GameResult::Ongoing {
+            return game;
+        }
+    }
+}
+
+/// plays one self-play game and returns its quiet positions. The game only
+/// depends on the seed of the config and `index`.
+pub fn play_game(config: &DatagenConfig, index: u64) -> Vec<Sample> {
+    let mut rng = Rng::new(config.seed ^ index.wrapping_mul(0x2545_f491_4f6c_dd1d));
+    let mut game = random_opening(&mut rng, config.random_plies);
+    let limits = SearchLimits {
+        depth: Some(config.depth),
+        ..Default::default()
+    };
+
+    let mut positions = vec![];
+    while game.result() == GameResult::Ongoing {
+        if game.ply() >= config.max_plies as usize {
+            game.set_result(GameResult::Draw, Termination::Adjudication);
+            break;
+        }
+        let board = game.board();
+        let result = search(board, &limits);
+        let Some(best) = result.best_move else {
+            break;
+        };
+
+        // positions in check or with a tactical best move are too noisy to label
+        let quiet = !board.in_check() && !is_capture(board, &best) && best.flag < 5;
+        if quiet && !is_mate_score(result.score) {
+            let score = match board.current_player() {
+                Color::White => result.score,
+                Color::Black => -result.score,
+            };
+            positions.push((board.clone(), score as i16));
+        }
+        game.push(&best).unwrap();
+    }
+
+    let result = game.result();
+    positions
+        .into_iter()
+        .map(|(board, score)| Sample { board, score, result })
+        .collect()
+}
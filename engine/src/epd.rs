@@ -0,0 +1,133 @@
+use crate::board::{Board, Move};
+use crate::notation::parse_san;
+
+/// a position in extended position description, the four FEN fields
+/// followed by operations like `bm Qg6; id "WAC.001";`
+pub struct Epd {
+    pub board: Board,
+    /// operations in the order they appear in, e.g. `("bm", ["Qg6"])`
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+/// splits the operations part into opcodes and their operands. Operands
+/// are separated by whitespace, strings in double quotes are one operand
+fn parse_operations(s: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut operations = vec![];
+    let mut tokens: Vec<String> = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if tokens.is_empty() {
+                    return Err("EPD parse error: empty operation".to_string());
+                }
+                let opcode = tokens.remove(0);
+                operations.push((opcode, std::mem::take(&mut tokens)));
+            }
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("EPD parse error: unterminated string".to_string()),
+                    }
+                }
+                tokens.push(token);
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == ';' {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    // the last operation is allowed to miss its semicolon
+    if !tokens.is_empty() {
+        let opcode = tokens.remove(0);
+        operations.push((opcode, tokens));
+    }
+    Ok(operations)
+}
+
+impl Epd {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let mut fields = vec![];
+        let mut rest = line;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err("EPD parse error: an EPD line has to start with 4 FEN fields".to_string());
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        let operations = parse_operations(rest)?;
+        let counter = |opcode: &str, default: &str| {
+            operations
+                .iter()
+                .find(|(o, _)| o == opcode)
+                .and_then(|(_, operands)| operands.first().cloned())
+                .unwrap_or(default.to_string())
+        };
+        let fen = format!("{} {} {}", fields.join(" "), counter("hmvc", "0"), counter("fmvn", "1"));
+        let board = Board::from_fen(&fen)?;
+
+        Ok(Epd { board, operations })
+    }
+
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(o, _)| o == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id")
+            .and_then(|operands| operands.first())
+            .map(|s| s.as_str())
+    }
+
+    fn moves(&self, opcode: &str) -> Result<Vec<Move>, String> {
+        self.operation(opcode)
+            .unwrap_or(&[])
+            .iter()
+            .map(|san| parse_san(&self.board, san))
+            .collect()
+    }
+
+    /// moves of the `bm` operation
+    pub fn best_moves(&self) -> Result<Vec<Move>, String> {
+        self.moves("bm")
+    }
+
+    /// moves of the `am` operation
+    pub fn avoid_moves(&self) -> Result<Vec<Move>, String> {
+        self.moves("am")
+    }
+
+    /// checks a move against the `bm` and `am` operations. Returns `None`
+    /// if the position has neither of them.
+    pub fn is_solved_by(&self, m: &Move) -> Result<Option<bool>, String> {
+        let (best, avoid) = (self.best_moves()?, self.avoid_moves()?);
+        if best.is_empty() && avoid.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            (best.is_empty() || best.contains(m)) && !avoid.contains(m),
+        ))
+    }
+}
@@ -0,0 +1,406 @@
+use crate::board::bitboard::BitBoard;
+use crate::board::helper::{Color, Piece};
+use crate::board::square::Square;
+use crate::board::Board;
+use std::cell::RefCell;
+use std::ops::{Add, AddAssign, Mul, Neg, Range, Sub, SubAssign};
+
+pub mod king;
+pub mod nnue;
+pub mod params;
+pub mod pawns;
+pub mod pieces;
+pub mod trace;
+
+use king::king_terms;
+pub use params::DEFAULT_PARAMS;
+use pawns::{passed_king_terms, pawn_terms, PawnEntry, PawnTable};
+use pieces::piece_terms;
+
+/// centipawn values, indexed by `Piece`. Used for move ordering, the
+/// evaluation uses `EvalParams::material`
+pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+
+/// phase contribution of every piece, all pieces on the board add up to `MAX_PHASE`
+pub const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+/// a middlegame and an endgame value, mixed by the phase of the game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct S(pub i32, pub i32);
+
+impl S {
+    /// the value for a phase between 0 (endgame) and `MAX_PHASE` (opening)
+    pub fn taper(self, phase: i32) -> i32 {
+        (self.0 * phase + self.1 * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for S {
+    type Output = S;
+    fn add(self, rhs: S) -> S {
+        S(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub for S {
+    type Output = S;
+    fn sub(self, rhs: S) -> S {
+        S(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Neg for S {
+    type Output = S;
+    fn neg(self) -> S {
+        S(-self.0, -self.1)
+    }
+}
+
+impl Mul<i32> for S {
+    type Output = S;
+    fn mul(self, rhs: i32) -> S {
+        S(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl AddAssign for S {
+    fn add_assign(&mut self, rhs: S) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for S {
+    fn sub_assign(&mut self, rhs: S) {
+        *self = *self - rhs;
+    }
+}
+
+/// every tunable value of the evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    /// indexed by `Piece`
+    pub material: [S; 6],
+    /// piece-square tables from white's view, indexed by `Piece` and square with a1 = 0
+    pub pst: [[S; 64]; 6],
+    /// for every pawn with another pawn of its color in front of it
+    pub doubled_pawn: S,
+    /// no pawns of the same color on the neighbouring files
+    pub isolated_pawn: S,
+    /// can not be defended by pawns and can not advance safely
+    pub backward_pawn: S,
+    /// defended by or next to a pawn of its color, indexed by relative rank
+    pub connected_pawn: [S; 8],
+    /// no enemy pawns in front on the same or neighbouring files, indexed by relative rank
+    pub passed_pawn: [S; 8],
+    /// per square between the own king and the square in front of a passed pawn
+    pub passed_own_king: S,
+    /// per square between the enemy king and the square in front of a passed pawn
+    pub passed_enemy_king: S,
+    /// by the number of squares a piece attacks, that are neither occupied by
+    /// its own pieces nor attacked by enemy pawns
+    pub knight_mobility: [S; 9],
+    pub bishop_mobility: [S; 14],
+    pub rook_mobility: [S; 15],
+    pub queen_mobility: [S; 28],
+    /// per attacked square next to the enemy king, indexed by the attacking `Piece`
+    pub king_attack: [S; 5],
+    /// per pawn one and two ranks in front of the king, on its file or the neighbouring ones
+    pub pawn_shield: [S; 2],
+    /// per file next to the king without any pawns
+    pub king_open_file: S,
+    /// per file next to the king with only enemy pawns
+    pub king_semi_open_file: S,
+    pub bishop_pair: S,
+    pub rook_open_file: S,
+    pub rook_semi_open_file: S,
+    pub rook_seventh_rank: S,
+}
+
+impl EvalParams {
+    // indices of the values in `to_vec`
+    pub const DOUBLED_PAWN: usize = 6 + 6 * 64;
+    pub const ISOLATED_PAWN: usize = Self::DOUBLED_PAWN + 1;
+    pub const BACKWARD_PAWN: usize = Self::DOUBLED_PAWN + 2;
+    pub const CONNECTED_PAWN: usize = Self::DOUBLED_PAWN + 3;
+    pub const PASSED_PAWN: usize = Self::CONNECTED_PAWN + 8;
+    pub const PASSED_OWN_KING: usize = Self::PASSED_PAWN + 8;
+    pub const PASSED_ENEMY_KING: usize = Self::PASSED_OWN_KING + 1;
+    pub const KNIGHT_MOBILITY: usize = Self::PASSED_ENEMY_KING + 1;
+    pub const BISHOP_MOBILITY: usize = Self::KNIGHT_MOBILITY + 9;
+    pub const ROOK_MOBILITY: usize = Self::BISHOP_MOBILITY + 14;
+    pub const QUEEN_MOBILITY: usize = Self::ROOK_MOBILITY + 15;
+    pub const KING_ATTACK: usize = Self::QUEEN_MOBILITY + 28;
+    pub const PAWN_SHIELD: usize = Self::KING_ATTACK + 5;
+    pub const KING_OPEN_FILE: usize = Self::PAWN_SHIELD + 2;
+    pub const KING_SEMI_OPEN_FILE: usize = Self::KING_OPEN_FILE + 1;
+    pub const BISHOP_PAIR: usize = Self::KING_SEMI_OPEN_FILE + 1;
+    pub const ROOK_OPEN_FILE: usize = Self::BISHOP_PAIR + 1;
+    pub const ROOK_SEMI_OPEN_FILE: usize = Self::ROOK_OPEN_FILE + 1;
+    pub const ROOK_SEVENTH_RANK: usize = Self::ROOK_SEMI_OPEN_FILE + 1;
+    /// number of values in `to_vec`
+    pub const LEN: usize = Self::ROOK_SEVENTH_RANK + 1;
+
+    /// name of every evaluation term and the indices of its values
+    pub const TERMS: [(&'static str, Range<usize>); 20] = [
+        ("material", 0..6),
+        ("piece-square tables", 6..Self::DOUBLED_PAWN),
+        ("doubled pawns", Self::DOUBLED_PAWN..Self::ISOLATED_PAWN),
+        ("isolated pawns", Self::ISOLATED_PAWN..Self::BACKWARD_PAWN),
+        ("backward pawns", Self::BACKWARD_PAWN..Self::CONNECTED_PAWN),
+        ("connected pawns", Self::CONNECTED_PAWN..Self::PASSED_PAWN),
+        ("passed pawns", Self::PASSED_PAWN..Self::PASSED_OWN_KING),
+        (
+            "passed pawn king distance",
+            Self::PASSED_OWN_KING..Self::KNIGHT_MOBILITY,
+        ),
+        (
+            "knight mobility",
+            Self::KNIGHT_MOBILITY..Self::BISHOP_MOBILITY,
+        ),
+        (
+            "bishop mobility",
+            Self::BISHOP_MOBILITY..Self::ROOK_MOBILITY,
+        ),
+        ("rook mobility", Self::ROOK_MOBILITY..Self::QUEEN_MOBILITY),
+        ("queen mobility", Self::QUEEN_MOBILITY..Self::KING_ATTACK),
+        ("king attacks", Self::KING_ATTACK..Self::PAWN_SHIELD),
+        ("pawn shield", Self::PAWN_SHIELD..Self::KING_OPEN_FILE),
+        (
+            "king open files",
+            Self::KING_OPEN_FILE..Self::KING_SEMI_OPEN_FILE,
+        ),
+        (
+            "king semi-open files",
+            Self::KING_SEMI_OPEN_FILE..Self::BISHOP_PAIR,
+        ),
+        ("bishop pair", Self::BISHOP_PAIR..Self::ROOK_OPEN_FILE),
+        (
+            "rooks on open files",
+            Self::ROOK_OPEN_FILE..Self::ROOK_SEMI_OPEN_FILE,
+        ),
+        (
+            "rooks on semi-open files",
+            Self::ROOK_SEMI_OPEN_FILE..Self::ROOK_SEVENTH_RANK,
+        ),
+        (
+            "rooks on the seventh rank",
+            Self::ROOK_SEVENTH_RANK..Self::LEN,
+        ),
+    ];
+
+    /// the value with the given index in `to_vec`
+    pub fn get(&self, i: usize) -> S {
+        match i {
+            0..6 => self.material[i],
+            6..Self::DOUBLED_PAWN => self.pst[(i - 6) / 64][(i - 6) % 64],
+            Self::DOUBLED_PAWN => self.doubled_pawn,
+            Self::ISOLATED_PAWN => self.isolated_pawn,
+            Self::BACKWARD_PAWN => self.backward_pawn,
+            Self::CONNECTED_PAWN..Self::PASSED_PAWN => {
+                self.connected_pawn[i - Self::CONNECTED_PAWN]
+            }
+            Self::PASSED_PAWN..Self::PASSED_OWN_KING => self.passed_pawn[i - Self::PASSED_PAWN],
+            Self::PASSED_OWN_KING => self.passed_own_king,
+            Self::PASSED_ENEMY_KING => self.passed_enemy_king,
+            Self::KNIGHT_MOBILITY..Self::BISHOP_MOBILITY => {
+                self.knight_mobility[i - Self::KNIGHT_MOBILITY]
+            }
+            Self::BISHOP_MOBILITY..Self::ROOK_MOBILITY => {
+                self.bishop_mobility[i - Self::BISHOP_MOBILITY]
+            }
+            Self::ROOK_MOBILITY..Self::QUEEN_MOBILITY => {
+                self.rook_mobility[i - Self::ROOK_MOBILITY]
+            }
+            Self::QUEEN_MOBILITY..Self::KING_ATTACK => {
+                self.queen_mobility[i - Self::QUEEN_MOBILITY]
+            }
+            Self::KING_ATTACK..Self::PAWN_SHIELD => self.king_attack[i - Self::KING_ATTACK],
+            Self::PAWN_SHIELD..Self::KING_OPEN_FILE => self.pawn_shield[i - Self::PAWN_SHIELD],
+            Self::KING_OPEN_FILE => self.king_open_file,
+            Self::KING_SEMI_OPEN_FILE => self.king_semi_open_file,
+            Self::BISHOP_PAIR => self.bishop_pair,
+            Self::ROOK_OPEN_FILE => self.rook_open_file,
+            Self::ROOK_SEMI_OPEN_FILE => self.rook_semi_open_file,
+            Self::ROOK_SEVENTH_RANK => self.rook_seventh_rank,
+            _ => panic!("there is no parameter {}", i),
+        }
+    }
+
+    fn get_mut(&mut self, i: usize) -> &mut S {
+        match i {
+            0..6 => &mut self.material[i],
+            6..Self::DOUBLED_PAWN => &mut self.pst[(i - 6) / 64][(i - 6) % 64],
+            Self::DOUBLED_PAWN => &mut self.doubled_pawn,
+            Self::ISOLATED_PAWN => &mut self.isolated_pawn,
+            Self::BACKWARD_PAWN => &mut self.backward_pawn,
+            Self::CONNECTED_PAWN..Self::PASSED_PAWN => {
+                &mut self.connected_pawn[i - Self::CONNECTED_PAWN]
+            }
+            Self::PASSED_PAWN..Self::PASSED_OWN_KING => {
+                &mut self.passed_pawn[i - Self::PASSED_PAWN]
+            }
+            Self::PASSED_OWN_KING => &mut self.passed_own_king,
+            Self::PASSED_ENEMY_KING => &mut self.passed_enemy_king,
+            Self::KNIGHT_MOBILITY..Self::BISHOP_MOBILITY => {
+                &mut self.knight_mobility[i - Self::KNIGHT_MOBILITY]
+            }
+            Self::BISHOP_MOBILITY..Self::ROOK_MOBILITY => {
+                &mut self.bishop_mobility[i - Self::BISHOP_MOBILITY]
+            }
+            Self::ROOK_MOBILITY..Self::QUEEN_MOBILITY => {
+                &mut self.rook_mobility[i - Self::ROOK_MOBILITY]
+            }
+            Self::QUEEN_MOBILITY..Self::KING_ATTACK => {
+                &mut self.queen_mobility[i - Self::QUEEN_MOBILITY]
+            }
+            Self::KING_ATTACK..Self::PAWN_SHIELD => &mut self.king_attack[i - Self::KING_ATTACK],
+            Self::PAWN_SHIELD..Self::KING_OPEN_FILE => &mut self.pawn_shield[i - Self::PAWN_SHIELD],
+            Self::KING_OPEN_FILE => &mut self.king_open_file,
+            Self::KING_SEMI_OPEN_FILE => &mut self.king_semi_open_file,
+            Self::BISHOP_PAIR => &mut self.bishop_pair,
+            Self::ROOK_OPEN_FILE => &mut self.rook_open_file,
+            Self::ROOK_SEMI_OPEN_FILE => &mut self.rook_semi_open_file,
+            Self::ROOK_SEVENTH_RANK => &mut self.rook_seventh_rank,
+            _ => panic!("there is no parameter {}", i),
+        }
+    }
+
+    /// all values in a fixed order, the indices of `coefficients` refer to it
+    pub fn to_vec(&self) -> Vec<S> {
+        (0..Self::LEN).map(|i| self.get(i)).collect()
+    }
+
+    pub fn from_slice(values: &[S]) -> Self {
+        assert_eq!(values.len(), Self::LEN);
+        let mut params = DEFAULT_PARAMS;
+        for (i, value) in values.iter().enumerate() {
+            *params.get_mut(i) = *value;
+        }
+        params
+    }
+}
+
+/// 0 for pawn endgames up to `MAX_PHASE` with all pieces on the board
+pub fn phase(board: &Board) -> i32 {
+    let mut phase = 0;
+    for p in Piece::ALL_NONEMPTY {
+        let count = board.pieces(Color::White, p).count_set_bits()
+            + board.pieces(Color::Black, p).count_set_bits();
+        phase += PHASE_WEIGHTS[p as usize] * count as i32;
+    }
+    phase.min(MAX_PHASE)
+}
+
+/// the square as seen by `color`, black's pieces use the mirrored tables
+#[inline]
+fn relative(sq: Square, color: Color) -> usize {
+    match color {
+        Color::White => sq.index() as usize,
+        Color::Black => sq.index() as usize ^ 56,
+    }
+}
+
+/// calls `add` with the index of every parameter that counts in the
+/// position and how often, positive for white and negative for black
+fn walk_terms(board: &Board, mut add: impl FnMut(usize, i32)) {
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        for p in Piece::ALL_NONEMPTY {
+            for sq in board.pieces(color, p).squares() {
+                add(p as usize, sign);
+                add(6 + p as usize * 64 + relative(sq, color), sign);
+            }
+        }
+    }
+    let passed = pawn_terms(board, &mut add);
+    passed_king_terms(board, passed, &mut add);
+    piece_terms(board, &mut add);
+    king_terms(board, &mut add);
+}
+
+/// how often every parameter counts for white minus how often for black.
+/// The evaluation from white's view is the sum of these coefficients times
+/// the tapered parameters.
+pub fn coefficients(board: &Board) -> Vec<(usize, i32)> {
+    let mut coefs = vec![0; EvalParams::LEN];
+    walk_terms(board, |i, c| coefs[i] += c);
+    coefs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, c)| *c != 0)
+        .collect()
+}
+
+/// pawn structure scores of recently seen positions, one table per thread
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+thread_local! {
+    static PAWN_TABLE: RefCell<PawnTable> = RefCell::new(PawnTable::new(PAWN_TABLE_SIZE));
+}
+
+fn pawn_entry(board: &Board, params: &EvalParams) -> PawnEntry {
+    let mut score = S::default();
+    let passed = pawn_terms(board, |i, c| score += params.get(i) * c);
+    PawnEntry {
+        key: board.pawn_hash(),
+        score,
+        passed,
+    }
+}
+
+/// white's score from everything but the pawn structure
+fn evaluate_rest(board: &Board, params: &EvalParams, passed: [BitBoard; 2]) -> S {
+    let mut score = S::default();
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        for p in Piece::ALL_NONEMPTY {
+            for sq in board.pieces(color, p).squares() {
+                score += (params.material[p as usize]
+                    + params.pst[p as usize][relative(sq, color)])
+                    * sign;
+            }
+        }
+    }
+    let mut add = |i, c| score += params.get(i) * c;
+    passed_king_terms(board, passed, &mut add);
+    piece_terms(board, &mut add);
+    king_terms(board, &mut add);
+    score
+}
+
+fn side_to_move_view(board: &Board, score: S) -> i32 {
+    let score = score.taper(phase(board));
+    match board.current_player() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// static evaluation in centipawns with the given parameters, from the view
+/// of the side to move
+pub fn evaluate_with(board: &Board, params: &EvalParams) -> i32 {
+    let pawns = pawn_entry(board, params);
+    side_to_move_view(
+        board,
+        pawns.score + evaluate_rest(board, params, pawns.passed),
+    )
+}
+
+/// static evaluation in centipawns, from the view of the side to move. Uses
+/// the network if one is set on the board, see `Board::set_network`.
+pub fn evaluate(board: &Board) -> i32 {
+    if let Some(acc) = board.accumulator() {
+        return acc.evaluate(board.current_player());
+    }
+    let pawns = PAWN_TABLE.with_borrow_mut(|table| match table.probe(board.pawn_hash()) {
+        Some(entry) => *entry,
+        None => {
+            let entry = pawn_entry(board, &DEFAULT_PARAMS);
+            table.store(entry);
+            entry
+        }
+    });
+    side_to_move_view(
+        board,
+        pawns.score + evaluate_rest(board, &DEFAULT_PARAMS, pawns.passed),
+    )
+}
@@ -0,0 +1,49 @@
+use super::pieces::piece_attacks;
+use super::EvalParams;
+use crate::board::bitboard::BitBoard;
+use crate::board::helper::{Color, Piece};
+use crate::board::precompute::KING_ATTACKS;
+use crate::board::Board;
+
+/// calls `add` with the index of every king safety parameter and how often
+/// it counts, positive for white and negative for black
+pub fn king_terms(board: &Board, mut add: impl FnMut(usize, i32)) {
+    let pawns = board.pieces(Color::White, Piece::Pawn) | board.pieces(Color::Black, Piece::Pawn);
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        let Some(king) = board.pieces(color, Piece::King).squares().next() else {
+            continue;
+        };
+        let own_pawns = board.pieces(color, Piece::Pawn);
+
+        // attacks of the enemy on the squares around the king count against it
+        let zone = KING_ATTACKS[king.index() as usize] | king.bitboard();
+        for piece in [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            for sq in board.pieces(!color, piece).squares() {
+                let attacked = (piece_attacks(board, piece, !color, sq) & zone).count_set_bits() as i32;
+                if attacked > 0 {
+                    add(EvalParams::KING_ATTACK + piece as usize, -sign * attacked);
+                }
+            }
+        }
+
+        let file = king.file() as usize;
+        for f in file.saturating_sub(1)..=(file + 1).min(7) {
+            let file = BitBoard::FILES[f];
+            if (pawns & file).is_empty() {
+                add(EvalParams::KING_OPEN_FILE, sign);
+            } else if (own_pawns & file).is_empty() {
+                add(EvalParams::KING_SEMI_OPEN_FILE, sign);
+            }
+
+            for ahead in 1..=2 {
+                let rank = match color {
+                    Color::White => king.rank() as i32 + ahead,
+                    Color::Black => king.rank() as i32 - ahead,
+                };
+                if (0..8).contains(&rank) && !(own_pawns & file & BitBoard::RANKS[rank as usize]).is_empty() {
+                    add(EvalParams::PAWN_SHIELD + ahead as usize - 1, sign);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::board::helper::{Color, Piece, Position};
+use crate::board::Board;
+use std::sync::Arc;
+
+/// quantization of the hidden layer, activations are clipped to `0..=QA`
+pub const QA: i32 = 255;
+/// quantization of the output weights
+pub const QB: i32 = 64;
+/// the network predicts a win probability, this turns it into centipawns
+pub const SCALE: i32 = 400;
+
+const MAGIC: &[u8; 4] = b"SNN1";
+/// pieces of both colors on every square
+const INPUTS: usize = 2 * 6 * 64;
+/// the largest hidden layer a network may have. The accumulator has room
+/// for it in place, so copying a board does not allocate.
+pub const MAX_HIDDEN: usize = 512;
+
+/// a network with 768 inputs per perspective, one hidden layer that is
+/// shared by both perspectives and a single output.
+///
+/// The weights file is little endian: the magic `SNN1`, the hidden size as
+/// u32, the input weights as i16 (input major), the hidden biases as i16,
+/// the output weights as i16 (side to move first) and the output bias as i32.
+///
+/// Sums that do not fit wrap around, in the accumulator as i16 and in the
+/// output as i32, so every network gives the same result on every path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    hidden: usize,
+    input_weights: Vec<i16>,
+    hidden_bias: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+/// reads little endian values off the front of a byte slice
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        if self.0.len() < N {
+            return Err("the network file is too short".to_string());
+        }
+        let (head, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn i16s(&mut self, n: usize) -> Result<Vec<i16>, String> {
+        (0..n).map(|_| Ok(i16::from_le_bytes(self.take()?))).collect()
+    }
+}
+
+impl Network {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader(bytes);
+        if &r.take::<4>()? != MAGIC {
+            return Err("not a network file".to_string());
+        }
+        let hidden = u32::from_le_bytes(r.take()?) as usize;
+        if hidden == 0 || hidden > MAX_HIDDEN {
+            return Err(format!("unsupported hidden layer size {}", hidden));
+        }
+        let net = Network {
+            hidden,
+            input_weights: r.i16s(INPUTS * hidden)?,
+            hidden_bias: r.i16s(hidden)?,
+            output_weights: r.i16s(2 * hidden)?,
+            output_bias: i32::from_le_bytes(r.take()?),
+        };
+        if !r.0.is_empty() {
+            return Err("the network file is too long".to_string());
+        }
+        Ok(net)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&(self.hidden as u32).to_le_bytes());
+        for w in self.input_weights.iter().chain(&self.hidden_bias).chain(&self.output_weights) {
+            bytes.extend_from_slice(&w.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    /// input index of a piece, seen from `perspective`. Black sees the board
+    /// mirrored, so both perspectives share the weights.
+    #[inline]
+    fn input(perspective: Color, pos: Position, piece: Piece, color: Color) -> usize {
+        let (side, square) = match perspective {
+            Color::White => (color as usize, pos.0 as usize),
+            Color::Black => (!color as usize, pos.0 as usize ^ 56),
+        };
+        side * 384 + piece as usize * 64 + square
+    }
+
+    fn weights(&self, input: usize) -> &[i16] {
+        &self.input_weights[input * self.hidden..(input + 1) * self.hidden]
+    }
+
+    /// evaluation in centipawns from the hidden layers of the side to move
+    /// and its opponent. Uses avx2 where the cpu has it, the result is the
+    /// same as `output_scalar`.
+    pub fn output(&self, us: &[i16], them: &[i16]) -> i32 {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            let (ours, theirs) = self.output_weights.split_at(self.hidden);
+            // SAFETY: the cpu supports avx2
+            return self.scale(unsafe { simd::dot(us, ours).wrapping_add(simd::dot(them, theirs)) });
+        }
+        self.output_scalar(us, them)
+    }
+
+    pub fn output_scalar(&self, us: &[i16], them: &[i16]) -> i32 {
+        let (ours, theirs) = self.output_weights.split_at(self.hidden);
+        self.scale(dot_scalar(us, ours).wrapping_add(dot_scalar(them, theirs)))
+    }
+
+    #[inline]
+    fn scale(&self, sum: i32) -> i32 {
+        (sum.wrapping_add(self.output_bias) as i64 * SCALE as i64 / (QA * QB) as i64) as i32
+    }
+}
+
+/// clipped relu of the accumulator times the weights
+#[inline]
+fn dot_scalar(values: &[i16], weights: &[i16]) -> i32 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(&v, &w)| (v as i32).clamp(0, QA) * w as i32)
+        .fold(0, i32::wrapping_add)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use super::{dot_scalar, QA};
+    use std::arch::x86_64::*;
+
+    /// `dot_scalar` 16 values at a time
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot(values: &[i16], weights: &[i16]) -> i32 {
+        let chunks = values.len() / 16;
+        let (zero, max) = (_mm256_setzero_si256(), _mm256_set1_epi16(QA as i16));
+        let mut sum = _mm256_setzero_si256();
+        for i in 0..chunks {
+            let v = _mm256_loadu_si256(values.as_ptr().add(i * 16) as *const __m256i);
+            let w = _mm256_loadu_si256(weights.as_ptr().add(i * 16) as *const __m256i);
+            let v = _mm256_min_epi16(_mm256_max_epi16(v, zero), max);
+            // pairs of products fit into i32, as activations are at most QA
+            sum = _mm256_add_epi32(sum, _mm256_madd_epi16(v, w));
+        }
+        let mut lanes = [0i32; 8];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum);
+        let rest = dot_scalar(&values[chunks * 16..], &weights[chunks * 16..]);
+        lanes.iter().fold(rest, |sum, &lane| sum.wrapping_add(lane))
+    }
+}
+
+/// the hidden layer of both perspectives, kept up to date while pieces are
+/// added to and removed from the board
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    net: Arc<Network>,
+    /// indexed by the perspective, only the first `hidden_size` values are used
+    values: [[i16; MAX_HIDDEN]; 2],
+}
+
+impl Accumulator {
+    /// computes the accumulator of the board from scratch
+    pub fn new(net: Arc<Network>, board: &Board) -> Self {
+        let mut values = [[0; MAX_HIDDEN]; 2];
+        for v in &mut values {
+            v[..net.hidden].copy_from_slice(&net.hidden_bias);
+        }
+        let mut acc = Accumulator { values, net };
+        for color in [Color::White, Color::Black] {
+            for piece in Piece::ALL_NONEMPTY {
+                for pos in board.pieces(color, piece) {
+                    acc.add(pos, piece, color);
+                }
+            }
+        }
+        acc
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.net
+    }
+
+    pub fn values(&self, perspective: Color) -> &[i16] {
+        &self.values[perspective as usize][..self.net.hidden]
+    }
+
+    #[inline]
+    pub fn add(&mut self, pos: Position, piece: Piece, color: Color) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = self.net.weights(Network::input(perspective, pos, piece, color));
+            for (v, w) in self.values[perspective as usize].iter_mut().zip(weights) {
+                *v = v.wrapping_add(*w);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, pos: Position, piece: Piece, color: Color) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = self.net.weights(Network::input(perspective, pos, piece, color));
+            for (v, w) in self.values[perspective as usize].iter_mut().zip(weights) {
+                *v = v.wrapping_sub(*w);
+            }
+        }
+    }
+
+    /// evaluation in centipawns from the view of `side_to_move`
+    pub fn evaluate(&self, side_to_move: Color) -> i32 {
+        self.net.output(self.values(side_to_move), self.values(!side_to_move))
+    }
+}
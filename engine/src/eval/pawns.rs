@@ -0,0 +1,159 @@
+use super::{EvalParams, S};
+use crate::board::bitboard::BitBoard;
+use crate::board::helper::{Color, Piece};
+use crate::board::precompute::KING_PAWN_ATTACKS;
+use crate::board::square::Square;
+use crate::board::Board;
+
+/// the pawn structure score of a position, from white's view
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PawnEntry {
+    /// `Board::pawn_hash` of the position
+    pub key: u64,
+    pub score: S,
+    /// passed pawns, indexed by `Color`
+    pub passed: [BitBoard; 2],
+}
+
+/// caches pawn structure scores by the pawn hash. Empty slots have key 0,
+/// which is the key of positions without pawns, whose entry is all zero.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl PawnTable {
+    /// a table with `size` entries, rounded up to a power of two
+    pub fn new(size: usize) -> Self {
+        PawnTable {
+            entries: vec![PawnEntry::default(); size.next_power_of_two()],
+        }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&PawnEntry> {
+        let entry = &self.entries[self.slot(key)];
+        (entry.key == key).then_some(entry)
+    }
+
+    /// stores the entry, replacing whatever was in its slot
+    pub fn store(&mut self, entry: PawnEntry) {
+        let slot = self.slot(entry.key);
+        self.entries[slot] = entry;
+    }
+}
+
+/// every square on a rank in front of `sq` from the view of `color`
+fn ahead(sq: Square, color: Color) -> BitBoard {
+    let rank = sq.rank() as u32;
+    match color {
+        Color::White => BitBoard(u64::MAX.checked_shl(8 * (rank + 1)).unwrap_or(0)),
+        Color::Black => BitBoard(u64::MAX.checked_shr(8 * (8 - rank)).unwrap_or(0)),
+    }
+}
+
+fn neighbour_files(sq: Square) -> BitBoard {
+    let file = sq.file() as usize;
+    let mut files = BitBoard(0);
+    if file > 0 {
+        files |= BitBoard::FILES[file - 1];
+    }
+    if file < 7 {
+        files |= BitBoard::FILES[file + 1];
+    }
+    files
+}
+
+fn relative_rank(sq: Square, color: Color) -> usize {
+    match color {
+        Color::White => sq.rank() as usize,
+        Color::Black => 7 - sq.rank() as usize,
+    }
+}
+
+/// the square a pawn of `color` moves to
+fn stop_square(sq: Square, color: Color) -> Option<Square> {
+    match color {
+        Color::White => sq.up(),
+        Color::Black => sq.down(),
+    }
+}
+
+fn distance(a: Square, b: Square) -> i32 {
+    let files = (a.file() as i32 - b.file() as i32).abs();
+    let ranks = (a.rank() as i32 - b.rank() as i32).abs();
+    files.max(ranks)
+}
+
+/// calls `add` with the index of every pawn structure parameter and how
+/// often it counts, positive for white and negative for black. Returns the
+/// passed pawns of both colors. Only depends on the pawns, so the result
+/// can be cached by the pawn hash.
+pub fn pawn_terms(board: &Board, mut add: impl FnMut(usize, i32)) -> [BitBoard; 2] {
+    let mut passed = [BitBoard(0); 2];
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        let own = board.pieces(color, Piece::Pawn);
+        let enemy = board.pieces(!color, Piece::Pawn);
+        for sq in own.squares() {
+            let file = BitBoard::FILES[sq.file() as usize];
+            let neighbours = neighbour_files(sq);
+            let front = ahead(sq, color);
+            let rank = relative_rank(sq, color);
+
+            let doubled = !(own & file & front).is_empty();
+            if doubled {
+                add(EvalParams::DOUBLED_PAWN, sign);
+            }
+
+            let isolated = (own & neighbours).is_empty();
+            if isolated {
+                add(EvalParams::ISOLATED_PAWN, sign);
+            }
+
+            // a pawn of the other color on this square attacks the defenders
+            let supported =
+                !(own & KING_PAWN_ATTACKS[!color as usize][sq.index() as usize]).is_empty();
+            let phalanx = !(own & neighbours & BitBoard::RANKS[sq.rank() as usize]).is_empty();
+            if supported || phalanx {
+                add(EvalParams::CONNECTED_PAWN + rank, sign);
+            }
+
+            let stop_attacked = stop_square(sq, color).is_some_and(|stop| {
+                !(enemy & KING_PAWN_ATTACKS[color as usize][stop.index() as usize]).is_empty()
+            });
+            if !isolated && (own & neighbours & !front).is_empty() && stop_attacked {
+                add(EvalParams::BACKWARD_PAWN, sign);
+            }
+
+            if !doubled && (enemy & (file | neighbours) & front).is_empty() {
+                add(EvalParams::PASSED_PAWN + rank, sign);
+                passed[color as usize] |= sq.bitboard();
+            }
+        }
+    }
+    passed
+}
+
+/// calls `add` for the distances of both kings to the squares in front of
+/// the passed pawns, like `pawn_terms`
+pub fn passed_king_terms(board: &Board, passed: [BitBoard; 2], mut add: impl FnMut(usize, i32)) {
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        let (Some(own_king), Some(enemy_king)) = (
+            board.pieces(color, Piece::King).squares().next(),
+            board.pieces(!color, Piece::King).squares().next(),
+        ) else {
+            continue;
+        };
+        for sq in passed[color as usize].squares() {
+            if let Some(stop) = stop_square(sq, color) {
+                add(EvalParams::PASSED_OWN_KING, sign * distance(own_king, stop));
+                add(
+                    EvalParams::PASSED_ENEMY_KING,
+                    sign * distance(enemy_king, stop),
+                );
+            }
+        }
+    }
+}
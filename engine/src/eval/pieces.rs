@@ -0,0 +1,72 @@
+use super::EvalParams;
+use crate::board::bitboard::BitBoard;
+use crate::board::helper::{Color, Piece};
+use crate::board::precompute::{KING_ATTACKS, KING_PAWN_ATTACKS, KNIGHT_ATTACKS};
+use crate::board::sliding_pieces::{get_all_attacks_bishop, get_all_attacks_rook};
+use crate::board::square::Square;
+use crate::board::Board;
+
+/// every square attacked by a pawn of `color`
+pub fn pawn_attacks(board: &Board, color: Color) -> BitBoard {
+    let mut attacks = BitBoard(0);
+    for sq in board.pieces(color, Piece::Pawn).squares() {
+        attacks |= KING_PAWN_ATTACKS[color as usize][sq.index() as usize];
+    }
+    attacks
+}
+
+/// the squares a single piece attacks, sliding pieces stop at the first
+/// piece in every direction but look through the enemy king
+pub fn piece_attacks(board: &Board, piece: Piece, color: Color, sq: Square) -> BitBoard {
+    let bb = sq.bitboard();
+    match piece {
+        Piece::Pawn => KING_PAWN_ATTACKS[color as usize][sq.index() as usize],
+        Piece::Knight => KNIGHT_ATTACKS[sq.index() as usize],
+        Piece::Bishop => get_all_attacks_bishop(board, bb, color),
+        Piece::Rook => get_all_attacks_rook(board, bb, color),
+        Piece::Queen => get_all_attacks_bishop(board, bb, color) | get_all_attacks_rook(board, bb, color),
+        Piece::King => KING_ATTACKS[sq.index() as usize],
+        Piece::Empty => BitBoard(0),
+    }
+}
+
+/// calls `add` with the index of every mobility, bishop pair and rook
+/// parameter and how often it counts, positive for white and negative for black
+pub fn piece_terms(board: &Board, mut add: impl FnMut(usize, i32)) {
+    let pawns = board.pieces(Color::White, Piece::Pawn) | board.pieces(Color::Black, Piece::Pawn);
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        let area = !board.color_occupancy(color) & !pawn_attacks(board, !color);
+        for (piece, mobility) in [
+            (Piece::Knight, EvalParams::KNIGHT_MOBILITY),
+            (Piece::Bishop, EvalParams::BISHOP_MOBILITY),
+            (Piece::Rook, EvalParams::ROOK_MOBILITY),
+            (Piece::Queen, EvalParams::QUEEN_MOBILITY),
+        ] {
+            for sq in board.pieces(color, piece).squares() {
+                let attacks = piece_attacks(board, piece, color, sq);
+                add(mobility + (attacks & area).count_set_bits() as usize, sign);
+            }
+        }
+
+        if board.pieces(color, Piece::Bishop).more_than_one() {
+            add(EvalParams::BISHOP_PAIR, sign);
+        }
+
+        let own_pawns = board.pieces(color, Piece::Pawn);
+        let seventh = match color {
+            Color::White => BitBoard::RANKS[6],
+            Color::Black => BitBoard::RANKS[1],
+        };
+        for sq in board.pieces(color, Piece::Rook).squares() {
+            let file = BitBoard::FILES[sq.file() as usize];
+            if (pawns & file).is_empty() {
+                add(EvalParams::ROOK_OPEN_FILE, sign);
+            } else if (own_pawns & file).is_empty() {
+                add(EvalParams::ROOK_SEMI_OPEN_FILE, sign);
+            }
+            if seventh.has(sq.into()) {
+                add(EvalParams::ROOK_SEVENTH_RANK, sign);
+            }
+        }
+    }
+}
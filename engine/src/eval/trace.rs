@@ -0,0 +1,101 @@
+use super::{phase, walk_terms, EvalParams, MAX_PHASE, S};
+use crate::board::helper::Color;
+use crate::board::Board;
+use std::fmt;
+
+/// the score of one evaluation term, both colors from their own view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermTrace {
+    pub name: &'static str,
+    pub white: S,
+    pub black: S,
+}
+
+impl TermTrace {
+    /// from white's view
+    pub fn total(&self) -> S {
+        self.white - self.black
+    }
+}
+
+/// the evaluation of a position split up into the terms of `EvalParams::TERMS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalTrace {
+    pub terms: Vec<TermTrace>,
+    /// between 0 for the endgame and `MAX_PHASE`
+    pub phase: i32,
+    /// the tapered sum of all terms from white's view
+    pub score: i32,
+    /// the network evaluation from white's view, if the board has a network
+    pub network: Option<i32>,
+}
+
+impl EvalTrace {
+    pub fn new(board: &Board, params: &EvalParams) -> Self {
+        let mut terms: Vec<TermTrace> = EvalParams::TERMS
+            .iter()
+            .map(|(name, _)| TermTrace {
+                name,
+                white: S::default(),
+                black: S::default(),
+            })
+            .collect();
+        walk_terms(board, |i, c| {
+            let term = EvalParams::TERMS.iter().position(|(_, range)| range.contains(&i)).unwrap();
+            match c > 0 {
+                true => terms[term].white += params.get(i) * c,
+                false => terms[term].black += params.get(i) * -c,
+            }
+        });
+
+        let phase = phase(board);
+        let white_view = |score: i32| match board.current_player() {
+            Color::White => score,
+            Color::Black => -score,
+        };
+        let total = terms.iter().fold(S::default(), |acc, t| acc + t.total());
+        EvalTrace {
+            terms,
+            phase,
+            score: total.taper(phase),
+            network: board.accumulator().map(|acc| white_view(acc.evaluate(board.current_player()))),
+        }
+    }
+
+    pub fn term(&self, name: &str) -> Option<&TermTrace> {
+        self.terms.iter().find(|t| t.name == name)
+    }
+}
+
+fn pawns(score: i32) -> String {
+    format!("{:+.2}", score as f64 / 100.0)
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line = format!("{:-<28}+{:-<15}+{:-<15}+{:-<15}", "", "", "", "");
+        let header = format!("{:<28}|{:^15}|{:^15}|{:^15}", " term", "white", "black", "total");
+        writeln!(f, "{}", header.trim_end())?;
+        writeln!(f, "{:<28}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7}", "", "mg", "eg", "mg", "eg", "mg", "eg")?;
+        writeln!(f, "{}", line)?;
+        let mut total = S::default();
+        for t in &self.terms {
+            let sum = t.total();
+            total += sum;
+            writeln!(
+                f,
+                " {:<27}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7}",
+                t.name, t.white.0, t.white.1, t.black.0, t.black.1, sum.0, sum.1
+            )?;
+        }
+        writeln!(f, "{}", line)?;
+        writeln!(f, " {:<27}|{:15}|{:15}|{:>7}{:>7}", "total", "", "", total.0, total.1)?;
+        writeln!(f)?;
+        writeln!(f, "phase {} of {}", self.phase, MAX_PHASE)?;
+        writeln!(f, "classical evaluation {} (white side)", pawns(self.score))?;
+        if let Some(score) = self.network {
+            writeln!(f, "network evaluation   {} (white side)", pawns(score))?;
+        }
+        Ok(())
+    }
+}
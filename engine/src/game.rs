@@ -0,0 +1,411 @@
+use crate::board::helper::{Color, GameState};
+use crate::board::{Board, Move};
+use crate::notation::{parse_san, parse_uci, to_san};
+use std::fmt;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Ongoing,
+}
+
+/// result as written in PGN, e.g. `1-0` or `*`
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::WhiteWins => write!(f, "1-0"),
+            GameResult::BlackWins => write!(f, "0-1"),
+            GameResult::Draw => write!(f, "1/2-1/2"),
+            GameResult::Ongoing => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    Resignation,
+    DrawAgreement,
+    TimeForfeit,
+    /// decided by someone outside of the game, e.g. a match runner
+    Adjudication,
+    /// lost by a player that stopped playing, like an engine that crashed
+    Abandoned,
+    /// lost by breaking the rules, like an engine playing an illegal move
+    RulesInfraction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayedMove {
+    pub mov: Move,
+    pub san: String,
+}
+
+/// a game from a starting position, with every played move and the board
+/// after it. Undone moves are kept until a different move is played, so
+/// they can be redone.
+#[derive(Clone)]
+pub struct Game {
+    /// boards[0] is the starting position, boards[n] the one after n plies
+    boards: Vec<Board>,
+    moves: Vec<PlayedMove>,
+    /// number of plies that are currently played
+    ply: usize,
+    /// result set from outside the board, like a resignation. Cleared as
+    /// soon as the position changes
+    decided: Option<(GameResult, Termination)>,
+    /// PGN tag pairs in the order they get exported, without `Result`
+    tags: Vec<(String, String)>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::new(START_FEN))
+    }
+}
+
+impl Game {
+    pub fn new(start: Board) -> Self {
+        let tags = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        Game {
+            boards: vec![start],
+            moves: vec![],
+            ply: 0,
+            decided: None,
+            tags,
+        }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let board = Board::from_fen(fen)?;
+        board.validate().map_err(|e| e.to_string())?;
+        Ok(Self::new(board))
+    }
+
+    /// reads the first game of a PGN. Comments, NAGs and variations are skipped.
+    pub fn from_pgn(pgn: &str) -> Result<Self, String> {
+        let mut tags = vec![];
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let line = line.trim();
+            if line.starts_with('%') {
+                continue;
+            }
+            if let Some(tag) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if !movetext.trim().is_empty() {
+                    // tags of the next game
+                    break;
+                }
+                let (name, value) = tag.split_once(' ').ok_or(format!("invalid tag '{}'", line))?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .ok_or(format!("invalid tag '{}'", line))?;
+                tags.push((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")));
+            } else {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let mut game = match tags.iter().find(|(k, _)| k == "FEN") {
+            Some((_, fen)) => Game::from_fen(fen)?,
+            None => Game::default(),
+        };
+        for (name, value) in &tags {
+            if name != "Result" && name != "SetUp" && name != "FEN" {
+                game.set_tag(name, value);
+            }
+        }
+
+        let mut result = None;
+        let mut depth = 0;
+        let mut chars = movetext.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    chars.by_ref().find(|&c| c == '}');
+                    continue;
+                }
+                ';' => {
+                    chars.by_ref().find(|&c| c == '\n');
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ if c.is_whitespace() => (),
+                _ => {
+                    let mut token = c.to_string();
+                    while let Some(&n) = chars.peek() {
+                        if n.is_whitespace() || matches!(n, '{' | '(' | ')' | ';') {
+                            break;
+                        }
+                        token.push(n);
+                        chars.next();
+                    }
+                    if depth > 0 || token.starts_with('$') {
+                        continue;
+                    }
+                    match token.as_str() {
+                        "1-0" => result = Some(GameResult::WhiteWins),
+                        "0-1" => result = Some(GameResult::BlackWins),
+                        "1/2-1/2" => result = Some(GameResult::Draw),
+                        "*" => result = Some(GameResult::Ongoing),
+                        _ => {
+                            // move numbers like `12.` or `12...` can be glued to the move
+                            let san = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+                            if !san.is_empty() {
+                                game.push_san(san)
+                                    .map_err(|e| format!("move {}: {}", game.board().fullmove_number(), e))?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // a result the board does not show itself, e.g. after a resignation
+        if let Some(result) = result {
+            if result != GameResult::Ongoing && game.result() == GameResult::Ongoing {
+                game.set_result(result, Termination::Adjudication);
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn start_position(&self) -> &Board {
+        &self.boards[0]
+    }
+
+    /// the current position
+    pub fn board(&self) -> &Board {
+        &self.boards[self.ply]
+    }
+
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// the moves played up to the current position
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves[..self.ply]
+    }
+
+    /// the position before every played move together with the move
+    pub fn iter(&self) -> impl Iterator<Item = (&Board, &PlayedMove)> {
+        self.boards.iter().zip(self.moves())
+    }
+
+    pub fn last_move(&self) -> Option<&PlayedMove> {
+        self.moves().last()
+    }
+
+    /// plays a legal move. Moves that were undone before are dropped, unless
+    /// the move is the same as the next one to redo.
+    pub fn push(&mut self, m: &Move) -> Result<(), String> {
+        if self.result() != GameResult::Ongoing {
+            return Err("the game is already over".to_string());
+        }
+        let board = self.board();
+        if !board.move_list.contains(m) {
+            return Err(format!("{} is not a legal move", m));
+        }
+
+        if self.moves.get(self.ply).map(|p| &p.mov) == Some(m) {
+            return self.redo().map(|_| ()).ok_or("nothing to redo".to_string());
+        }
+
+        let san = to_san(board, m);
+        let mut next = board.clone();
+        next.make_move(m);
+
+        self.boards.truncate(self.ply + 1);
+        self.moves.truncate(self.ply);
+        self.boards.push(next);
+        self.moves.push(PlayedMove { mov: m.clone(), san });
+        self.ply += 1;
+        self.decided = None;
+        Ok(())
+    }
+
+    pub fn push_san(&mut self, san: &str) -> Result<(), String> {
+        let m = parse_san(self.board(), san)?;
+        self.push(&m)
+    }
+
+    pub fn push_uci(&mut self, uci: &str) -> Result<(), String> {
+        let m = parse_uci(self.board(), uci)?;
+        self.push(&m)
+    }
+
+    /// takes back the last move, returns it if there was one
+    pub fn undo(&mut self) -> Option<&PlayedMove> {
+        if self.ply == 0 {
+            return None;
+        }
+        self.ply -= 1;
+        self.decided = None;
+        Some(&self.moves[self.ply])
+    }
+
+    /// plays the last undone move again
+    pub fn redo(&mut self) -> Option<&PlayedMove> {
+        if self.ply >= self.moves.len() {
+            return None;
+        }
+        self.ply += 1;
+        self.decided = None;
+        Some(&self.moves[self.ply - 1])
+    }
+
+    /// jumps to the position after `ply` plies, within the played and undone moves
+    pub fn goto(&mut self, ply: usize) -> Result<(), String> {
+        if ply > self.moves.len() {
+            return Err(format!("the game only has {} plies", self.moves.len()));
+        }
+        if ply != self.ply {
+            self.ply = ply;
+            self.decided = None;
+        }
+        Ok(())
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result_and_termination().0
+    }
+
+    pub fn termination(&self) -> Option<Termination> {
+        self.result_and_termination().1
+    }
+
+    fn result_and_termination(&self) -> (GameResult, Option<Termination>) {
+        if let Some((result, termination)) = self.decided {
+            return (result, Some(termination));
+        }
+        let board = self.board();
+        match board.game_state {
+            GameState::Playing => (GameResult::Ongoing, None),
+            GameState::WhiteWins => (GameResult::WhiteWins, Some(Termination::Checkmate)),
+            GameState::BlackWins => (GameResult::BlackWins, Some(Termination::Checkmate)),
+            GameState::Draw if board.move_list.is_empty() => (GameResult::Draw, Some(Termination::Stalemate)),
+            GameState::Draw if board.halfmove_clock() >= 100 => (GameResult::Draw, Some(Termination::FiftyMoveRule)),
+            GameState::Draw => (GameResult::Draw, Some(Termination::ThreefoldRepetition)),
+        }
+    }
+
+    /// ends the game for a reason the board does not know about, like a resignation
+    pub fn set_result(&mut self, result: GameResult, termination: Termination) {
+        self.decided = Some((result, termination));
+    }
+
+    /// `color` resigns, the other side wins
+    pub fn resign(&mut self, color: Color) {
+        let result = match color {
+            Color::White => GameResult::BlackWins,
+            Color::Black => GameResult::WhiteWins,
+        };
+        self.set_result(result, Termination::Resignation);
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// sets a PGN tag, replacing it if it already exists. `Result` is
+    /// always taken from the game itself.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(k, _)| k == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// the game up to the current position in PGN
+    pub fn to_pgn(&self) -> String {
+        // the seven tag roster comes first, with the result last
+        let roster = ["Event", "Site", "Date", "Round", "White", "Black"];
+        let (roster_tags, other_tags): (Vec<_>, Vec<_>) = self
+            .tags
+            .iter()
+            .filter(|(name, _)| name != "Result" && name != "SetUp" && name != "FEN")
+            .partition(|(name, _)| roster.contains(&name.as_str()));
+
+        let mut pgn = String::new();
+        for (name, value) in roster_tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape_tag(value)));
+        }
+        pgn.push_str(&format!("[Result \"{}\"]\n", self.result()));
+        for (name, value) in other_tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape_tag(value)));
+        }
+        let start = self.start_position();
+        let start_fen = start.to_fen();
+        if start_fen != START_FEN {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format!("[FEN \"{}\"]\n", start_fen));
+        }
+        pgn.push('\n');
+
+        let mut tokens = vec![];
+        let mut move_number = start.fullmove_number();
+        let mut color = start.current_player();
+        for (i, played) in self.moves().iter().enumerate() {
+            // move numbers stay on the same line as their move
+            if color == Color::White {
+                tokens.push(format!("{}. {}", move_number, played.san));
+            } else if i == 0 {
+                tokens.push(format!("{}... {}", move_number, played.san));
+            } else {
+                tokens.push(played.san.clone());
+            }
+            if color == Color::Black {
+                move_number += 1;
+            }
+            color = !color;
+        }
+        tokens.push(self.result().to_string());
+
+        // movetext lines should not be longer than 80 characters
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + 1 + token.len() > 80 {
+                pgn.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                pgn.push(' ');
+                line_len += 1;
+            }
+            line_len += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+        pgn
+    }
+}
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
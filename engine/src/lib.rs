@@ -2,3 +2,17 @@
 #![feature(lazy_cell)]
 
 pub mod board;
+pub mod book;
+pub mod datagen;
+pub mod epd;
+pub mod eval;
+pub mod game;
+pub mod notation;
+pub mod perft;
+pub mod rng;
+pub mod search;
+pub mod sprt;
+pub mod strength;
+pub mod syzygy;
+pub mod tune;
+pub mod uci;
@@ -0,0 +1,215 @@
+use crate::board::helper::{GameState, Piece, Position};
+use crate::board::{Board, Move};
+
+const PIECE_LETTERS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
+fn promotion_flag(piece: Piece) -> Option<u8> {
+    match piece {
+        Piece::Queen => Some(5),
+        Piece::Rook => Some(6),
+        Piece::Bishop => Some(7),
+        Piece::Knight => Some(8),
+        _ => None,
+    }
+}
+
+fn promotion_piece(flag: u8) -> Option<Piece> {
+    match flag {
+        5 => Some(Piece::Queen),
+        6 => Some(Piece::Rook),
+        7 => Some(Piece::Bishop),
+        8 => Some(Piece::Knight),
+        _ => None,
+    }
+}
+
+fn piece_from_letter(c: char) -> Option<Piece> {
+    match c.to_ascii_uppercase() {
+        'P' => Some(Piece::Pawn),
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+pub fn parse_square(s: &str) -> Option<Position> {
+    let mut chars = s.chars();
+    let (file, rank) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() || !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+        return None;
+    }
+    Some(Position::new(rank as u8 - b'1', file as u8 - b'a'))
+}
+
+/// finds the legal move for a move in long algebraic notation, e.g. `e2e4` or `e7e8q`
+pub fn parse_uci(board: &Board, s: &str) -> Result<Move, String> {
+    let s = s.trim();
+    if s.len() < 4 || s.len() > 5 || !s.is_ascii() {
+        return Err(format!("'{}' is not a move in UCI notation", s));
+    }
+    let from = parse_square(&s[0..2]).ok_or(format!("'{}' is not a square", &s[0..2]))?;
+    let to = parse_square(&s[2..4]).ok_or(format!("'{}' is not a square", &s[2..4]))?;
+    let promotion = match s.chars().nth(4) {
+        Some(c) => Some(
+            piece_from_letter(c)
+                .and_then(promotion_flag)
+                .ok_or(format!("'{}' is not a promotion piece", c))?,
+        ),
+        None => None,
+    };
+
+    board
+        .move_list
+        .iter()
+        .find(|m| {
+            m.from == from
+                && m.to == to
+                && match promotion_piece(m.flag) {
+                    Some(_) => Some(m.flag) == promotion,
+                    None => promotion.is_none(),
+                }
+        })
+        .cloned()
+        .ok_or(format!("{} is not a legal move", s))
+}
+
+/// standard algebraic notation of a legal move, e.g. `Nbd7`, `exd6`, `O-O` or `e8=Q#`
+pub fn to_san(board: &Board, m: &Move) -> String {
+    let mut san = match m.flag {
+        3 => "O-O".to_string(),
+        4 => "O-O-O".to_string(),
+        _ => {
+            let (piece, _) = board.get_by_idx(m.from);
+            let is_capture = !board.tile_is_empty(m.to) || m.flag == 1;
+            let mut san = String::new();
+
+            if piece == Piece::Pawn {
+                if is_capture {
+                    san.push((b'a' + m.from.file()) as char);
+                }
+            } else {
+                san.push(PIECE_LETTERS[piece as usize]);
+
+                // other pieces of the same kind, that can reach the target square
+                let others: Vec<&Move> = board
+                    .move_list
+                    .iter()
+                    .filter(|o| o.to == m.to && o.from != m.from && board.get_by_idx(o.from).0 == piece)
+                    .collect();
+                if !others.is_empty() {
+                    if others.iter().all(|o| o.from.file() != m.from.file()) {
+                        san.push((b'a' + m.from.file()) as char);
+                    } else if others.iter().all(|o| o.from.rank() != m.from.rank()) {
+                        san.push((b'1' + m.from.rank()) as char);
+                    } else {
+                        san.push_str(&m.from.to_string());
+                    }
+                }
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&m.to.to_string());
+            if let Some(p) = promotion_piece(m.flag) {
+                san.push('=');
+                san.push(PIECE_LETTERS[p as usize]);
+            }
+            san
+        }
+    };
+
+    let mut b = board.clone();
+    b.make_move(m);
+    match b.game_state {
+        GameState::WhiteWins | GameState::BlackWins => san.push('#'),
+        _ if b.in_check() => san.push('+'),
+        _ => (),
+    }
+    san
+}
+
+/// finds the legal move for a move in standard algebraic notation. Check
+/// markers, annotations like `!?` and redundant disambiguation are accepted.
+pub fn parse_san(board: &Board, s: &str) -> Result<Move, String> {
+    let cleaned: String = s
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, 'x' | ':' | '=' | '+' | '#' | '!' | '?'))
+        .collect();
+
+    let castle = match cleaned.as_str() {
+        "O-O" | "0-0" => Some(3),
+        "O-O-O" | "0-0-0" => Some(4),
+        _ => None,
+    };
+    if let Some(flag) = castle {
+        return board
+            .move_list
+            .iter()
+            .find(|m| m.flag == flag)
+            .cloned()
+            .ok_or(format!("{} is not a legal move", s.trim()));
+    }
+
+    let mut chars: Vec<char> = cleaned.chars().collect();
+    let piece = match chars.first() {
+        Some(c) if c.is_ascii_uppercase() => {
+            let p = piece_from_letter(*c).ok_or(format!("'{}' is not a piece", c))?;
+            chars.remove(0);
+            p
+        }
+        _ => Piece::Pawn,
+    };
+
+    let mut promotion = None;
+    if piece == Piece::Pawn && chars.len() >= 3 && chars[chars.len() - 2].is_ascii_digit() {
+        let c = chars.pop().unwrap();
+        promotion = Some(
+            piece_from_letter(c)
+                .and_then(promotion_flag)
+                .ok_or(format!("'{}' is not a promotion piece", c))?,
+        );
+    }
+
+    if chars.len() < 2 {
+        return Err(format!("'{}' is not a move in SAN", s.trim()));
+    }
+    let target: String = chars[chars.len() - 2..].iter().collect();
+    let to = parse_square(&target).ok_or(format!("'{}' is not a square", target))?;
+
+    let (mut from_file, mut from_rank) = (None, None);
+    for c in &chars[..chars.len() - 2] {
+        match c {
+            'a'..='h' => from_file = Some(*c as u8 - b'a'),
+            '1'..='8' => from_rank = Some(*c as u8 - b'1'),
+            _ => return Err(format!("'{}' is not a move in SAN", s.trim())),
+        }
+    }
+
+    let candidates: Vec<&Move> = board
+        .move_list
+        .iter()
+        .filter(|m| {
+            m.to == to
+                && m.flag != 3
+                && m.flag != 4
+                && board.get_by_idx(m.from).0 == piece
+                && from_file.is_none_or(|f| m.from.file() == f)
+                && from_rank.is_none_or(|r| m.from.rank() == r)
+                && promotion.is_none_or(|p| m.flag == p)
+        })
+        .collect();
+
+    match candidates.len() {
+        0 => Err(format!("{} is not a legal move", s.trim())),
+        1 => Ok(candidates[0].clone()),
+        _ if candidates.iter().all(|m| promotion_piece(m.flag).is_some()) => {
+            Err(format!("{} is missing the promotion piece", s.trim()))
+        }
+        _ => Err(format!("{} is ambiguous", s.trim())),
+    }
+}
@@ -0,0 +1,117 @@
+use crate::board::{Board, Move};
+
+/// counts all leaf nodes of the move tree up to `depth`
+pub fn perft(board: &Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return board.move_list.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for m in &board.move_list {
+        let mut b = board.clone();
+        b.make_move(m);
+        nodes += perft(&b, depth - 1);
+    }
+    nodes
+}
+
+/// perft split up by the root moves, in move generation order
+pub fn divide(board: &Board, depth: u8) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+
+    board
+        .move_list
+        .iter()
+        .map(|m| {
+            let mut b = board.clone();
+            b.make_move(m);
+            (m.clone(), perft(&b, depth - 1))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: u8,
+    nodes: u64,
+}
+
+/// transposition table for perft, always replaces on collision
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    mask: usize,
+}
+
+impl PerftTable {
+    /// creates a table using roughly `mb` megabytes, at least one entry
+    pub fn new(mb: usize) -> Self {
+        let wanted = (mb * 1024 * 1024 / std::mem::size_of::<PerftEntry>()).max(1);
+        // round down to a power of two, so the index can be masked
+        let size = 1 << (usize::BITS - 1 - wanted.leading_zeros());
+        PerftTable {
+            entries: vec![PerftEntry::default(); size],
+            mask: size - 1,
+        }
+    }
+
+    fn get(&self, key: u64, depth: u8) -> Option<u64> {
+        let e = &self.entries[key as usize & self.mask];
+        if e.key == key && e.depth == depth {
+            Some(e.nodes)
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, key: u64, depth: u8, nodes: u64) {
+        self.entries[key as usize & self.mask] = PerftEntry { key, depth, nodes };
+    }
+}
+
+/// same as `perft`, but reuses subtree counts of transposed positions by
+/// their zobrist key
+pub fn perft_hashed(board: &Board, depth: u8, table: &mut PerftTable) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return board.move_list.len() as u64;
+    }
+
+    let key = board.zobrist_hash();
+    if let Some(nodes) = table.get(key, depth) {
+        return nodes;
+    }
+
+    let mut nodes = 0;
+    for m in &board.move_list {
+        let mut b = board.clone();
+        b.make_move(m);
+        nodes += perft_hashed(&b, depth - 1, table);
+    }
+    table.insert(key, depth, nodes);
+    nodes
+}
+
+/// same as `divide`, but with the subtrees counted by `perft_hashed`
+pub fn divide_hashed(board: &Board, depth: u8, table: &mut PerftTable) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+
+    board
+        .move_list
+        .iter()
+        .map(|m| {
+            let mut b = board.clone();
+            b.make_move(m);
+            (m.clone(), perft_hashed(&b, depth - 1, table))
+        })
+        .collect()
+}
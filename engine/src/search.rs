@@ -0,0 +1,545 @@
+pub mod control;
+pub mod mate;
+pub mod tt;
+
+use crate::board::helper::{Color, GameState, Piece, Position};
+use crate::board::{Board, Move};
+use crate::eval::{evaluate, PIECE_VALUES};
+use crate::syzygy::{RootMove, Tablebases, Wdl};
+use control::{SearchControl, SystemClock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tt::{Bound, TranspositionTable, TtEntry};
+
+/// score of being mated right now, mate in n plies is scored `MATE_SCORE - n`
+pub const MATE_SCORE: i32 = 30_000;
+pub const MAX_PLY: u8 = 64;
+/// a win the tablebases know about, below any mate the search can find
+pub const TB_WIN_SCORE: i32 = MATE_SCORE - 2 * MAX_PLY as i32;
+const INFINITY: i32 = 32_000;
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+}
+
+/// settings of the engine rather than of a single search
+#[derive(Clone, Debug)]
+pub struct SearchOptions {
+    /// the main thread plus helpers that share the transposition table
+    pub threads: usize,
+    /// how many of the best root moves get a score and a pv
+    pub multipv: usize,
+    /// probed at the root and below it
+    pub tablebases: Option<Arc<Tablebases>>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            threads: 1,
+            multipv: 1,
+            tablebases: None,
+        }
+    }
+}
+
+/// one of the best root moves and how the game goes on after it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PvLine {
+    /// centipawns from the view of the side to move
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// centipawns from the view of the side to move
+    pub score: i32,
+    /// last fully searched depth
+    pub depth: u8,
+    /// deepest ply reached, including the quiescence search
+    pub seldepth: u8,
+    /// of all threads
+    pub nodes: u64,
+    /// positions found in the tablebases, by all threads
+    pub tb_hits: u64,
+    pub pv: Vec<Move>,
+    /// the `multipv` best root moves, best first. The first line is the
+    /// same as `score` and `pv`.
+    pub lines: Vec<PvLine>,
+    pub time: Duration,
+}
+
+/// progress of a running search, see `search_with`
+#[derive(Debug)]
+pub enum SearchInfo<'a> {
+    /// an iteration finished, with the result so far
+    Iteration(&'a SearchResult),
+    /// the main thread starts on a root move, numbered from 1
+    CurrMove { depth: u8, mov: &'a Move, number: usize },
+}
+
+#[inline]
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - MAX_PLY as i32
+}
+
+struct Searcher<'a> {
+    limits: &'a SearchLimits,
+    tt: &'a TranspositionTable,
+    /// stopped by the caller, the helpers by the main thread when it is done
+    control: &'a SearchControl,
+    /// nodes of the helper threads, added in batches
+    helper_nodes: &'a AtomicU64,
+    /// only the main thread reports
+    report: Option<&'a mut dyn FnMut(SearchInfo)>,
+    is_main: bool,
+    multipv: usize,
+    /// root moves of the lines already searched in this iteration
+    excluded: Vec<Move>,
+    /// probed below the root, unless the root moves already say it all
+    tablebases: Option<&'a Tablebases>,
+    /// the root moves the tables keep, only they get searched
+    root_moves: Option<&'a [RootMove]>,
+    nodes: u64,
+    tb_hits: u64,
+    seldepth: u8,
+    stopped: bool,
+    root_depth: u8,
+}
+
+/// searches the position with iterative deepening until one of the limits
+/// is hit. The first iteration is always finished, so there is a best move
+/// as long as the game is not over.
+pub fn search(board: &Board, limits: &SearchLimits) -> SearchResult {
+    let tt = TranspositionTable::new(1);
+    let control = SearchControl::new(Arc::new(SystemClock::default()), false);
+    search_with(board, limits, &SearchOptions::default(), &tt, &control, |_| {})
+}
+
+/// like `search`, but keeps its results in `tt`, runs `options.threads`
+/// threads, can be stopped or told about a ponderhit through `control` and
+/// reports its progress
+pub fn search_with(
+    board: &Board,
+    limits: &SearchLimits,
+    options: &SearchOptions,
+    tt: &TranspositionTable,
+    control: &SearchControl,
+    mut report: impl FnMut(SearchInfo),
+) -> SearchResult {
+    let mut result = SearchResult {
+        best_move: None,
+        score: terminal_score(board, 0).unwrap_or(0),
+        depth: 0,
+        seldepth: 0,
+        nodes: 0,
+        tb_hits: 0,
+        pv: vec![],
+        lines: vec![],
+        time: Duration::ZERO,
+    };
+    if !matches!(board.game_state, GameState::Playing) {
+        return result;
+    }
+
+    // with the root in the tables only the best moves by them are searched.
+    // Probing below the root is left for when the root has no distance to
+    // zeroing and is not lost anyway.
+    let mut tablebases = options.tablebases.as_deref();
+    let ranking = tablebases.and_then(|tb| tb.rank_root_moves(board));
+    let root_moves = ranking.as_ref().map(|r| {
+        if r.by_dtz || r.moves[0].score <= 0 {
+            tablebases = None;
+        }
+        let best = r.moves.iter().take_while(|m| m.rank == r.moves[0].rank).count();
+        &r.moves[..best]
+    });
+    result.tb_hits = ranking.as_ref().map_or(0, |r| r.moves.len() as u64);
+
+    tt.new_search();
+    let helper_nodes = AtomicU64::new(0);
+    // the helpers stop with the main thread, but the caller's control stays as it is
+    let done = SearchControl::new(control.clock().clone(), false);
+    std::thread::scope(|scope| {
+        let helpers: Vec<_> = (1..options.threads.max(1))
+            .map(|id| {
+                let (done, helper_nodes) = (&done, &helper_nodes);
+                scope.spawn(move || {
+                    let mut helper = Searcher::new(limits, tt, done, helper_nodes, None);
+                    (helper.tablebases, helper.root_moves) = (tablebases, root_moves);
+                    helper.iterate(board, &mut None, 1 + id as u8 % 2);
+                    helper_nodes.fetch_add(helper.nodes & 1023, Ordering::Relaxed);
+                    helper.tb_hits
+                })
+            })
+            .collect();
+
+        let mut main = Searcher::new(limits, tt, control, &helper_nodes, Some(&mut report));
+        (main.tablebases, main.root_moves) = (tablebases, root_moves);
+        main.tb_hits = result.tb_hits;
+        let root_count = root_moves.map_or(board.move_list.len(), |m| m.len());
+        main.multipv = options.multipv.clamp(1, root_count);
+        main.iterate(board, &mut Some(&mut result), 1);
+        done.stop();
+        result.nodes = main.nodes;
+        result.tb_hits = main.tb_hits + helpers.into_iter().map(|h| h.join().unwrap()).sum::<u64>();
+    });
+
+    result.nodes += helper_nodes.load(Ordering::Relaxed);
+    result.time = control.elapsed();
+    result
+}
+
+/// thinking time for one move from the clock. Without `moves_to_go` the
+/// remaining time is spread over the next 30 moves.
+pub fn time_for_move(time_left: Duration, increment: Duration, moves_to_go: Option<u32>) -> Duration {
+    let moves = moves_to_go.unwrap_or(30).max(1);
+    let time = time_left / moves + increment * 3 / 4;
+    // never use more than half of what is left
+    time.min(time_left / 2)
+}
+
+/// score of a finished game from the view of the side to move
+fn terminal_score(board: &Board, ply: u8) -> Option<i32> {
+    match board.game_state {
+        GameState::Playing => None,
+        GameState::Draw => Some(0),
+        // the side to move is always the one that got mated
+        GameState::WhiteWins | GameState::BlackWins => Some(-MATE_SCORE + ply as i32),
+    }
+}
+
+fn piece_on(board: &Board, pos: Position, color: Color) -> Option<Piece> {
+    Piece::ALL_NONEMPTY
+        .into_iter()
+        .find(|p| board.pieces(color, *p).has(pos))
+}
+
+#[inline]
+pub(crate) fn is_capture(board: &Board, m: &Move) -> bool {
+    m.flag == 1 || !board.tile_is_empty(m.to)
+}
+
+/// orders the moves by the previous principal variation, then captures by
+/// most valuable victim / least valuable attacker, then promotions
+fn ordered_moves(board: &Board, pv_move: Option<&Move>, captures_only: bool) -> Vec<Move> {
+    let us = board.current_player();
+    let mut moves: Vec<(i32, Move)> = board
+        .move_list
+        .iter()
+        .filter(|m| !captures_only || is_capture(board, m) || m.flag >= 5)
+        .map(|m| {
+            let mut score = 0;
+            if Some(m) == pv_move {
+                score += 1_000_000;
+            }
+            if is_capture(board, m) {
+                let victim = piece_on(board, m.to, !us).unwrap_or(Piece::Pawn);
+                let attacker = piece_on(board, m.from, us).unwrap_or(Piece::Pawn);
+                score += 10_000 + 10 * PIECE_VALUES[victim as usize] - PIECE_VALUES[attacker as usize] / 10;
+            }
+            if m.flag == 5 {
+                score += PIECE_VALUES[Piece::Queen as usize];
+            }
+            (score, m.clone())
+        })
+        .collect();
+    moves.sort_by_key(|(score, _)| -score);
+    moves.into_iter().map(|(_, m)| m).collect()
+}
+
+impl<'a> Searcher<'a> {
+    fn new(
+        limits: &'a SearchLimits,
+        tt: &'a TranspositionTable,
+        control: &'a SearchControl,
+        helper_nodes: &'a AtomicU64,
+        report: Option<&'a mut dyn FnMut(SearchInfo)>,
+    ) -> Self {
+        Searcher {
+            limits,
+            tt,
+            control,
+            helper_nodes,
+            is_main: report.is_some(),
+            report,
+            multipv: 1,
+            excluded: vec![],
+            tablebases: None,
+            root_moves: None,
+            nodes: 0,
+            tb_hits: 0,
+            seldepth: 0,
+            stopped: false,
+            root_depth: 0,
+        }
+    }
+
+    /// iterative deepening from `first_depth` on, the main thread fills in
+    /// `result` after every finished iteration
+    fn iterate(&mut self, board: &Board, result: &mut Option<&mut SearchResult>, first_depth: u8) {
+        let max_depth = self.limits.depth.unwrap_or(MAX_PLY).clamp(1, MAX_PLY);
+        let mut prev_lines: Vec<PvLine> = vec![];
+        'deepening: for depth in first_depth.min(max_depth)..=max_depth {
+            self.root_depth = depth;
+            // every line searches the root without the moves of the lines before it
+            self.excluded.clear();
+            let mut lines = vec![];
+            for i in 0..self.multipv {
+                let prev_pv = prev_lines.get(i).map_or(&[][..], |l| &l.pv);
+                let mut pv = vec![];
+                let score = self.negamax(board, depth, 0, -INFINITY, INFINITY, prev_pv, &mut pv);
+                if self.stopped {
+                    break 'deepening;
+                }
+                self.excluded.push(pv[0].clone());
+                lines.push(PvLine { score, pv });
+            }
+            lines.sort_by_key(|l| -l.score);
+            prev_lines = lines.clone();
+
+            let Some(result) = result else {
+                continue;
+            };
+            // the tables know better than the search, unless it found a mate
+            for line in lines.iter_mut().filter(|l| !is_mate_score(l.score)) {
+                if let Some(root) = self.root_moves.and_then(|r| r.iter().find(|r| r.mov == line.pv[0])) {
+                    line.score = root.score;
+                }
+            }
+            result.best_move = lines[0].pv.first().cloned();
+            result.score = lines[0].score;
+            result.depth = depth;
+            result.seldepth = self.seldepth;
+            result.pv = lines[0].pv.clone();
+            result.lines = lines;
+            result.nodes = self.nodes + self.helper_nodes.load(Ordering::Relaxed);
+            result.tb_hits = self.tb_hits;
+            result.time = self.control.elapsed();
+            if let Some(report) = &mut self.report {
+                report(SearchInfo::Iteration(result));
+            }
+
+            // a shorter mate can not be found by searching deeper
+            let solved = |l: &PvLine| is_mate_score(l.score) && MATE_SCORE - l.score.abs() <= depth as i32;
+            if result.lines.iter().all(solved) {
+                break;
+            }
+            // the next iteration would most likely not finish in time
+            if let (Some(t), Some(elapsed)) = (self.limits.movetime, self.control.limit_elapsed()) {
+                if elapsed * 2 > t {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn check_limits(&mut self) {
+        if !self.is_main {
+            if self.nodes & 1023 == 0 {
+                self.helper_nodes.fetch_add(1024, Ordering::Relaxed);
+            }
+            self.stopped |= self.control.stopped();
+            return;
+        }
+        // the first iteration is always finished
+        if self.root_depth <= 1 {
+            return;
+        }
+        if self.control.stopped() {
+            self.stopped = true;
+        }
+        if let Some(n) = self.limits.nodes {
+            if self.nodes >= n {
+                self.stopped = true;
+            }
+        }
+        if let Some(t) = self.limits.movetime {
+            if self.nodes & 1023 == 0 && self.control.limit_elapsed().is_some_and(|e| e >= t) {
+                self.stopped = true;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &Board,
+        depth: u8,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+        prev_pv: &[Move],
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        if let Some(score) = terminal_score(board, ply) {
+            return score;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        self.check_limits();
+        if self.stopped {
+            return 0;
+        }
+
+        let key = board.zobrist_hash();
+        let tt_entry = self.tt.probe(key, ply);
+        if let Some(e) = tt_entry.as_ref().filter(|e| ply > 0 && e.depth >= depth) {
+            // only scores outside the window are taken, so the pv stays complete
+            let cutoff = match e.bound {
+                Bound::Exact => e.score <= alpha || e.score >= beta,
+                Bound::Lower => e.score >= beta,
+                Bound::Upper => e.score <= alpha,
+            };
+            if cutoff {
+                return e.score.clamp(alpha, beta);
+            }
+        }
+
+        // right after a capture or pawn move the tables know the result. A
+        // win or loss is only a bound, the search may find a mate.
+        let mut tb_bound = None;
+        let covered = |tb: &&Tablebases| ply > 0 && board.halfmove_clock() == 0 && tb.covers(board);
+        if let Some(wdl) = self.tablebases.filter(covered).and_then(|tb| tb.probe_wdl(board)) {
+            self.tb_hits += 1;
+            let (score, bound) = match wdl {
+                Wdl::Win => (TB_WIN_SCORE - ply as i32, Bound::Lower),
+                Wdl::Loss => (-TB_WIN_SCORE + ply as i32, Bound::Upper),
+                _ => (2 * wdl as i32, Bound::Exact),
+            };
+            let cutoff = match bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                self.store(key, ply, (depth + 6).min(MAX_PLY - 1), score, bound, None);
+                return score.clamp(alpha, beta);
+            }
+            tb_bound = Some((score, bound));
+        }
+        // a win is what the moves have to beat, a loss is the best they can get
+        let (min_score, max_score) = match tb_bound {
+            Some((score, Bound::Lower)) => (score, INFINITY),
+            Some((score, _)) => (-INFINITY, score),
+            None => (-INFINITY, INFINITY),
+        };
+
+        let original_alpha = alpha;
+        alpha = alpha.max(min_score);
+        let pv_move = prev_pv.first().or(tt_entry.as_ref().and_then(|e| e.best_move.as_ref()));
+        let mut moves = ordered_moves(board, pv_move, false);
+        if ply == 0 {
+            moves.retain(|m| !self.excluded.contains(m));
+            if let Some(root) = self.root_moves {
+                moves.retain(|m| root.iter().any(|r| r.mov == *m));
+            }
+        }
+        for (number, m) in moves.into_iter().enumerate() {
+            if ply == 0 {
+                if let Some(report) = &mut self.report {
+                    report(SearchInfo::CurrMove {
+                        depth,
+                        mov: &m,
+                        number: number + 1,
+                    });
+                }
+            }
+            let mut b = board.clone();
+            b.make_move(&m);
+
+            // only follow the previous pv while still on it
+            let child_prev_pv = match prev_pv.first() {
+                Some(p) if *p == m => &prev_pv[1..],
+                _ => &[],
+            };
+            let mut child_pv = vec![];
+            let score = -self.negamax(&b, depth - 1, ply + 1, -beta, -alpha, child_prev_pv, &mut child_pv);
+            if self.stopped {
+                return 0;
+            }
+
+            let score = score.min(max_score);
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(m);
+                pv.append(&mut child_pv);
+                if alpha >= beta {
+                    self.store(key, ply, depth, beta, Bound::Lower, pv.first());
+                    return beta;
+                }
+            }
+        }
+
+        let bound = match tb_bound {
+            // the score is the one of the tables rather than of a searched
+            // move, so it is no more than the bound they gave
+            Some((score, bound)) if alpha == score && (pv.is_empty() || bound == Bound::Upper) => bound,
+            _ if alpha > original_alpha => Bound::Exact,
+            _ => Bound::Upper,
+        };
+        self.store(key, ply, depth, alpha, bound, pv.first());
+        alpha
+    }
+
+    fn store(&self, key: u64, ply: u8, depth: u8, score: i32, bound: Bound, best_move: Option<&Move>) {
+        // the root without some of its moves is not the position itself
+        if ply == 0 && !self.excluded.is_empty() {
+            return;
+        }
+        let entry = TtEntry {
+            best_move: best_move.cloned(),
+            score,
+            depth,
+            bound,
+        };
+        self.tt.store(key, ply, &entry);
+    }
+
+    fn quiescence(&mut self, board: &Board, ply: u8, mut alpha: i32, beta: i32) -> i32 {
+        if let Some(score) = terminal_score(board, ply) {
+            return score;
+        }
+
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        self.check_limits();
+        if self.stopped {
+            return 0;
+        }
+
+        let stand_pat = evaluate(board);
+        if stand_pat >= beta {
+            return beta;
+        }
+        if ply >= MAX_PLY {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        for m in ordered_moves(board, None, true) {
+            let mut b = board.clone();
+            b.make_move(&m);
+            let score = -self.quiescence(&b, ply + 1, -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// where a search takes its time from, so tests can control it
+pub trait Clock: Send + Sync {
+    /// time since an arbitrary but fixed point
+    fn now(&self) -> Duration;
+}
+
+/// the real time
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// a clock that only moves when it is told to
+#[derive(Default)]
+pub struct ManualClock {
+    nanos: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn advance(&self, time: Duration) {
+        self.nanos.fetch_add(time.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+/// shared by a running search and whoever started it, to stop the search
+/// or to tell it that the move it ponders on was played
+pub struct SearchControl {
+    clock: Arc<dyn Clock>,
+    /// clock time when the search was started
+    start: Duration,
+    stop: AtomicBool,
+    pondering: AtomicBool,
+    /// clock time in nanoseconds from when the time limit counts, the
+    /// ponderhit for a ponder search
+    limit_start: AtomicU64,
+}
+
+impl SearchControl {
+    /// while `pondering` the search ignores its time limit
+    pub fn new(clock: Arc<dyn Clock>, pondering: bool) -> Self {
+        let start = clock.now();
+        SearchControl {
+            clock,
+            start,
+            stop: AtomicBool::new(false),
+            pondering: AtomicBool::new(pondering),
+            limit_start: AtomicU64::new(start.as_nanos() as u64),
+        }
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// the expected move was played, the time limit counts from now on
+    pub fn ponderhit(&self) {
+        self.limit_start.store(self.clock.now().as_nanos() as u64, Ordering::SeqCst);
+        self.pondering.store(false, Ordering::SeqCst);
+    }
+
+    pub fn pondering(&self) -> bool {
+        self.pondering.load(Ordering::SeqCst)
+    }
+
+    /// time since the search was started, pondering included
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start)
+    }
+
+    /// the time that counts against the time limit, none while pondering
+    pub fn limit_elapsed(&self) -> Option<Duration> {
+        if self.pondering() {
+            return None;
+        }
+        let start = Duration::from_nanos(self.limit_start.load(Ordering::SeqCst));
+        Some(self.clock.now().saturating_sub(start))
+    }
+}
@@ -0,0 +1,125 @@
+use super::control::{SearchControl, SystemClock};
+use crate::board::helper::GameState;
+use crate::board::{Board, Move};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// the shortest forced mate in at most `moves` moves for the side to move,
+/// as the line up to and including the mating move. The defender tries
+/// every reply, but the attacker only checks, so a mate that needs a quiet
+/// move is not found.
+pub fn find_mate(board: &Board, moves: u8) -> Option<Vec<Move>> {
+    let control = SearchControl::new(Arc::new(SystemClock::default()), false);
+    MateSearch::new(&control).find(board, moves)
+}
+
+/// proves mates by trying all checks of the attacker against all replies of
+/// the defender, see `find_mate`
+pub struct MateSearch<'a> {
+    control: &'a SearchControl,
+    nodes: u64,
+    /// positions with the attacker to move, and the most moves in which
+    /// they have no mate
+    refuted: HashMap<u64, u8>,
+    /// a repetition or the 50 move rule was hit, those draws depend on the
+    /// moves that led to the position, so its refutation is not cached
+    drawn: bool,
+    stopped: bool,
+}
+
+impl<'a> MateSearch<'a> {
+    pub fn new(control: &'a SearchControl) -> Self {
+        MateSearch {
+            control,
+            nodes: 0,
+            refuted: HashMap::new(),
+            drawn: false,
+            stopped: false,
+        }
+    }
+
+    /// like `find_mate`, but gives up with none once `control` is stopped
+    pub fn find(&mut self, board: &Board, moves: u8) -> Option<Vec<Move>> {
+        if !matches!(board.game_state, GameState::Playing) {
+            return None;
+        }
+        self.stopped = self.control.stopped();
+        (1..=moves).find_map(|n| self.attack(board, n))
+    }
+
+    /// positions searched so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// a mate in at most `n` moves with the attacker to move
+    fn attack(&mut self, board: &Board, n: u8) -> Option<Vec<Move>> {
+        // the defender escaped into a draw
+        if matches!(board.game_state, GameState::Draw) {
+            self.drawn |= !board.move_list.is_empty();
+            return None;
+        }
+        let key = board.zobrist_hash();
+        if self.refuted.get(&key).is_some_and(|&r| r >= n) {
+            return None;
+        }
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) && self.control.stopped() {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return None;
+        }
+
+        // checks that leave the fewest replies first
+        let mut checks: Vec<(&Move, Board)> = board
+            .move_list
+            .iter()
+            .map(|m| {
+                let mut b = board.clone();
+                b.make_move(m);
+                (m, b)
+            })
+            .filter(|(_, b)| b.king_attacker_count > 0)
+            .collect();
+        checks.sort_by_key(|(_, b)| b.move_list.len());
+        let drawn_before = std::mem::take(&mut self.drawn);
+        for (m, child) in checks {
+            if let Some(mut line) = self.defend(&child, n) {
+                line.insert(0, m.clone());
+                self.drawn |= drawn_before;
+                return Some(line);
+            }
+        }
+        // a stopped search proved nothing
+        if !self.stopped && !self.drawn {
+            self.refuted.insert(key, n);
+        }
+        self.drawn |= drawn_before;
+        None
+    }
+
+    /// the longest defence if every reply is mated within the other `n - 1`
+    /// moves, none if one escapes
+    fn defend(&mut self, board: &Board, n: u8) -> Option<Vec<Move>> {
+        match board.game_state {
+            GameState::Playing => (),
+            // a stalemate is a draw in any case
+            GameState::Draw => {
+                self.drawn |= !board.move_list.is_empty();
+                return None;
+            }
+            GameState::WhiteWins | GameState::BlackWins => return Some(vec![]),
+        }
+        let mut longest: Option<Vec<Move>> = None;
+        for m in &board.move_list {
+            let mut child = board.clone();
+            child.make_move(m);
+            let mate = (1..n).find_map(|k| self.attack(&child, k))?;
+            if longest.as_ref().is_none_or(|l| mate.len() >= l.len()) {
+                longest = Some([vec![m.clone()], mate].concat());
+            }
+        }
+        longest
+    }
+}
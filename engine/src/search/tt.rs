@@ -0,0 +1,177 @@
+use super::{MAX_PLY, TB_WIN_SCORE};
+use crate::board::helper::Position;
+use crate::board::Move;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact = 1,
+    /// the score is at least this high
+    Lower = 2,
+    /// the score is at most this high
+    Upper = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    /// from the view of the side to move, mate scores relative to the probing ply
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+/// the key is stored xored with the data, so an entry that was torn by
+/// two threads writing at once does not match any key
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// a hash table of search results shared by all search threads without
+/// locking. A slot holds one entry, packed into 64 bits as the move (16),
+/// the score (16), the depth (8), the bound (2) and the generation (6).
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    /// counts the searches, entries of older searches are replaced first
+    generation: AtomicU8,
+}
+
+const SLOT_SIZE: usize = std::mem::size_of::<Slot>();
+
+/// mate and tablebase win scores are stored relative to the node and not
+/// the root
+fn score_to_tt(score: i32, ply: u8) -> i32 {
+    if score >= TB_WIN_SCORE - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -TB_WIN_SCORE + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: u8) -> i32 {
+    if score >= TB_WIN_SCORE - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -TB_WIN_SCORE + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+fn pack_move(m: Option<&Move>) -> u64 {
+    match m {
+        Some(m) => m.from.0 as u64 | (m.to.0 as u64) << 6 | (m.flag as u64) << 12,
+        // a1a1 is never a move
+        None => 0,
+    }
+}
+
+fn unpack_move(bits: u64) -> Option<Move> {
+    let (from, to, flag) = (bits & 63, bits >> 6 & 63, bits >> 12 & 15);
+    (bits != 0).then(|| Move::new_with_flags(Position(from as u8), Position(to as u8), flag as u8))
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_MB: usize = 16;
+
+    /// a table using `mb` megabytes, at least one entry
+    pub fn new(mb: usize) -> Self {
+        let len = (mb * 1024 * 1024 / SLOT_SIZE).max(1);
+        TranspositionTable {
+            slots: (0..len).map(|_| Slot::default()).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// called before every search
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed) as u64 & 63
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        // maps the key to the length without needing a power of two
+        &self.slots[((key as u128 * self.slots.len() as u128) >> 64) as usize]
+    }
+
+    pub fn probe(&self, key: u64, ply: u8) -> Option<TtEntry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        Some(TtEntry {
+            best_move: unpack_move(data & 0xffff),
+            score: score_from_tt((data >> 16) as u16 as i16 as i32, ply),
+            depth: (data >> 32) as u8,
+            bound: match data >> 40 & 3 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+        })
+    }
+
+    /// replaces entries of older searches and shallower entries, keeps the
+    /// best move of the position if the new entry has none
+    pub fn store(&self, key: u64, ply: u8, entry: &TtEntry) {
+        let slot = self.slot(key);
+        let old = slot.data.load(Ordering::Relaxed);
+        let same_key = old != 0 && slot.key.load(Ordering::Relaxed) ^ old == key;
+        let old_depth = (old >> 32) as u8;
+        let generation = self.generation();
+        if old != 0 && old >> 42 == generation && entry.bound != Bound::Exact && entry.depth + 2 < old_depth {
+            return;
+        }
+
+        let mut best_move = pack_move(entry.best_move.as_ref());
+        if best_move == 0 && same_key {
+            best_move = old & 0xffff;
+        }
+        let score = score_to_tt(entry.score, ply) as i16 as u16 as u64;
+        let data = best_move
+            | score << 16
+            | (entry.depth as u64) << 32
+            | (entry.bound as u64) << 40
+            | generation << 42;
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    /// how full the table is in permille, counting the entries of the
+    /// current search among the first thousand
+    pub fn hashfull(&self) -> u16 {
+        let sample = &self.slots[..self.slots.len().min(1000)];
+        let generation = self.generation();
+        let used = sample
+            .iter()
+            .filter(|s| {
+                let data = s.data.load(Ordering::Relaxed);
+                data != 0 && data >> 42 == generation
+            })
+            .count();
+        (used * 1000 / sample.len()) as u16
+    }
+}
@@ -0,0 +1,134 @@
+/// results of a match from the view of the first engine, used for the
+/// Elo difference, the likelihood of superiority and the SPRT
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+fn elo_from_score(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// error function, Abramowitz and Stegun 7.1.26 with an error below 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+impl MatchScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// points per game, between 0 and 1
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// variance of the points of a single game
+    fn variance(&self) -> f64 {
+        let n = self.games() as f64;
+        let s = self.score();
+        (self.wins as f64 * (1.0 - s).powi(2) + self.losses as f64 * s.powi(2) + self.draws as f64 * (0.5 - s).powi(2))
+            / n
+    }
+
+    /// Elo difference, infinite if one side scored everything
+    pub fn elo(&self) -> f64 {
+        elo_from_score(self.score())
+    }
+
+    /// half the width of the 95% confidence interval of `elo`
+    pub fn elo_margin(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        if self.losses + self.draws == 0 || self.wins + self.draws == 0 {
+            return f64::INFINITY;
+        }
+        let deviation = (self.variance() / self.games() as f64).sqrt();
+        let (low, high) = (self.score() - 1.96 * deviation, self.score() + 1.96 * deviation);
+        (elo_from_score(high.min(1.0)) - elo_from_score(low.max(0.0))) / 2.0
+    }
+
+    /// likelihood of superiority, the chance that the first engine is the stronger one
+    pub fn los(&self) -> f64 {
+        let decisive = (self.wins + self.losses) as f64;
+        if decisive == 0.0 {
+            return 0.5;
+        }
+        0.5 * (1.0 + erf((self.wins as f64 - self.losses as f64) / (2.0 * decisive).sqrt()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtStatus {
+    /// the change is not better than `elo0`
+    AcceptH0,
+    /// the change is at least `elo1` better
+    AcceptH1,
+    Continue,
+}
+
+/// sequential probability ratio test between the hypotheses that the Elo
+/// difference is `elo0` or `elo1`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// lower and upper bound of the log likelihood ratio
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// log likelihood ratio of the results, using the normal approximation
+    /// of the score distribution
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        if score.games() == 0 || score.wins + score.draws == 0 || score.losses + score.draws == 0 {
+            return 0.0;
+        }
+        let variance = score.variance();
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        score.games() as f64 * (s1 - s0) * (2.0 * score.score() - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn status(&self, score: &MatchScore) -> SprtStatus {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtStatus::AcceptH1
+        } else if llr <= lower {
+            SprtStatus::AcceptH0
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
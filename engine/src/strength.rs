@@ -0,0 +1,97 @@
+use crate::board::{Board, Move};
+use crate::rng::Rng;
+use crate::search::control::{SearchControl, SystemClock};
+use crate::search::tt::TranspositionTable;
+use crate::search::{search_with, PvLine, SearchLimits, SearchOptions, SearchResult};
+use std::sync::Arc;
+
+/// how the engine plays below its full strength: it searches less, picks
+/// among its best moves at random with a softmax over their scores and now
+/// and then plays any legal move
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    pub elo: u32,
+    /// caps the nodes of every search
+    pub nodes: u64,
+    /// caps the depth of every search
+    pub depth: u8,
+    /// how many of the best moves are candidates
+    pub multipv: usize,
+    /// in centipawns, a move this much worse than the best is picked
+    /// `e` times less often. Zero always picks the best move.
+    pub temperature: f64,
+    /// chance of playing a random legal move instead
+    pub blunder_chance: f64,
+}
+
+impl Strength {
+    pub const MIN_ELO: u32 = 800;
+    pub const MAX_ELO: u32 = 2800;
+
+    /// a rough guess for playing at about `elo`, it is clamped to the
+    /// supported range. Every 200 points double the nodes and add a ply.
+    pub fn from_elo(elo: u32) -> Self {
+        let elo = elo.clamp(Self::MIN_ELO, Self::MAX_ELO);
+        let steps = (elo - Self::MIN_ELO) / 200;
+        let temperature = 2400u32.saturating_sub(elo) as f64 / 8.0;
+        Strength {
+            elo,
+            nodes: 50 << steps,
+            depth: 1 + steps as u8,
+            multipv: if temperature > 0.0 { 4 } else { 1 },
+            temperature,
+            // one in ten moves at the lowest level, none from 2000 on
+            blunder_chance: 2000u32.saturating_sub(elo) as f64 / 12_000.0,
+        }
+    }
+
+    /// the limits with the caps of this strength applied
+    pub fn limit(&self, limits: &SearchLimits) -> SearchLimits {
+        SearchLimits {
+            depth: Some(limits.depth.map_or(self.depth, |d| d.min(self.depth))),
+            nodes: Some(limits.nodes.map_or(self.nodes, |n| n.min(self.nodes))),
+            ..limits.clone()
+        }
+    }
+
+    /// picks the line to play from the lines of a MultiPV search. A blunder
+    /// is a line of just the move. None if there are no legal moves.
+    pub fn choose(&self, board: &Board, lines: &[PvLine], rng: &mut Rng) -> Option<Vec<Move>> {
+        let best = lines.first()?;
+        if board.move_list.len() > 1 && rng.next_f64() < self.blunder_chance {
+            let others: Vec<&Move> = board.move_list.iter().filter(|m| Some(*m) != best.pv.first()).collect();
+            return Some(vec![others[rng.below(others.len())].clone()]);
+        }
+
+        let candidates = &lines[..lines.len().min(self.multipv.max(1))];
+        if self.temperature <= 0.0 || candidates.len() == 1 {
+            return Some(best.pv.clone());
+        }
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|l| ((l.score - best.score) as f64 / self.temperature).exp())
+            .collect();
+        let mut r = rng.next_f64() * weights.iter().sum::<f64>();
+        for (line, w) in candidates.iter().zip(&weights) {
+            if r < *w {
+                return Some(line.pv.clone());
+            }
+            r -= w;
+        }
+        Some(best.pv.clone())
+    }
+}
+
+/// searches the position and picks a move like a player of the given
+/// strength would. The same seed always gives the same line.
+pub fn play(board: &Board, limits: &SearchLimits, strength: &Strength, rng: &mut Rng) -> Option<(Vec<Move>, SearchResult)> {
+    let options = SearchOptions {
+        multipv: strength.multipv,
+        ..Default::default()
+    };
+    let tt = TranspositionTable::new(1);
+    let control = SearchControl::new(Arc::new(SystemClock::default()), false);
+    let result = search_with(board, &strength.limit(limits), &options, &tt, &control, |_| {});
+    let line = strength.choose(board, &result.lines, rng)?;
+    Some((line, result))
+}
@@ -0,0 +1,537 @@
+//! endgame tablebases in the syzygy format. A win/draw/loss (`.rtbw`) and
+//! a distance to zeroing (`.rtbz`) file per set of pieces tell the result
+//! of a position and how many plies it takes until the next capture or
+//! pawn move that keeps it, with the 50 move rule in mind.
+//!
+//! The reader follows the layout of the published tables of up to 7
+//! pieces, but is only tested against tables made by `generate`, which can
+//! not make symmetric tables or tables with pawns on both sides. Files
+//! named like a table that is not one of up to 7 pieces are rejected.
+
+pub mod encoding;
+pub mod generate;
+mod table;
+
+use crate::board::helper::{Color, GameState, Piece};
+use crate::board::{Board, Move};
+use crate::eval::PIECE_VALUES;
+use crate::search::TB_WIN_SCORE;
+use encoding::{piece_code, Material};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Neg;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use table::{Kind, Table, STM};
+
+/// win/draw/loss of the side to move. A cursed win can not be forced
+/// within 50 moves, a blessed loss holds on long enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss = -2,
+    BlessedLoss = -1,
+    Draw = 0,
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Self {
+        match value {
+            ..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+    fn neg(self) -> Wdl {
+        Wdl::from_value(-(self as i32))
+    }
+}
+
+/// a table that can not be probed, or the other side to move is stored
+enum ProbeError {
+    Missing,
+    ChangeStm,
+}
+
+/// the distance to zeroing at the time of a zeroing move that keeps `wdl`
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+fn is_capture(board: &Board, m: &Move) -> bool {
+    m.flag == 1 || !board.tile_is_empty(m.to)
+}
+
+fn is_zeroing(board: &Board, m: &Move) -> bool {
+    is_capture(board, m) || board.piece_is_type(m.from, board.current_player(), Piece::Pawn)
+}
+
+/// white and black piece counts as one number
+fn material_key(counts: &[[u8; 6]; 2]) -> u64 {
+    counts
+        .iter()
+        .flatten()
+        .fold(0, |key, &n| key << 4 | n as u64)
+}
+
+/// pieces by color and type from a name like `KRvKP`, white first
+fn parse_name(name: &str) -> Option<[[u8; 6]; 2]> {
+    let (white, black) = name.split_once('v')?;
+    let mut counts = [[0; 6]; 2];
+    for (color, side) in [white, black].into_iter().enumerate() {
+        for c in side.chars() {
+            let piece = match c {
+                'P' => Piece::Pawn,
+                'N' => Piece::Knight,
+                'B' => Piece::Bishop,
+                'R' => Piece::Rook,
+                'Q' => Piece::Queen,
+                'K' => Piece::King,
+                _ => return None,
+            };
+            counts[color][piece as usize] += 1;
+        }
+        if counts[color][Piece::King as usize] != 1 {
+            return None;
+        }
+    }
+    let total: u8 = counts.iter().flatten().sum();
+    (total <= encoding::MAX_PIECES as u8).then_some(counts)
+}
+
+/// the name of a table like `KRvKP`, the strongest pieces first
+fn table_name(counts: &[[u8; 6]; 2]) -> String {
+    let side = |counts: &[u8; 6]| {
+        let mut name = String::new();
+        for piece in (0..6).rev() {
+            for _ in 0..counts[piece] {
+                name.push(b"PNBRQK"[piece] as char);
+            }
+        }
+        name
+    };
+    format!("{}v{}", side(&counts[0]), side(&counts[1]))
+}
+
+fn material(counts: &[[u8; 6]; 2]) -> Material {
+    let mut pieces = vec![];
+    for (color, counts) in [Color::White, Color::Black].into_iter().zip(counts) {
+        for piece in Piece::ALL_NONEMPTY {
+            pieces.extend((0..counts[piece as usize]).map(|_| piece_code(piece, color)));
+        }
+    }
+    Material::new(pieces)
+}
+
+/// pieces of the board by color and type
+fn piece_counts(board: &Board) -> [[u8; 6]; 2] {
+    let mut counts = [[0; 6]; 2];
+    for color in [Color::White, Color::Black] {
+        for piece in Piece::ALL_NONEMPTY {
+            counts[color as usize][piece as usize] = board.pieces(color, piece).count_set_bits();
+        }
+    }
+    counts
+}
+
+/// piece codes and squares of the board, with `flip` as seen by the other
+/// color
+fn placement(board: &Board, flip: bool) -> Vec<(u8, u8)> {
+    let mut pieces = vec![];
+    for color in [Color::White, Color::Black] {
+        for piece in Piece::ALL_NONEMPTY {
+            for pos in board.pieces(color, piece) {
+                pieces.push((
+                    piece_code(piece, color) ^ (8 * flip as u8),
+                    pos.0 ^ (56 * flip as u8),
+                ));
+            }
+        }
+    }
+    pieces
+}
+
+struct Entry {
+    name: String,
+    /// pieces of white and black as in the name
+    counts: [[u8; 6]; 2],
+    /// the win/draw/loss and the distance to zeroing file
+    paths: [Option<PathBuf>; 2],
+    /// read on first use
+    tables: [OnceLock<Option<Table>>; 2],
+}
+
+impl Entry {
+    fn symmetric(&self) -> bool {
+        self.counts[0] == self.counts[1]
+    }
+
+    fn table(&self, kind: Kind) -> Option<&Table> {
+        let i = kind as usize;
+        self.tables[i]
+            .get_or_init(|| {
+                let bytes = std::fs::read(self.paths[i].as_ref()?).ok()?;
+                Table::new(bytes, kind, material(&self.counts)).ok()
+            })
+            .as_ref()
+    }
+}
+
+/// a root move with its rank by the tables, higher is better, and the
+/// score to show for it
+#[derive(Debug, Clone)]
+pub struct RootMove {
+    pub mov: Move,
+    pub rank: i32,
+    pub score: i32,
+}
+
+/// the root moves ranked by the tables, best first
+#[derive(Debug, Clone)]
+pub struct RootRanking {
+    pub moves: Vec<RootMove>,
+    /// ranked by the distance to zeroing, otherwise only by win/draw/loss
+    pub by_dtz: bool,
+}
+
+/// all tables found in a list of directories. The files are only read
+/// once a position of theirs gets probed.
+pub struct Tablebases {
+    entries: Vec<Entry>,
+    /// material key of both colors to the entry
+    keys: HashMap<u64, usize>,
+    max_pieces: u8,
+}
+
+impl fmt::Debug for Tablebases {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.entries.iter().map(|e| e.name.as_str()).collect();
+        f.debug_struct("Tablebases")
+            .field("tables", &names)
+            .finish()
+    }
+}
+
+/// tables never change once loaded, so all threads probe them at once
+const _: fn() = || {
+    fn assert_sync<T: Send + Sync>() {}
+    assert_sync::<Tablebases>();
+};
+
+impl Tablebases {
+    /// finds the tables in the directories of `path`, separated by `:`
+    /// (`;` on windows)
+    pub fn load(path: &str) -> Result<Self, String> {
+        let separator = if cfg!(windows) { ';' } else { ':' };
+        let mut tb = Tablebases {
+            entries: vec![],
+            keys: HashMap::new(),
+            max_pieces: 0,
+        };
+        for dir in path.split(separator).filter(|d| !d.is_empty()) {
+            let files = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir, e))?;
+            let mut files: Vec<PathBuf> = files.filter_map(|f| Some(f.ok()?.path())).collect();
+            files.sort();
+            for file in files {
+                tb.add(&file)?;
+            }
+        }
+        Ok(tb)
+    }
+
+    fn add(&mut self, file: &Path) -> Result<(), String> {
+        let kind = match file.extension().and_then(|e| e.to_str()) {
+            Some("rtbw") => Kind::Wdl,
+            Some("rtbz") => Kind::Dtz,
+            _ => return Ok(()),
+        };
+        let name = file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let counts = parse_name(name).ok_or_else(|| {
+            format!(
+                "{}: not a table of up to {} pieces",
+                file.display(),
+                encoding::MAX_PIECES
+            )
+        })?;
+        let key = material_key(&counts);
+        let i = *self.keys.entry(key).or_insert_with(|| {
+            self.entries.push(Entry {
+                name: name.to_string(),
+                counts,
+                paths: [None, None],
+                tables: [OnceLock::new(), OnceLock::new()],
+            });
+            self.entries.len() - 1
+        });
+        // the first directory with the file wins
+        let entry = &mut self.entries[i];
+        if entry.paths[kind as usize].is_none() {
+            entry.paths[kind as usize] = Some(file.to_path_buf());
+        }
+        self.keys.insert(material_key(&[counts[1], counts[0]]), i);
+        let pieces: u8 = counts.iter().flatten().sum();
+        if entry.paths[Kind::Wdl as usize].is_some() {
+            self.max_pieces = self.max_pieces.max(pieces);
+        }
+        Ok(())
+    }
+
+    /// the number of win/draw/loss tables
+    pub fn len(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.paths[Kind::Wdl as usize].is_some())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the most pieces, kings included, of any win/draw/loss table
+    pub fn max_pieces(&self) -> u8 {
+        self.max_pieces
+    }
+
+    /// whether the position has few enough pieces and no castling rights
+    pub fn covers(&self, board: &Board) -> bool {
+        board.piece_count() <= self.max_pieces && !board.has_castling_rights()
+    }
+
+    /// the stored value of the position, a win/draw/loss or the distance to
+    /// zeroing in plies for a position that is `wdl`
+    fn probe_table(&self, board: &Board, kind: Kind, wdl: Wdl) -> Result<i32, ProbeError> {
+        // the kings alone are a draw without a table
+        if board.piece_count() == 2 {
+            return Ok(0);
+        }
+        let counts = piece_counts(board);
+        let entry = &self.entries[*self
+            .keys
+            .get(&material_key(&counts))
+            .ok_or(ProbeError::Missing)?];
+        let table = entry.table(kind).ok_or(ProbeError::Missing)?;
+
+        // tables have their white as the side listed first, and symmetric
+        // ones only store white to move
+        let black = board.current_player() == Color::Black;
+        let flip = counts != entry.counts || (entry.symmetric() && black);
+        let stm = (flip ^ black) as usize;
+        let mut pieces = placement(board, flip);
+
+        let material = &table.material;
+        let file = encoding::arrange(material, table.parts[0][0].layout.pieces[0], &mut pieces);
+        // distance to zeroing tables only store one side to move
+        if kind == Kind::Dtz
+            && table.parts[0][file].flags & STM != stm as u8
+            && (!entry.symmetric() || material.has_pawns)
+        {
+            return Err(ProbeError::ChangeStm);
+        }
+        let part = &table.parts[stm % table.parts.len()][file];
+        let idx = part.layout.index_of(material, &mut pieces);
+        let value = table.value(stm, file, idx);
+        Ok(match kind {
+            Kind::Wdl => value as i32 - 2,
+            Kind::Dtz => table.map_dtz(file, value, wdl as i8),
+        })
+    }
+
+    /// the result with the captures, and with `zeroing_moves` the pawn moves
+    /// too, searched instead of taken from the table, which is not right
+    /// about en passant and may have any value where the best move is one
+    /// of them. Tells whether a zeroing move is best.
+    fn search(&self, board: &Board, zeroing_moves: bool) -> Option<(Wdl, bool)> {
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for m in &board.move_list {
+            if !is_capture(board, m) && (!zeroing_moves || !is_zeroing(board, m)) {
+                continue;
+            }
+            searched += 1;
+            let mut child = board.clone();
+            child.make_move(m);
+            let value = -self.search(&child, false)?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        let no_more_moves = searched > 0 && searched == board.move_list.len();
+        let value = match no_more_moves {
+            true => best,
+            false => Wdl::from_value(self.probe_table(board, Kind::Wdl, Wdl::Draw).ok()?),
+        };
+        match best >= value {
+            true => Some((best, best > Wdl::Draw || no_more_moves)),
+            false => Some((value, false)),
+        }
+    }
+
+    /// the result of the position as if the halfmove clock was zero, none
+    /// without the table
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        self.search(board, false).map(|(wdl, _)| wdl)
+    }
+
+    /// plies until the next zeroing move that keeps the result, negative if
+    /// the side to move loses, 0 for a draw. A cursed win or blessed loss
+    /// is 100 plies further away. Only exact right after a zeroing move,
+    /// otherwise it may be a ply more.
+    pub fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        let sign = (wdl as i32).signum();
+        match self.probe_table(board, Kind::Dtz, wdl) {
+            Ok(dtz) => {
+                let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+                return Some((dtz + 100 * cursed as i32) * sign);
+            }
+            Err(ProbeError::Missing) => return None,
+            Err(ProbeError::ChangeStm) => (),
+        }
+
+        // the table has the other side to move, so look one ply ahead for
+        // the fastest win or the slowest loss
+        let mut min_dtz = i32::MAX;
+        for m in &board.move_list {
+            let zeroing = is_zeroing(board, m);
+            let mut child = board.clone();
+            child.make_move(m);
+            // the sign of a zeroing move is from the position after it
+            let mut dtz = match zeroing {
+                true => -dtz_before_zeroing(self.search(&child, false)?.0),
+                false => -self.probe_dtz(&child)?,
+            };
+            if dtz == 1 && child.in_check() && child.move_list.is_empty() {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == sign {
+                min_dtz = dtz;
+            }
+        }
+        // mated
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    /// ranks the root moves by the tables, best first, none if the position
+    /// is not covered or a table is missing. Wins are ranked by how close
+    /// the next zeroing move is, so the engine makes progress, and a win
+    /// the 50 move rule would take away counts as almost a draw.
+    pub fn rank_root_moves(&self, board: &Board) -> Option<RootRanking> {
+        if !self.covers(board) || board.move_list.is_empty() {
+            return None;
+        }
+        let (mut moves, by_dtz) = match self.root_probe_dtz(board) {
+            Some(moves) => (moves, true),
+            None => (self.root_probe_wdl(board)?, false),
+        };
+        moves.sort_by_key(|m| -m.rank);
+        Some(RootRanking { moves, by_dtz })
+    }
+
+    fn root_probe_dtz(&self, board: &Board) -> Option<Vec<RootMove>> {
+        let clock = board.halfmove_clock() as i32;
+        let repeated = board.repeated();
+        let mut moves = vec![];
+        for m in &board.move_list {
+            let mut child = board.clone();
+            child.make_move(m);
+            let mut dtz = if child.halfmove_clock() == 0 {
+                dtz_before_zeroing(-self.probe_wdl(&child)?)
+            } else if matches!(child.game_state, GameState::Draw) {
+                // a repetition or the 50 move rule
+                0
+            } else {
+                let dtz = -self.probe_dtz(&child)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && child.in_check() && child.move_list.is_empty() {
+                dtz = 1;
+            }
+
+            let rank = match dtz {
+                1.. if dtz + clock <= 99 && !repeated => MAX_DTZ - dtz,
+                1.. => MAX_DTZ / 2 - (dtz + clock),
+                ..=-1 if -dtz * 2 + clock < 100 => -MAX_DTZ - dtz,
+                ..=-1 => -MAX_DTZ / 2 + (-dtz + clock),
+                0 => 0,
+            };
+            moves.push(RootMove {
+                mov: m.clone(),
+                rank,
+                score: rank_score(rank),
+            });
+        }
+        Some(moves)
+    }
+
+    fn root_probe_wdl(&self, board: &Board) -> Option<Vec<RootMove>> {
+        let mut moves = vec![];
+        for m in &board.move_list {
+            let mut child = board.clone();
+            child.make_move(m);
+            let wdl = match child.game_state {
+                GameState::Draw => Wdl::Draw,
+                _ => -self.probe_wdl(&child)?,
+            };
+            let (rank, score) = match wdl {
+                Wdl::Loss => (-MAX_DTZ, -TB_WIN_SCORE),
+                Wdl::BlessedLoss => (-MAX_DTZ + 101, -2),
+                Wdl::Draw => (0, 0),
+                Wdl::CursedWin => (MAX_DTZ - 101, 2),
+                Wdl::Win => (MAX_DTZ, TB_WIN_SCORE),
+            };
+            moves.push(RootMove {
+                mov: m.clone(),
+                rank,
+                score,
+            });
+        }
+        Some(moves)
+    }
+}
+
+/// above any rank of a distance to zeroing
+const MAX_DTZ: i32 = 1 << 18;
+
+/// a sure win or loss, otherwise a few centipawns that grow as the 50
+/// move draw gets further away
+fn rank_score(rank: i32) -> i32 {
+    let bound = MAX_DTZ / 2 - 100;
+    let pawn = PIECE_VALUES[Piece::Pawn as usize];
+    match rank {
+        r if r >= bound => TB_WIN_SCORE,
+        r if r > 0 => (r - (MAX_DTZ / 2 - 200)).max(3) * pawn / 200,
+        0 => 0,
+        r if r > -bound => (r + (MAX_DTZ / 2 - 200)).min(-3) * pawn / 200,
+        _ => -TB_WIN_SCORE,
+    }
+}
@@ -0,0 +1,390 @@
+//! how positions are numbered in syzygy tables, shared by the prober and
+//! the generator. Squares are 0 (a1) to 63 (h8) and pieces are stored as
+//! in the files: 1 to 6 for the white pawn to king, 9 to 14 for black.
+
+use crate::board::helper::{Color, Piece};
+use std::sync::LazyLock;
+
+/// pieces per table, the encoding supports up to 7
+pub const MAX_PIECES: usize = 7;
+
+pub const WHITE_PAWN: u8 = 1;
+pub const WHITE_KING: u8 = 6;
+
+pub fn piece_code(piece: Piece, color: Color) -> u8 {
+    piece as u8 + 1 + 8 * color as u8
+}
+
+pub fn piece_of(code: u8) -> (Piece, Color) {
+    let color = if code & 8 == 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    (Piece::ALL_NONEMPTY[(code & 7) as usize - 1], color)
+}
+
+pub fn is_pawn(code: u8) -> bool {
+    code & 7 == WHITE_PAWN
+}
+
+/// below zero under the a1-h8 diagonal, above zero over it
+fn off_diagonal(sq: u8) -> i8 {
+    (sq / 8) as i8 - (sq % 8) as i8
+}
+
+fn flip_diagonal(sq: u8) -> u8 {
+    ((sq >> 3) | (sq << 3)) & 63
+}
+
+pub struct Maps {
+    /// ways to place k pieces on n squares
+    pub binomial: [[u64; 64]; MAX_PIECES],
+    /// squares of the a1-d1-d4 triangle to 0..10, the diagonal last
+    map_a1d1d4: [u64; 64],
+    /// the 462 placements of two kings with the first in the triangle
+    map_kk: [[u64; 64]; 10],
+    /// squares below the a1-h8 diagonal to 0..28
+    map_b1h1h7: [u64; 64],
+    /// a2-h7 to 0..48, the leading pawn has the highest value: next to
+    /// the edge and on the lowest rank
+    pub map_pawns: [u64; 64],
+    /// index of the leading pawns by their count and the square of the first
+    lead_pawn_idx: [[u64; 64]; 6],
+    /// number of leading pawn placements by their count and file
+    pub lead_pawns_size: [[u64; 4]; 6],
+}
+
+pub static MAPS: LazyLock<Maps> = LazyLock::new(Maps::new);
+
+impl Maps {
+    fn new() -> Self {
+        let mut m = Maps {
+            binomial: [[0; 64]; MAX_PIECES],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            map_b1h1h7: [0; 64],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for sq in 0..64 {
+            if off_diagonal(sq) < 0 {
+                m.map_b1h1h7[sq as usize] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal = vec![];
+        code = 0;
+        for sq in 0..=27u8 {
+            if off_diagonal(sq) < 0 && sq % 8 <= 3 {
+                m.map_a1d1d4[sq as usize] = code;
+                code += 1;
+            } else if off_diagonal(sq) == 0 && sq % 8 <= 3 {
+                diagonal.push(sq);
+            }
+        }
+        for sq in diagonal {
+            m.map_a1d1d4[sq as usize] = code;
+            code += 1;
+        }
+
+        let king_near = |a: u8, b: u8| (a / 8).abs_diff(b / 8) <= 1 && (a % 8).abs_diff(b % 8) <= 1;
+        let mut both_on_diagonal = vec![];
+        code = 0;
+        for idx in 0..10 {
+            for s1 in 0..=27u8 {
+                // b1 is mapped to 0, like a1 that is on the diagonal
+                if m.map_a1d1d4[s1 as usize] != idx || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64u8 {
+                    if king_near(s1, s2) || (off_diagonal(s1) == 0 && off_diagonal(s2) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        m.map_kk[idx as usize][s2 as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            m.map_kk[idx as usize][s2 as usize] = code;
+            code += 1;
+        }
+
+        m.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                m.binomial[k][n] = if k > 0 { m.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { m.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 47;
+        for lead in 1..6 {
+            for file in 0..4u8 {
+                let mut idx = 0;
+                for rank in 1..7u8 {
+                    let sq = rank * 8 + file;
+                    if lead == 1 {
+                        m.map_pawns[sq as usize] = available;
+                        m.map_pawns[(sq ^ 7) as usize] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    m.lead_pawn_idx[lead][sq as usize] = idx;
+                    idx += m.binomial[lead - 1][m.map_pawns[sq as usize] as usize];
+                }
+                m.lead_pawns_size[lead][file as usize] = idx;
+            }
+        }
+        m
+    }
+}
+
+/// the pieces of a table: how many of each there are and which color
+/// leads when there are pawns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Material {
+    /// piece codes, sorted
+    pub pieces: Vec<u8>,
+    pub has_pawns: bool,
+    /// some piece other than a king is there only once
+    pub unique_pieces: bool,
+    /// pawns of the leading color, then of the other one
+    pub pawn_count: [usize; 2],
+}
+
+impl Material {
+    pub fn new(mut pieces: Vec<u8>) -> Self {
+        pieces.sort_unstable();
+        let count = |code: u8| pieces.iter().filter(|&&p| p == code).count();
+        let unique_pieces = pieces.iter().any(|&p| p & 7 != WHITE_KING && count(p) == 1);
+        let (white, black) = (count(WHITE_PAWN), count(WHITE_PAWN + 8));
+        // the side with fewer pawns leads, that compresses better
+        let white_leads = black == 0 || (white > 0 && black >= white);
+        let pawn_count = match white_leads {
+            true => [white, black],
+            false => [black, white],
+        };
+        Material {
+            has_pawns: white + black > 0,
+            unique_pieces,
+            pawn_count,
+            pieces,
+        }
+    }
+
+    /// pawns on both sides, they are encoded as two groups
+    pub fn both_pawns(&self) -> bool {
+        self.has_pawns && self.pawn_count[1] > 0
+    }
+}
+
+/// how the pieces of one side to move and leading pawn file are ordered
+/// and grouped in a table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    /// piece codes in the order of the encoding
+    pub pieces: Vec<u8>,
+    /// sizes of the groups of pieces that are encoded together
+    pub group_len: Vec<usize>,
+    /// multiplier of each group, the last one is the size of the table
+    pub group_idx: Vec<u64>,
+}
+
+impl Layout {
+    /// groups the pieces. `order` gives the position of the leading group
+    /// and of the second pawn group among the multipliers, 15 if unused.
+    pub fn new(material: &Material, pieces: Vec<u8>, order: [u8; 2], file: usize) -> Self {
+        let maps = &*MAPS;
+        let mut first_len: i32 = match (material.has_pawns, material.unique_pieces) {
+            (true, _) => 0,
+            (false, true) => 3,
+            (false, false) => 2,
+        };
+        let mut group_len = vec![1];
+        for i in 1..pieces.len() {
+            first_len -= 1;
+            if first_len > 0 || pieces[i] == pieces[i - 1] {
+                *group_len.last_mut().unwrap() += 1;
+            } else {
+                group_len.push(1);
+            }
+        }
+
+        let n = group_len.len();
+        let pp = material.both_pawns();
+        let mut next = if pp { 2 } else { 1 };
+        let mut free_squares = 64 - group_len[0] - if pp { group_len[1] } else { 0 };
+        let mut group_idx = vec![0; n + 1];
+        let mut idx = 1;
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                group_idx[0] = idx;
+                idx *= match (material.has_pawns, material.unique_pieces) {
+                    (true, _) => maps.lead_pawns_size[group_len[0]][file],
+                    (false, true) => 31332,
+                    (false, false) => 462,
+                };
+            } else if k == order[1] as usize {
+                group_idx[1] = idx;
+                idx *= maps.binomial[group_len[1]][48 - group_len[0]];
+            } else {
+                group_idx[next] = idx;
+                idx *= maps.binomial[group_len[next]][free_squares];
+                free_squares -= group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        group_idx[n] = idx;
+        Layout {
+            pieces,
+            group_len,
+            group_idx,
+        }
+    }
+
+    /// the index of `pieces`, pairs of piece code and square that went
+    /// through [`arrange`]. The pieces after the leading pawns are put in
+    /// the order of the layout.
+    pub fn index_of(&self, material: &Material, pieces: &mut [(u8, u8)]) -> u64 {
+        let lead = if material.has_pawns {
+            self.group_len[0]
+        } else {
+            0
+        };
+        for i in lead..pieces.len() {
+            if let Some(j) = (i..pieces.len()).find(|&j| pieces[j].0 == self.pieces[i]) {
+                pieces.swap(i, j);
+            }
+        }
+        let mut squares: Vec<u8> = pieces.iter().map(|&(_, sq)| sq).collect();
+        self.index(material, &mut squares)
+    }
+
+    /// number of positions in the table
+    pub fn size(&self) -> u64 {
+        *self.group_idx.last().unwrap()
+    }
+
+    /// the index of a position. `squares` belong to `self.pieces` and the
+    /// leading pawn comes first.
+    pub fn index(&self, material: &Material, squares: &mut [u8]) -> u64 {
+        let maps = &*MAPS;
+        let n = squares.len();
+        if squares[0] % 8 > 3 {
+            for sq in squares.iter_mut() {
+                *sq ^= 7;
+            }
+        }
+
+        let mut idx;
+        if material.has_pawns {
+            let lead = self.group_len[0];
+            idx = maps.lead_pawn_idx[lead][squares[0] as usize];
+            squares[1..lead].sort_by_key(|&s| maps.map_pawns[s as usize]);
+            for (i, &sq) in squares.iter().enumerate().take(lead).skip(1) {
+                idx += maps.binomial[i][maps.map_pawns[sq as usize] as usize];
+            }
+        } else {
+            if squares[0] / 8 > 3 {
+                for sq in squares.iter_mut() {
+                    *sq ^= 56;
+                }
+            }
+            // the first piece of the leading group off the diagonal goes below it
+            for i in 0..self.group_len[0] {
+                match off_diagonal(squares[i]) {
+                    0 => continue,
+                    d if d > 0 => squares[i..]
+                        .iter_mut()
+                        .for_each(|sq| *sq = flip_diagonal(*sq)),
+                    _ => (),
+                }
+                break;
+            }
+
+            let sq = |i: usize| squares[i] as u64;
+            let on_diagonal = |i: usize| off_diagonal(squares[i]) == 0;
+            let rank = |i: usize| (squares[i] / 8) as u64;
+            if material.unique_pieces {
+                let adjust1 = (squares[1] > squares[0]) as u64;
+                let adjust2 = (squares[2] > squares[0]) as u64 + (squares[2] > squares[1]) as u64;
+                idx = if !on_diagonal(0) {
+                    (maps.map_a1d1d4[squares[0] as usize] * 63 + (sq(1) - adjust1)) * 62 + sq(2)
+                        - adjust2
+                } else if !on_diagonal(1) {
+                    (6 * 63 + rank(0) * 28 + maps.map_b1h1h7[squares[1] as usize]) * 62 + sq(2)
+                        - adjust2
+                } else if !on_diagonal(2) {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + rank(0) * 7 * 28
+                        + (rank(1) - adjust1) * 28
+                        + maps.map_b1h1h7[squares[2] as usize]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + rank(0) * 7 * 6
+                        + (rank(1) - adjust1) * 6
+                        + (rank(2) - adjust2)
+                };
+            } else {
+                idx =
+                    maps.map_kk[maps.map_a1d1d4[squares[0] as usize] as usize][squares[1] as usize];
+            }
+        }
+
+        idx *= self.group_idx[0];
+        let mut start = self.group_len[0];
+        let mut remaining_pawns = material.both_pawns();
+        for g in 1..self.group_len.len() {
+            let end = start + self.group_len[g];
+            squares[start..end].sort_unstable();
+            let mut group = 0;
+            for i in start..end {
+                let adjust = squares[..start].iter().filter(|&&s| squares[i] > s).count() as u64;
+                let sq = squares[i] as u64 - adjust - 8 * remaining_pawns as u64;
+                group += maps.binomial[i - start + 1][sq as usize];
+            }
+            remaining_pawns = false;
+            idx += group * self.group_idx[g];
+            start = end;
+        }
+        debug_assert!(start == n);
+        idx
+    }
+}
+
+/// the file of the leading pawn between a and d, the table is split by it
+pub fn lead_file(sq: u8) -> usize {
+    let file = (sq % 8) as usize;
+    file.min(7 - file)
+}
+
+/// puts the pawns of `lead`, the leading pawn code of the table, first with
+/// the leading one of them at the front and returns its file. Pawnless
+/// tables are not split by file.
+pub fn arrange(material: &Material, lead: u8, pieces: &mut [(u8, u8)]) -> usize {
+    if !material.has_pawns {
+        return 0;
+    }
+    pieces.sort_by_key(|&(code, _)| code != lead);
+    let count = pieces.iter().take_while(|&&(code, _)| code == lead).count();
+    let map_pawns = &MAPS.map_pawns;
+    let best = (0..count)
+        .max_by_key(|&i| map_pawns[pieces[i].1 as usize])
+        .unwrap();
+    pieces.swap(0, best);
+    lead_file(pieces[0].1)
+}
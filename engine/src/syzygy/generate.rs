@@ -0,0 +1,717 @@
+//! builds syzygy tables by retrograde analysis. Meant for the small tables
+//! of the tests: symmetric tables, tables with pawns on both sides and
+//! tables where the 50 move rule changes a result are not supported. The
+//! tables the captures and promotions lead to have to be in the same
+//! directory already.
+
+use super::encoding::{
+    arrange, is_pawn, lead_file, piece_of, Layout, Material, MAX_PIECES, WHITE_KING,
+};
+use super::table::{
+    DTZ_MAGIC, HAS_PAWNS, LOSS_PLIES, MAPPED, SINGLE_VALUE, SPLIT, WDL_MAGIC, WIN_PLIES,
+};
+use super::{
+    is_capture, is_zeroing, material, parse_name, piece_counts, placement, table_name, Tablebases,
+    Wdl,
+};
+use crate::board::helper::Color;
+use crate::board::Board;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::Path;
+
+/// the first byte of a slot no placement was found for
+const EMPTY: u8 = 64;
+
+/// more plies to the next zeroing move would need the 50 move rule
+const MAX_DTZ: u8 = 100;
+
+/// marks an edge that is a pawn move
+const ZEROING: u32 = 1 << 31;
+
+/// values are compressed in blocks of 2^6 bytes, with an entry of the
+/// sparse index every 2^10 values
+const BLOCK_SIZE: u8 = 6;
+const SPAN: u8 = 10;
+/// the values of a block have to fit in the 16 bit block lengths and
+/// offsets of the sparse index
+const MAX_BLOCK_VALUES: usize = 1 << 15;
+/// the pair tree has 12 bit symbols, 0xfff marks a leaf
+const MAX_SYMBOLS: usize = 0xfff;
+/// symbol lengths are stored in a byte, as one less
+const MAX_SYMBOL_VALUES: usize = 256;
+/// pairs that occur less often are not worth a symbol
+const MIN_PAIR_COUNT: u32 = 8;
+const MAX_CODE_LEN: u8 = 32;
+
+/// writes the `.rtbw` and `.rtbz` files of the table `name`, like `KQvKR`,
+/// to `dir`
+pub fn generate(dir: &str, name: &str) -> Result<(), String> {
+    let counts = parse_name(name).ok_or_else(|| format!("'{}' is not a table", name))?;
+    let material = material(&counts);
+    if counts[0] == counts[1] {
+        return Err(format!("{}: symmetric tables are not supported", name));
+    }
+    if material.both_pawns() {
+        return Err(format!("{}: pawns on both sides are not supported", name));
+    }
+    let tb = Tablebases::load(dir)?;
+
+    let mut gen = Generator::new(material, &tb);
+    gen.place(&mut vec![]);
+    gen.add_moves()?;
+    gen.solve_wdl();
+    gen.solve_dtz().map_err(|e| format!("{}: {}", name, e))?;
+
+    let dir = Path::new(dir);
+    let (wdl, dtz) = (gen.wdl_file(), gen.dtz_file());
+    let write = |ext: &str, bytes: Vec<u8>| {
+        let path = dir.join(format!("{}.{}", name, ext));
+        std::fs::write(&path, bytes).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    write("rtbw", wdl)?;
+    write("rtbz", dtz)
+}
+
+/// the pieces in the order of the encoding: pawns, kings, then the others
+/// with the rarest first, so a piece that is there only once is part of the
+/// leading group of pawnless tables
+fn piece_order(material: &Material) -> Vec<u8> {
+    let count = |code: u8| material.pieces.iter().filter(|&&p| p == code).count();
+    let mut pieces = material.pieces.clone();
+    pieces.sort_by_key(|&p| (!is_pawn(p), p & 7 != WHITE_KING, count(p), p));
+    pieces
+}
+
+struct Generator<'a> {
+    /// the tables of captures and promotions
+    tb: &'a Tablebases,
+    material: Material,
+    pieces: Vec<u8>,
+    layouts: Vec<Layout>,
+    /// where the slots of each file start
+    offsets: Vec<usize>,
+    /// slots of one side to move, a position is `side * slots + slot`
+    slots: usize,
+    /// a placement of `pieces` for every slot
+    squares: Vec<[u8; MAX_PIECES]>,
+    /// the positions that can not happen, their values do not matter
+    broken: Vec<bool>,
+    mated: Vec<bool>,
+    /// the best result of the moves that leave the table
+    known: Vec<Option<Wdl>>,
+    /// the moves that stay in the table, by the position they are made in
+    edge_start: Vec<usize>,
+    edges: Vec<u32>,
+    wdl: Vec<Option<Wdl>>,
+    /// plies to the next zeroing move, 0 for draws
+    dtz: Vec<u8>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(material: Material, tb: &'a Tablebases) -> Self {
+        let pieces = piece_order(&material);
+        let files = if material.has_pawns { 4 } else { 1 };
+        let layouts: Vec<Layout> = (0..files)
+            .map(|f| Layout::new(&material, pieces.clone(), [0, 0xf], f))
+            .collect();
+        let mut offsets = vec![0];
+        for layout in &layouts {
+            offsets.push(offsets.last().unwrap() + layout.size() as usize);
+        }
+        let slots = offsets.pop().unwrap();
+        Generator {
+            tb,
+            material,
+            pieces,
+            layouts,
+            offsets,
+            slots,
+            squares: vec![[EMPTY; MAX_PIECES]; slots],
+            broken: vec![],
+            mated: vec![],
+            known: vec![],
+            edge_start: vec![],
+            edges: vec![],
+            wdl: vec![],
+            dtz: vec![],
+        }
+    }
+
+    fn slot(&self, pieces: &mut [(u8, u8)]) -> usize {
+        let file = arrange(&self.material, self.pieces[0], pieces);
+        self.offsets[file] + self.layouts[file].index_of(&self.material, pieces) as usize
+    }
+
+    /// finds a placement for every slot. The first piece only goes where
+    /// the encoding turns the board to, every position can be mirrored
+    /// there.
+    fn place(&mut self, placed: &mut Vec<u8>) {
+        let i = placed.len();
+        if i == self.pieces.len() {
+            let king = |code: u8| placed[self.pieces.iter().position(|&p| p == code).unwrap()];
+            let (a, b) = (king(WHITE_KING), king(WHITE_KING + 8));
+            if (a / 8).abs_diff(b / 8) <= 1 && (a % 8).abs_diff(b % 8) <= 1 {
+                return;
+            }
+            let mut pieces: Vec<(u8, u8)> = self
+                .pieces
+                .iter()
+                .copied()
+                .zip(placed.iter().copied())
+                .collect();
+            let slot = self.slot(&mut pieces);
+            if self.squares[slot][0] == EMPTY {
+                self.squares[slot][..i].copy_from_slice(placed);
+            }
+            return;
+        }
+        for sq in 0..64 {
+            let code = self.pieces[i];
+            let allowed = match i {
+                _ if placed.contains(&sq) => false,
+                _ if is_pawn(code) && !(8..56).contains(&sq) => false,
+                0 if self.material.has_pawns => lead_file(sq) == (sq % 8) as usize,
+                0 => sq % 8 <= 3 && sq / 8 <= sq % 8,
+                _ => true,
+            };
+            if allowed {
+                placed.push(sq);
+                self.place(placed);
+                placed.pop();
+            }
+        }
+    }
+
+    /// the position of a slot as FEN, with white or black to move
+    fn fen(&self, slot: usize, side: usize) -> String {
+        let mut squares = [None; 64];
+        for (&code, &sq) in self.pieces.iter().zip(&self.squares[slot]) {
+            let (piece, color) = piece_of(code);
+            let letter = b"PNBRQK"[piece as usize] as char;
+            squares[sq as usize] = Some(if color == Color::White {
+                letter
+            } else {
+                letter.to_ascii_lowercase()
+            });
+        }
+        let mut ranks = vec![];
+        for rank in squares.chunks(8).rev() {
+            let mut fen = String::new();
+            let mut empty = 0;
+            for square in rank {
+                match square {
+                    Some(letter) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(*letter);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            ranks.push(fen);
+        }
+        format!(
+            "{} {} - - 0 1",
+            ranks.join("/"),
+            if side == 0 { 'w' } else { 'b' }
+        )
+    }
+
+    /// none for a slot without a placement, or if the side that is not to
+    /// move is in check
+    fn board(&self, id: usize) -> Option<Board> {
+        let (side, slot) = (id / self.slots, id % self.slots);
+        if self.squares[slot][0] == EMPTY || Board::new(&self.fen(slot, 1 - side)).in_check() {
+            return None;
+        }
+        Some(Board::new(&self.fen(slot, side)))
+    }
+
+    /// follows every move. Moves that stay in the table become edges,
+    /// the others are probed in the tables they lead to.
+    fn add_moves(&mut self) -> Result<(), String> {
+        let positions = 2 * self.slots;
+        for id in 0..positions {
+            self.edge_start.push(self.edges.len());
+            let board = self.board(id);
+            self.broken.push(board.is_none());
+            self.mated.push(
+                board
+                    .as_ref()
+                    .is_some_and(|b| b.in_check() && b.move_list.is_empty()),
+            );
+            self.known.push(None);
+            let Some(board) = board else {
+                continue;
+            };
+            let other_side = (1 - id / self.slots) * self.slots;
+            for m in &board.move_list {
+                let mut child = board.clone();
+                child.make_move(m);
+                if is_capture(&board, m) || m.flag >= 5 {
+                    let wdl = self.tb.probe_wdl(&child).map(|w| -w);
+                    let name = || table_name(&piece_counts(&child));
+                    let wdl = wdl.ok_or_else(|| format!("no table for {}", name()))?;
+                    if matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss) {
+                        return Err(format!(
+                            "cursed and blessed results of {} are not supported",
+                            name()
+                        ));
+                    }
+                    self.known[id] = self.known[id].max(Some(wdl));
+                } else {
+                    let child_id = other_side + self.slot(&mut placement(&child, false));
+                    let zeroing = if is_zeroing(&board, m) { ZEROING } else { 0 };
+                    self.edges.push(child_id as u32 | zeroing);
+                }
+            }
+        }
+        self.edge_start.push(self.edges.len());
+        Ok(())
+    }
+
+    /// the moves that stay in the table, as the child position and whether
+    /// the move is zeroing
+    fn edges(&self, id: usize) -> impl Iterator<Item = (usize, bool)> + '_ {
+        let edges = &self.edges[self.edge_start[id]..self.edge_start[id + 1]];
+        edges
+            .iter()
+            .map(|&e| ((e & !ZEROING) as usize, e & ZEROING != 0))
+    }
+
+    /// a position is won if a move leads to a lost one, and lost if all
+    /// moves lead to won ones. What is left after nothing changes is drawn.
+    fn solve_wdl(&mut self) {
+        let positions = self.broken.len();
+        self.wdl = vec![None; positions];
+        for id in 0..positions {
+            self.wdl[id] = match () {
+                _ if self.broken[id] => continue,
+                _ if self.mated[id] => Some(Wdl::Loss),
+                _ if self.known[id] == Some(Wdl::Win) => Some(Wdl::Win),
+                _ if self.known[id].is_none() && self.edges(id).next().is_none() => Some(Wdl::Draw),
+                _ => None,
+            };
+        }
+        loop {
+            let mut changed = false;
+            for id in 0..positions {
+                if self.broken[id] || self.wdl[id].is_some() {
+                    continue;
+                }
+                let mut all_win = self.known[id].is_none_or(|k| k == Wdl::Loss);
+                let mut any_loss = false;
+                for (child, _) in self.edges(id) {
+                    match self.wdl[child] {
+                        Some(Wdl::Loss) => any_loss = true,
+                        Some(Wdl::Win) => (),
+                        _ => all_win = false,
+                    }
+                }
+                if any_loss || all_win {
+                    self.wdl[id] = Some(if any_loss { Wdl::Win } else { Wdl::Loss });
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        for id in 0..positions {
+            if !self.broken[id] && self.wdl[id].is_none() {
+                self.wdl[id] = Some(Wdl::Draw);
+            }
+        }
+    }
+
+    /// counts the plies to the next zeroing move, level by level. A win
+    /// takes the fastest way there, a loss the slowest, and a move that
+    /// mates counts as zeroing.
+    fn solve_dtz(&mut self) -> Result<(), String> {
+        let positions = self.broken.len();
+        self.dtz = vec![0; positions];
+        for level in 1..=MAX_DTZ {
+            let mut changed = false;
+            for id in 0..positions {
+                if self.dtz[id] != 0 {
+                    continue;
+                }
+                let done = match self.wdl[id] {
+                    Some(Wdl::Win) if level == 1 => {
+                        self.known[id] == Some(Wdl::Win)
+                            || self.edges(id).any(|(child, zeroing)| {
+                                self.mated[child] || (zeroing && self.wdl[child] == Some(Wdl::Loss))
+                            })
+                    }
+                    Some(Wdl::Win) => self.edges(id).any(|(child, zeroing)| {
+                        !zeroing
+                            && self.wdl[child] == Some(Wdl::Loss)
+                            && self.dtz[child] == level - 1
+                    }),
+                    Some(Wdl::Loss) => self.edges(id).all(|(child, zeroing)| {
+                        zeroing || (self.dtz[child] != 0 && self.dtz[child] < level)
+                    }),
+                    _ => false,
+                };
+                if done {
+                    self.dtz[id] = level;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let decisive = |id: usize| matches!(self.wdl[id], Some(Wdl::Win | Wdl::Loss));
+        match (0..positions).find(|&id| decisive(id) && self.dtz[id] == 0) {
+            Some(id) => Err(format!(
+                "{} needs the 50 move rule",
+                self.fen(id % self.slots, id / self.slots)
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn files(&self) -> usize {
+        self.layouts.len()
+    }
+
+    /// the positions of one side to move and file
+    fn part(&self, side: usize, file: usize) -> std::ops::Range<usize> {
+        let start = side * self.slots + self.offsets[file];
+        start..start + self.layouts[file].size() as usize
+    }
+
+    /// both sides to move, the result as 0 to 4 for a loss to a win
+    fn wdl_file(&self) -> Vec<u8> {
+        let parts: Vec<Vec<Packed>> = (0..2)
+            .map(|side| {
+                let parts = (0..self.files()).map(|file| {
+                    let values = self
+                        .part(side, file)
+                        .map(|id| self.wdl[id].map(|w| (w as i8 + 2) as u16));
+                    pack(0, &fill(values))
+                });
+                parts.collect()
+            })
+            .collect();
+        self.write(WDL_MAGIC, &parts, None)
+    }
+
+    /// white to move only, in plies. Wins and losses each have a map from
+    /// the stored values to the distances, the most common ones first.
+    fn dtz_file(&self) -> Vec<u8> {
+        let mut parts = vec![];
+        let mut maps = vec![];
+        for file in 0..self.files() {
+            let range = self.part(0, file);
+            let mut map = [vec![], vec![], vec![], vec![]];
+            // the lists of wins and losses, see `WDL_MAP` of the reader
+            for (list, wdl) in [(0, Wdl::Win), (1, Wdl::Loss)] {
+                let mut counts: HashMap<u8, usize> = HashMap::new();
+                for id in range.clone().filter(|&id| self.wdl[id] == Some(wdl)) {
+                    *counts.entry(self.dtz[id] - 1).or_default() += 1;
+                }
+                let mut values: Vec<(usize, u8)> =
+                    counts.into_iter().map(|(v, n)| (n, v)).collect();
+                values.sort_by_key(|&(n, v)| (Reverse(n), v));
+                map[list] = values.into_iter().map(|(_, v)| v).collect();
+            }
+            let values = range.map(|id| {
+                let list = match self.wdl[id] {
+                    Some(Wdl::Win) => &map[0],
+                    Some(Wdl::Loss) => &map[1],
+                    _ => return None,
+                };
+                list.iter()
+                    .position(|&v| v == self.dtz[id] - 1)
+                    .map(|i| i as u16)
+            });
+            parts.push(pack(MAPPED | WIN_PLIES | LOSS_PLIES, &fill(values)));
+            maps.push(map);
+        }
+        self.write(DTZ_MAGIC, &[parts], Some(&maps))
+    }
+
+    /// lays out a table file, see `Table::parse` of the reader
+    fn write(
+        &self,
+        magic: [u8; 4],
+        parts: &[Vec<Packed>],
+        maps: Option<&[[Vec<u8>; 4]]>,
+    ) -> Vec<u8> {
+        let mut out = magic.to_vec();
+        out.push(
+            SPLIT
+                | if self.material.has_pawns {
+                    HAS_PAWNS
+                } else {
+                    0
+                },
+        );
+        for _ in 0..self.files() {
+            // both sides use the same order, with the leading group first
+            out.push(0);
+            out.extend(self.pieces.iter().map(|&p| p | p << 4));
+        }
+        let pad =
+            |out: &mut Vec<u8>, align: usize| out.resize(out.len().next_multiple_of(align), 0);
+        pad(&mut out, 2);
+        let each = |out: &mut Vec<u8>, f: &dyn Fn(&Packed) -> &[u8]| {
+            for file in 0..self.files() {
+                for side in parts {
+                    out.extend(f(&side[file]));
+                }
+            }
+        };
+        each(&mut out, &|p| &p.sizes);
+        if let Some(maps) = maps {
+            for map in maps {
+                for list in map {
+                    out.push(list.len() as u8);
+                    out.extend(list);
+                }
+            }
+            pad(&mut out, 2);
+        }
+        each(&mut out, &|p| &p.sparse_index);
+        each(&mut out, &|p| &p.block_lengths);
+        for file in 0..self.files() {
+            for side in parts {
+                pad(&mut out, 64);
+                out.extend(&side[file].data);
+            }
+        }
+        out
+    }
+}
+
+/// values that do not matter repeat the one before, that compresses best
+fn fill(values: impl Iterator<Item = Option<u16>>) -> Vec<u16> {
+    let values: Vec<Option<u16>> = values.collect();
+    let mut last = values.iter().flatten().next().copied().unwrap_or(0);
+    values
+        .into_iter()
+        .map(|v| {
+            last = v.unwrap_or(last);
+            last
+        })
+        .collect()
+}
+
+/// a part of a table as stored in the file
+#[derive(Default)]
+struct Packed {
+    /// the flags, then the single value or the block sizes and the code
+    sizes: Vec<u8>,
+    sparse_index: Vec<u8>,
+    block_lengths: Vec<u8>,
+    data: Vec<u8>,
+}
+
+#[derive(Clone, Copy)]
+enum Symbol {
+    Value(u16),
+    Pair(usize, usize),
+}
+
+/// compresses the values of a part. Pairs of symbols that follow each other
+/// often become new symbols, then the symbols get a huffman code.
+fn pack(flags: u8, values: &[u16]) -> Packed {
+    if values.iter().all(|&v| v == values[0]) {
+        return Packed {
+            sizes: vec![flags | SINGLE_VALUE, values[0] as u8],
+            ..Default::default()
+        };
+    }
+
+    let max = *values.iter().max().unwrap() as usize;
+    let mut symbols: Vec<Symbol> = (0..=max as u16).map(Symbol::Value).collect();
+    let mut lens = vec![1; symbols.len()];
+    let mut seq: Vec<usize> = values.iter().map(|&v| v as usize).collect();
+    while symbols.len() < MAX_SYMBOLS {
+        let mut counts: HashMap<(usize, usize), u32> = HashMap::new();
+        for pair in seq.windows(2) {
+            *counts.entry((pair[0], pair[1])).or_default() += 1;
+        }
+        let mut pairs: Vec<((usize, usize), u32)> = counts
+            .into_iter()
+            .filter(|&((a, b), n)| n >= MIN_PAIR_COUNT && lens[a] + lens[b] <= MAX_SYMBOL_VALUES)
+            .collect();
+        if pairs.is_empty() {
+            break;
+        }
+        pairs.sort_by_key(|&(pair, n)| (Reverse(n), pair));
+        pairs.truncate((MAX_SYMBOLS - symbols.len()).min(256));
+        let mut new = HashMap::new();
+        for (pair, _) in pairs {
+            new.insert(pair, symbols.len());
+            symbols.push(Symbol::Pair(pair.0, pair.1));
+            lens.push(lens[pair.0] + lens[pair.1]);
+        }
+        let mut next = Vec::with_capacity(seq.len());
+        let mut i = 0;
+        while i < seq.len() {
+            match seq.get(i + 1).and_then(|&b| new.get(&(seq[i], b))) {
+                Some(&s) => {
+                    next.push(s);
+                    i += 2;
+                }
+                None => {
+                    next.push(seq[i]);
+                    i += 1;
+                }
+            }
+        }
+        seq = next;
+    }
+
+    // canonical huffman code: longer codes get the lower symbols, the
+    // symbols without a code come last
+    let mut freq = vec![0u64; symbols.len()];
+    for &s in &seq {
+        freq[s] += 1;
+    }
+    let code_len = code_lengths(&freq);
+    let mut order: Vec<usize> = (0..symbols.len()).collect();
+    order.sort_by_key(|&s| (code_len[s] == 0, Reverse(code_len[s]), s));
+    let mut renumber = vec![0; symbols.len()];
+    for (id, &s) in order.iter().enumerate() {
+        renumber[s] = id;
+    }
+    let max_len = *code_len.iter().max().unwrap();
+    let min_len = *code_len.iter().filter(|&&l| l > 0).min().unwrap();
+    let mut count = vec![0usize; max_len as usize + 2];
+    for &l in code_len.iter().filter(|&&l| l > 0) {
+        count[l as usize] += 1;
+    }
+    let mut lowest = vec![0usize; max_len as usize + 1];
+    let mut base = vec![0u64; max_len as usize + 1];
+    for l in (min_len as usize..max_len as usize).rev() {
+        lowest[l] = lowest[l + 1] + count[l + 1];
+        base[l] = (base[l + 1] + count[l + 1] as u64) / 2;
+    }
+
+    let mut sizes = vec![flags, BLOCK_SIZE, SPAN, 0, 0, 0, 0, 0, max_len, min_len];
+    for l in min_len..=max_len {
+        sizes.extend((lowest[l as usize] as u16).to_le_bytes());
+    }
+    sizes.extend((symbols.len() as u16).to_le_bytes());
+    for &s in &order {
+        let (left, right) = match symbols[s] {
+            Symbol::Value(v) => (v, 0xfff),
+            Symbol::Pair(a, b) => (renumber[a] as u16, renumber[b] as u16),
+        };
+        sizes.extend([
+            left as u8,
+            (left >> 8) as u8 | (right << 4) as u8,
+            (right >> 4) as u8,
+        ]);
+    }
+    if symbols.len() % 2 == 1 {
+        sizes.push(0);
+    }
+
+    // whole symbols in blocks, the first bit of a code at the top
+    let block_bits = 8 << BLOCK_SIZE;
+    let mut data = vec![];
+    let mut block = vec![0u8; 1 << BLOCK_SIZE];
+    let (mut bits, mut block_values) = (0, 0);
+    let mut block_lengths = vec![];
+    let mut block_starts = vec![0];
+    for &s in &seq {
+        let len = code_len[s] as usize;
+        if bits + len > block_bits || block_values + lens[s] > MAX_BLOCK_VALUES {
+            data.append(&mut block);
+            block = vec![0u8; 1 << BLOCK_SIZE];
+            block_lengths.push(block_values - 1);
+            block_starts.push(block_starts.last().unwrap() + block_values);
+            (bits, block_values) = (0, 0);
+        }
+        let l = code_len[s] as usize;
+        let code = base[l] + (renumber[s] - lowest[l]) as u64;
+        for i in (0..len).rev() {
+            if code >> i & 1 != 0 {
+                block[bits / 8] |= 0x80 >> (bits % 8);
+            }
+            bits += 1;
+        }
+        block_values += lens[s];
+    }
+    data.append(&mut block);
+    block_lengths.push(block_values - 1);
+    sizes[4..8].copy_from_slice(&(block_lengths.len() as u32).to_le_bytes());
+
+    // every entry of the sparse index points at the middle of its span
+    let span = 1usize << SPAN;
+    let mut sparse_index = vec![];
+    for k in 0..values.len().div_ceil(span) {
+        let middle = k * span + span / 2;
+        let block =
+            block_starts.partition_point(|&start| start <= middle.min(values.len() - 1)) - 1;
+        sparse_index.extend((block as u32).to_le_bytes());
+        sparse_index.extend(((middle - block_starts[block]) as u16).to_le_bytes());
+    }
+
+    Packed {
+        sizes,
+        sparse_index,
+        block_lengths: block_lengths
+            .iter()
+            .flat_map(|&l| (l as u16).to_le_bytes())
+            .collect(),
+        data,
+    }
+}
+
+/// huffman code lengths, 0 for symbols that do not occur. Rare symbols
+/// count for more until no code is longer than the reader supports.
+fn code_lengths(freq: &[u64]) -> Vec<u8> {
+    let mut freq = freq.to_vec();
+    loop {
+        let mut lengths = vec![0u8; freq.len()];
+        let mut heap: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::new();
+        // nodes below the symbols are the leaves, the rest are merged pairs
+        let mut parent = vec![usize::MAX; freq.len()];
+        for (s, &f) in freq.iter().enumerate().filter(|(_, &f)| f > 0) {
+            heap.push(Reverse((f, s)));
+        }
+        if heap.len() == 1 {
+            let Reverse((_, s)) = heap.pop().unwrap();
+            lengths[s] = 1;
+            return lengths;
+        }
+        while heap.len() > 1 {
+            let Reverse((fa, a)) = heap.pop().unwrap();
+            let Reverse((fb, b)) = heap.pop().unwrap();
+            let node = parent.len();
+            parent.push(usize::MAX);
+            parent[a] = node;
+            parent[b] = node;
+            heap.push(Reverse((fa + fb, node)));
+        }
+        let mut depth = vec![0u8; parent.len()];
+        for node in (0..parent.len()).rev() {
+            if parent[node] != usize::MAX {
+                depth[node] = depth[parent[node]] + 1;
+            }
+        }
+        for (s, length) in lengths.iter_mut().enumerate() {
+            if freq[s] > 0 {
+                *length = depth[s];
+            }
+        }
+        if lengths.iter().all(|&l| l <= MAX_CODE_LEN) {
+            return lengths;
+        }
+        for f in freq.iter_mut().filter(|f| **f > 0) {
+            *f = f.div_ceil(2);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! reading syzygy files. Every table is split into up to two sides to
+//! move and, with pawns, four files of the leading pawn. Each part stores
+//! its values compressed by recursive pairing and a canonical huffman code.
+
+use super::encoding::{Layout, Material};
+
+pub const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+pub const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+/// header flags of the file
+pub const SPLIT: u8 = 1;
+pub const HAS_PAWNS: u8 = 2;
+
+/// flags of a part
+pub const STM: u8 = 1;
+pub const MAPPED: u8 = 2;
+pub const WIN_PLIES: u8 = 4;
+pub const LOSS_PLIES: u8 = 8;
+pub const WIDE: u8 = 16;
+pub const SINGLE_VALUE: u8 = 128;
+
+/// the symbol of a leaf in the pair tree
+const LEAF: u16 = 0xfff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Wdl,
+    Dtz,
+}
+
+/// one side to move and file of a table, offsets point into the file
+#[derive(Debug, Default)]
+pub struct Part {
+    pub layout: Layout,
+    pub flags: u8,
+    /// the value of a table with only one, otherwise the shortest code length
+    min_sym_len: u8,
+    block_size: usize,
+    span: u64,
+    blocks: usize,
+    /// the blocks and some padding
+    block_lengths_size: usize,
+    sparse_index: usize,
+    block_lengths: usize,
+    lowest_sym: usize,
+    /// the smallest code of each length, left aligned
+    base64: Vec<u64>,
+    /// how many values each symbol stands for, minus one
+    symlen: Vec<u8>,
+    btree: usize,
+    data: usize,
+    /// where the values of each result start in the dtz map
+    map_idx: [usize; 4],
+}
+
+#[derive(Debug)]
+pub struct Table {
+    bytes: Vec<u8>,
+    pub material: Material,
+    /// indexed by side to move and file
+    pub parts: Vec<Vec<Part>>,
+}
+
+/// makes sure the file is at least `len` bytes long
+fn need(bytes: &[u8], len: usize) -> Result<(), String> {
+    match bytes.len() >= len {
+        true => Ok(()),
+        false => Err("the table is truncated".to_string()),
+    }
+}
+
+/// little endian reads at an offset of the file
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// big endian, bytes past the end read as zero
+fn u32_be_at(bytes: &[u8], at: usize) -> u32 {
+    let mut word = [0; 4];
+    for (i, b) in word.iter_mut().enumerate() {
+        *b = bytes.get(at + i).copied().unwrap_or(0);
+    }
+    u32::from_be_bytes(word)
+}
+
+fn left(bytes: &[u8], btree: usize, sym: u16) -> u16 {
+    let at = btree + 3 * sym as usize;
+    ((bytes[at + 1] as u16 & 0xf) << 8) | bytes[at] as u16
+}
+
+fn right(bytes: &[u8], btree: usize, sym: u16) -> u16 {
+    let at = btree + 3 * sym as usize;
+    ((bytes[at + 2] as u16) << 4) | (bytes[at + 1] as u16 >> 4)
+}
+
+impl Table {
+    /// parses a table of the given pieces, with white being the side listed
+    /// first in the file name
+    pub fn new(bytes: Vec<u8>, kind: Kind, material: Material) -> Result<Self, String> {
+        let magic = match kind {
+            Kind::Wdl => WDL_MAGIC,
+            Kind::Dtz => DTZ_MAGIC,
+        };
+        if bytes.len() < 6 || bytes[..4] != magic {
+            return Err("not a syzygy table".to_string());
+        }
+        let mut table = Table {
+            bytes: vec![],
+            material,
+            parts: vec![],
+        };
+        table.parse(&bytes, kind)?;
+        table.bytes = bytes;
+        Ok(table)
+    }
+
+    fn parse(&mut self, bytes: &[u8], kind: Kind) -> Result<(), String> {
+        let material = &self.material;
+        let symmetric = is_symmetric(&material.pieces);
+        let flags = bytes[4];
+        if (flags & HAS_PAWNS != 0) != material.has_pawns || (flags & SPLIT != 0) == symmetric {
+            return Err("the table does not match its name".to_string());
+        }
+        let sides = match kind {
+            Kind::Wdl if !symmetric => 2,
+            _ => 1,
+        };
+        let files = if material.has_pawns { 4 } else { 1 };
+        let pp = material.both_pawns();
+        let n = material.pieces.len();
+
+        let mut at = 5;
+        need(bytes, at + 2)?;
+        let mut parts: Vec<Vec<Part>> = (0..sides).map(|_| vec![]).collect();
+        for file in 0..files {
+            let order = [
+                [bytes[at] & 0xf, if pp { bytes[at + 1] & 0xf } else { 0xf }],
+                [bytes[at] >> 4, if pp { bytes[at + 1] >> 4 } else { 0xf }],
+            ];
+            at += 1 + pp as usize;
+            need(bytes, at + n)?;
+            let pieces: Vec<Vec<u8>> = (0..sides)
+                .map(|side| {
+                    bytes[at..at + n]
+                        .iter()
+                        .map(|b| if side == 0 { b & 0xf } else { b >> 4 })
+                        .collect()
+                })
+                .collect();
+            at += n;
+            for side in 0..sides {
+                let mut sorted = pieces[side].clone();
+                sorted.sort_unstable();
+                if sorted != material.pieces {
+                    return Err("the table does not match its name".to_string());
+                }
+                parts[side].push(Part {
+                    layout: Layout::new(material, pieces[side].clone(), order[side], file),
+                    ..Default::default()
+                });
+            }
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side_parts in parts.iter_mut() {
+                at = side_parts[file].set_sizes(bytes, at)?;
+            }
+        }
+
+        if kind == Kind::Dtz {
+            for part in &mut parts[0] {
+                if part.flags & MAPPED == 0 {
+                    continue;
+                }
+                if part.flags & WIDE != 0 {
+                    at += at & 1;
+                    for i in 0..4 {
+                        need(bytes, at + 2)?;
+                        part.map_idx[i] = at + 2;
+                        at += 2 * u16_at(bytes, at) as usize + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        need(bytes, at + 1)?;
+                        part.map_idx[i] = at + 1;
+                        at += bytes[at] as usize + 1;
+                    }
+                }
+            }
+            at += at & 1;
+        }
+
+        for file in 0..files {
+            for side_parts in parts.iter_mut() {
+                let part = &mut side_parts[file];
+                part.sparse_index = at;
+                at += part.sparse_index_size() * 6;
+            }
+        }
+        for file in 0..files {
+            for side_parts in parts.iter_mut() {
+                let part = &mut side_parts[file];
+                part.block_lengths = at;
+                at += part.block_lengths_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side_parts in parts.iter_mut() {
+                let part = &mut side_parts[file];
+                at = (at + 0x3f) & !0x3f;
+                part.data = at;
+                at += part.blocks * part.block_size;
+            }
+        }
+        need(bytes, at)?;
+        self.parts = parts;
+        Ok(())
+    }
+
+    /// the stored value of a position, `side` and `file` pick the part
+    pub fn value(&self, side: usize, file: usize, idx: u64) -> u16 {
+        self.parts[side % self.parts.len()][file].decompress(&self.bytes, idx)
+    }
+
+    /// turns a stored dtz value into plies, `wdl` is -2 for a loss up to 2
+    /// for a win
+    pub fn map_dtz(&self, file: usize, value: u16, wdl: i8) -> i32 {
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let part = &self.parts[0][file];
+        let mut value = value as i32;
+        if part.flags & MAPPED != 0 {
+            let idx = part.map_idx[WDL_MAP[(wdl + 2) as usize]];
+            value = match part.flags & WIDE != 0 {
+                true => u16_at(&self.bytes, idx + 2 * value as usize) as i32,
+                false => self.bytes[idx + value as usize] as i32,
+            };
+        }
+        let in_moves = match wdl {
+            2 => part.flags & WIN_PLIES == 0,
+            -2 => part.flags & LOSS_PLIES == 0,
+            _ => true,
+        };
+        if in_moves {
+            value *= 2;
+        }
+        value + 1
+    }
+}
+
+/// both sides have the same pieces
+pub fn is_symmetric(pieces: &[u8]) -> bool {
+    let side = |black: u8| {
+        let mut side: Vec<u8> = pieces
+            .iter()
+            .filter(|&&p| p >> 3 == black)
+            .map(|p| p & 7)
+            .collect();
+        side.sort_unstable();
+        side
+    };
+    side(0) == side(1)
+}
+
+impl Part {
+    fn sparse_index_size(&self) -> usize {
+        match self.flags & SINGLE_VALUE {
+            0 => self.layout.size().div_ceil(self.span) as usize,
+            _ => 0,
+        }
+    }
+
+    /// reads the sizes and the huffman code, returns where the next part starts
+    fn set_sizes(&mut self, bytes: &[u8], mut at: usize) -> Result<usize, String> {
+        need(bytes, at + 2)?;
+        self.flags = bytes[at];
+        at += 1;
+        if self.flags & SINGLE_VALUE != 0 {
+            self.min_sym_len = bytes[at];
+            return Ok(at + 1);
+        }
+
+        need(bytes, at + 9)?;
+        let (block_size, span) = (bytes[at], bytes[at + 1]);
+        if block_size > 20 || span > 40 || bytes[at + 7] < bytes[at + 8] || bytes[at + 7] > 32 {
+            return Err("the table is corrupt".to_string());
+        }
+        self.block_size = 1 << block_size;
+        self.span = 1 << span;
+        self.blocks = u32_at(bytes, at + 3) as usize;
+        self.block_lengths_size = self.blocks + bytes[at + 2] as usize;
+        let max_sym_len = bytes[at + 7];
+        self.min_sym_len = bytes[at + 8];
+        at += 9;
+        self.lowest_sym = at;
+
+        let lengths = (max_sym_len - self.min_sym_len) as usize + 1;
+        need(bytes, at + 2 * lengths + 2)?;
+        self.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = |i: usize| u16_at(bytes, self.lowest_sym + 2 * i) as u64;
+            self.base64[i] = (self.base64[i + 1] + lowest(i)).wrapping_sub(lowest(i + 1)) / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base <<= 64 - i - self.min_sym_len as usize;
+        }
+        at += 2 * lengths;
+
+        let symbols = u16_at(bytes, at) as usize;
+        at += 2;
+        need(bytes, at + 3 * symbols)?;
+        self.btree = at;
+        self.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for sym in 0..symbols {
+            if !visited[sym] {
+                self.set_symlen(bytes, sym as u16, &mut visited)?;
+            }
+        }
+        Ok(at + 3 * symbols + (symbols & 1))
+    }
+
+    fn set_symlen(&mut self, bytes: &[u8], sym: u16, visited: &mut [bool]) -> Result<(), String> {
+        visited[sym as usize] = true;
+        let r = right(bytes, self.btree, sym);
+        if r == LEAF {
+            return Ok(());
+        }
+        let l = left(bytes, self.btree, sym);
+        for child in [l, r] {
+            match visited.get(child as usize) {
+                Some(false) => self.set_symlen(bytes, child, visited)?,
+                Some(true) => (),
+                None => return Err("the table is corrupt".to_string()),
+            }
+        }
+        let len = self.symlen[l as usize] as usize + self.symlen[r as usize] as usize + 1;
+        self.symlen[sym as usize] = len
+            .try_into()
+            .map_err(|_| "the table is corrupt".to_string())?;
+        Ok(())
+    }
+
+    fn decompress(&self, bytes: &[u8], idx: u64) -> u16 {
+        if self.flags & SINGLE_VALUE != 0 {
+            return self.min_sym_len as u16;
+        }
+
+        // the sparse index knows the block of every span-th value, the
+        // block lengths lead from there to the block of `idx`
+        let k = (idx / self.span) as usize;
+        let mut block = u32_at(bytes, self.sparse_index + 6 * k) as usize;
+        let mut offset = u16_at(bytes, self.sparse_index + 6 * k + 4) as i64;
+        offset += (idx % self.span) as i64 - (self.span / 2) as i64;
+        let block_length = |b: usize| u16_at(bytes, self.block_lengths + 2 * b) as i64;
+        while offset < 0 {
+            block -= 1;
+            offset += block_length(block) + 1;
+        }
+        while offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+        }
+
+        // walk the symbols of the block until the one holding the value
+        let mut at = self.data + block * self.block_size;
+        let mut buf = (u32_be_at(bytes, at) as u64) << 32 | u32_be_at(bytes, at + 4) as u64;
+        at += 8;
+        let mut buf_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buf < self.base64[len] {
+                len += 1;
+            }
+            sym = ((buf - self.base64[len]) >> (64 - len - self.min_sym_len as usize)) as u16;
+            sym += u16_at(bytes, self.lowest_sym + 2 * len);
+            if offset < self.symlen[sym as usize] as i64 + 1 {
+                break;
+            }
+            offset -= self.symlen[sym as usize] as i64 + 1;
+            let len = len + self.min_sym_len as usize;
+            buf <<= len;
+            buf_size -= len;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= (u32_be_at(bytes, at) as u64) << (64 - buf_size);
+                at += 4;
+            }
+        }
+
+        // the pairs of the symbol lie next to each other, so the value is
+        // found by going down the tree
+        while self.symlen[sym as usize] != 0 {
+            let l = left(bytes, self.btree, sym);
+            if offset < self.symlen[l as usize] as i64 + 1 {
+                sym = l;
+            } else {
+                offset -= self.symlen[l as usize] as i64 + 1;
+                sym = right(bytes, self.btree, sym);
+            }
+        }
+        left(bytes, self.btree, sym)
+    }
+}
@@ -0,0 +1,216 @@
+use crate::datagen::Sample;
+use crate::eval::{coefficients, phase, EvalParams, S, MAX_PHASE};
+use crate::game::GameResult;
+
+/// a position reduced to what the error depends on
+struct Entry {
+    /// parameter index and coefficient from white's view
+    coefs: Vec<(u32, i8)>,
+    /// share of the middlegame value, between 0 and 1
+    mg: f64,
+    /// game result from white's view, 1, 0.5 or 0
+    result: f64,
+}
+
+/// fits the evaluation parameters to game results by minimizing the mean
+/// squared error between the results and the sigmoid of the evaluation
+pub struct Tuner {
+    entries: Vec<Entry>,
+    /// middlegame and endgame value of every parameter
+    params: Vec<[f64; 2]>,
+    /// scaling of the sigmoid
+    pub k: f64,
+    // state of the adam optimizer
+    moments: Vec<[f64; 2]>,
+    velocities: Vec<[f64; 2]>,
+    steps: i32,
+}
+
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+impl Tuner {
+    pub fn new(params: &EvalParams) -> Self {
+        let params: Vec<[f64; 2]> = params.to_vec().iter().map(|s| [s.0 as f64, s.1 as f64]).collect();
+        Tuner {
+            entries: vec![],
+            moments: vec![[0.0; 2]; params.len()],
+            velocities: vec![[0.0; 2]; params.len()],
+            params,
+            k: 1.0,
+            steps: 0,
+        }
+    }
+
+    pub fn add(&mut self, sample: &Sample) {
+        let coefs = coefficients(&sample.board)
+            .into_iter()
+            .map(|(i, c)| (i as u32, c.clamp(i8::MIN as i32, i8::MAX as i32) as i8))
+            .collect();
+        let result = match sample.result {
+            GameResult::WhiteWins => 1.0,
+            GameResult::BlackWins => 0.0,
+            _ => 0.5,
+        };
+        self.entries.push(Entry {
+            coefs,
+            mg: phase(&sample.board) as f64 / MAX_PHASE as f64,
+            result,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn eval(&self, entry: &Entry) -> f64 {
+        entry
+            .coefs
+            .iter()
+            .map(|&(i, c)| {
+                let [mg, eg] = self.params[i as usize];
+                c as f64 * (mg * entry.mg + eg * (1.0 - entry.mg))
+            })
+            .sum()
+    }
+
+    /// mean squared error with the current parameters
+    pub fn error(&self) -> f64 {
+        let sum: f64 = self
+            .entries
+            .iter()
+            .map(|e| (e.result - sigmoid(self.eval(e), self.k)).powi(2))
+            .sum();
+        sum / self.entries.len().max(1) as f64
+    }
+
+    /// picks the `k` with the lowest error for the current parameters
+    pub fn fit_k(&mut self) {
+        let (mut low, mut high) = (0.0, 10.0);
+        // golden section search, the error is unimodal in k
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        for _ in 0..50 {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+            self.k = a;
+            let error_a = self.error();
+            self.k = b;
+            let error_b = self.error();
+            if error_a < error_b {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        self.k = (low + high) / 2.0;
+    }
+
+    /// one step of gradient descent over all positions, with adam
+    pub fn step(&mut self, learning_rate: f64) {
+        let mut gradient = vec![[0.0; 2]; self.params.len()];
+        let scale = self.k * std::f64::consts::LN_10 / 400.0;
+        for entry in &self.entries {
+            let s = sigmoid(self.eval(entry), self.k);
+            // derivative of the squared error by the evaluation
+            let d = -2.0 * (entry.result - s) * s * (1.0 - s) * scale;
+            for &(i, c) in &entry.coefs {
+                let g = &mut gradient[i as usize];
+                g[0] += d * c as f64 * entry.mg;
+                g[1] += d * c as f64 * (1.0 - entry.mg);
+            }
+        }
+
+        let (beta1, beta2): (f64, f64) = (0.9, 0.999);
+        self.steps += 1;
+        let bias1 = 1.0 - beta1.powi(self.steps);
+        let bias2 = 1.0 - beta2.powi(self.steps);
+        let n = self.entries.len().max(1) as f64;
+        let values = self.params.iter_mut().flatten();
+        let moments = self.moments.iter_mut().flatten();
+        let velocities = self.velocities.iter_mut().flatten();
+        for (((value, m), v), g) in values.zip(moments).zip(velocities).zip(gradient.iter().flatten()) {
+            let g = g / n;
+            *m = beta1 * *m + (1.0 - beta1) * g;
+            *v = beta2 * *v + (1.0 - beta2) * g * g;
+            *value -= learning_rate * (*m / bias1) / ((*v / bias2).sqrt() + 1e-8);
+        }
+    }
+
+    /// the current parameters rounded to whole centipawns
+    pub fn params(&self) -> EvalParams {
+        let values: Vec<S> = self
+            .params
+            .iter()
+            .map(|[mg, eg]| S(mg.round() as i32, eg.round() as i32))
+            .collect();
+        EvalParams::from_slice(&values)
+    }
+}
+
+fn format_values(values: &[S]) -> String {
+    values.iter().map(|s| format!("S({}, {})", s.0, s.1)).collect::<Vec<_>>().join(", ")
+}
+
+/// the parameters as the source of `eval/params.rs`
+pub fn params_to_rust(params: &EvalParams) -> String {
+    let names = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+    let mut src = String::from("// generated by the tuner, see `tune::params_to_rust`\n");
+    src.push_str("use super::{EvalParams, S};\n\n");
+    src.push_str("pub const DEFAULT_PARAMS: EvalParams = EvalParams {\n");
+    src.push_str(&format!("    material: [{}],\n", format_values(&params.material)));
+    src.push_str("    // rank 1 first\n");
+    src.push_str("    pst: [\n");
+    for (name, table) in names.iter().zip(&params.pst) {
+        src.push_str(&format!("        // {}\n", name));
+        src.push_str("        [\n");
+        for rank in table.chunks(8) {
+            src.push_str(&format!("            {},\n", format_values(rank)));
+        }
+        src.push_str("        ],\n");
+    }
+    src.push_str("    ],\n");
+    for (name, value) in [
+        ("doubled_pawn", params.doubled_pawn),
+        ("isolated_pawn", params.isolated_pawn),
+        ("backward_pawn", params.backward_pawn),
+    ] {
+        src.push_str(&format!("    {}: {},\n", name, format_values(&[value])));
+    }
+    src.push_str("    // by relative rank\n");
+    src.push_str(&format!("    connected_pawn: [{}],\n", format_values(&params.connected_pawn)));
+    src.push_str(&format!("    passed_pawn: [{}],\n", format_values(&params.passed_pawn)));
+    src.push_str(&format!("    passed_own_king: {},\n", format_values(&[params.passed_own_king])));
+    src.push_str(&format!("    passed_enemy_king: {},\n", format_values(&[params.passed_enemy_king])));
+    src.push_str("    // by the number of attacked squares\n");
+    for (name, table) in [
+        ("knight_mobility", &params.knight_mobility[..]),
+        ("bishop_mobility", &params.bishop_mobility),
+        ("rook_mobility", &params.rook_mobility),
+        ("queen_mobility", &params.queen_mobility),
+    ] {
+        src.push_str(&format!("    {}: [\n", name));
+        for values in table.chunks(8) {
+            src.push_str(&format!("        {},\n", format_values(values)));
+        }
+        src.push_str("    ],\n");
+    }
+    src.push_str(&format!("    king_attack: [{}],\n", format_values(&params.king_attack)));
+    src.push_str(&format!("    pawn_shield: [{}],\n", format_values(&params.pawn_shield)));
+    for (name, value) in [
+        ("king_open_file", params.king_open_file),
+        ("king_semi_open_file", params.king_semi_open_file),
+        ("bishop_pair", params.bishop_pair),
+        ("rook_open_file", params.rook_open_file),
+        ("rook_semi_open_file", params.rook_semi_open_file),
+        ("rook_seventh_rank", params.rook_seventh_rank),
+    ] {
+        src.push_str(&format!("    {}: {},\n", name, format_values(&[value])));
+    }
+    src.push_str("};\n");
+    src
+}
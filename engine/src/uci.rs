@@ -0,0 +1,638 @@
+use crate::board::helper::{Color, GameState};
+use crate::board::{Board, Move};
+use crate::book::Book;
+use crate::eval::nnue::Network;
+use crate::eval::trace::EvalTrace;
+use crate::eval::DEFAULT_PARAMS;
+use crate::game::START_FEN;
+use crate::notation::parse_uci;
+use crate::rng::Rng;
+use crate::search::control::{Clock, SearchControl, SystemClock};
+use crate::search::mate::MateSearch;
+use crate::search::tt::TranspositionTable;
+use crate::search::{
+    is_mate_score, search_with, time_for_move, PvLine, SearchInfo, SearchLimits, SearchOptions, SearchResult,
+    MATE_SCORE, MAX_PLY,
+};
+use crate::strength::Strength;
+use crate::syzygy::Tablebases;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+const MAX_HASH_MB: i64 = 65536;
+const MAX_THREADS: i64 = 256;
+const MAX_MULTIPV: i64 = 256;
+const MAX_MOVE_OVERHEAD: i64 = 5000;
+const MIN_ELO: i64 = Strength::MIN_ELO as i64;
+const MAX_ELO: i64 = Strength::MAX_ELO as i64;
+/// root moves are only reported once the search runs this long
+const CURRMOVE_DELAY: Duration = Duration::from_secs(1);
+
+/// where the engine writes to, shared with the search thread
+type Output = Arc<Mutex<dyn Write + Send>>;
+
+/// an engine speaking the universal chess interface, fed one line at a
+/// time. Searches run on their own thread, so `stop` and `ponderhit` can
+/// reach them.
+pub struct Uci {
+    out: Output,
+    clock: Arc<dyn Clock>,
+    board: Board,
+    /// evaluates every position given by `position`, set by the EvalFile option
+    network: Option<Arc<Network>>,
+    tt: Arc<TranspositionTable>,
+    options: SearchOptions,
+    /// subtracted from the thinking time for the lag of the GUI
+    move_overhead: Duration,
+    own_book: bool,
+    book: Option<Book>,
+    /// picks the book moves and the moves played at a limited strength
+    rng: Rng,
+    limit_strength: bool,
+    elo: u32,
+    /// adds the expected reply to `bestmove`
+    ponder: bool,
+    search: Option<RunningSearch>,
+}
+
+/// a search on its own thread, it writes `bestmove` when it ends
+struct RunningSearch {
+    control: Arc<SearchControl>,
+    /// an infinite search only ends with `stop`
+    infinite: bool,
+    /// set when the result is not wanted anymore, nothing more gets written
+    discard: Arc<AtomicBool>,
+    thread: JoinHandle<io::Result<()>>,
+}
+
+/// a parsed `go` command
+struct Go {
+    limits: SearchLimits,
+    ponder: bool,
+    infinite: bool,
+    /// moves of a mate to prove before searching as usual
+    mate: Option<u8>,
+}
+
+/// writes the output of a search
+struct Reporter {
+    out: Output,
+    tt: Arc<TranspositionTable>,
+    multipv: usize,
+    ponder: bool,
+}
+
+impl Default for Uci {
+    fn default() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl Uci {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self::with_clock(out, Arc::new(SystemClock::default()))
+    }
+
+    /// an engine that takes the time for its searches from `clock`
+    pub fn with_clock(out: impl Write + Send + 'static, clock: Arc<dyn Clock>) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Uci {
+            out: Arc::new(Mutex::new(out)),
+            clock,
+            board: Board::new(START_FEN),
+            network: None,
+            tt: Arc::new(TranspositionTable::new(TranspositionTable::DEFAULT_MB)),
+            options: SearchOptions::default(),
+            move_overhead: Duration::from_millis(10),
+            own_book: false,
+            book: None,
+            rng: Rng::new(seed),
+            limit_strength: false,
+            elo: MAX_ELO as u32,
+            ponder: false,
+            search: None,
+        }
+    }
+
+    /// the position set by the last `position` command
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// whether a search is running or waiting for `stop` or `ponderhit`
+    pub fn searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// waits until the running search has written its `bestmove`. A ponder
+    /// or infinite search only ends with `stop` or `ponderhit`.
+    pub fn wait(&mut self) -> io::Result<()> {
+        match self.search.take() {
+            Some(search) => search.thread.join().expect("the search thread panicked"),
+            None => Ok(()),
+        }
+    }
+
+    /// handles one line of input, returns false after `quit`
+    pub fn handle(&mut self, line: &str) -> io::Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&cmd, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+
+        // these reach a running search, everything else waits for it
+        match cmd {
+            "isready" => return self.write(|out| writeln!(out, "readyok")).map(|_| true),
+            "stop" => return self.stop(false).map(|_| true),
+            "ponderhit" => {
+                if let Some(search) = &self.search {
+                    search.control.ponderhit();
+                }
+                return Ok(true);
+            }
+            "quit" => return self.stop(false).map(|_| false),
+            _ => self.finish_search()?,
+        }
+
+        let mut messages = vec![];
+        match cmd {
+            "uci" => {
+                messages.push(format!("id name smogfish {}", env!("CARGO_PKG_VERSION")));
+                messages.push("id author the smogfish developers".to_string());
+                for option in [
+                    format!("Hash type spin default {} min 1 max {}", TranspositionTable::DEFAULT_MB, MAX_HASH_MB),
+                    format!("Threads type spin default 1 min 1 max {}", MAX_THREADS),
+                    format!("MultiPV type spin default 1 min 1 max {}", MAX_MULTIPV),
+                    format!("Move Overhead type spin default 10 min 0 max {}", MAX_MOVE_OVERHEAD),
+                    "Ponder type check default false".to_string(),
+                    "OwnBook type check default false".to_string(),
+                    "BookFile type string default <empty>".to_string(),
+                    "SyzygyPath type string default <empty>".to_string(),
+                    "EvalFile type string default <empty>".to_string(),
+                    "UCI_Chess960 type check default false".to_string(),
+                    "UCI_LimitStrength type check default false".to_string(),
+                    format!("UCI_Elo type spin default {} min {} max {}", MAX_ELO, MIN_ELO, MAX_ELO),
+                ] {
+                    messages.push(format!("option name {}", option));
+                }
+                messages.push("uciok".to_string());
+            }
+            "setoption" => {
+                if let Err(e) = parse_setoption(args).and_then(|(name, value)| self.set_option(&name, &value)) {
+                    messages.push(format!("info string {}", e));
+                }
+            }
+            "ucinewgame" => {
+                self.tt.clear();
+                self.set_board(Board::new(START_FEN));
+            }
+            "position" => match parse_position(args) {
+                Ok(board) => self.set_board(board),
+                Err(e) => messages.push(format!("info string {}", e)),
+            },
+            "go" => {
+                let mut warnings = vec![];
+                let go = parse_go(args, self.board.current_player(), &mut warnings);
+                messages.extend(warnings.iter().map(|w| format!("info string {}", w)));
+                self.write(|out| messages.iter().try_for_each(|m| writeln!(out, "{}", m)))?;
+                return self.go(go).map(|_| true);
+            }
+            // not part of the protocol, prints the evaluation of the position term by term
+            "eval" => {
+                let trace = EvalTrace::new(&self.board, &DEFAULT_PARAMS).to_string();
+                messages.extend(trace.lines().map(|l| l.to_string()));
+            }
+            _ => messages.push(format!("info string unknown command '{}'", cmd)),
+        }
+        self.write(|out| messages.iter().try_for_each(|m| writeln!(out, "{}", m)))?;
+        Ok(true)
+    }
+
+    /// makes the book moves and the moves at a limited strength repeatable
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    fn write(&self, f: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        f(&mut *out)?;
+        out.flush()
+    }
+
+    /// stops the running search, its `bestmove` is still written unless `discard`
+    fn stop(&mut self, discard: bool) -> io::Result<()> {
+        if let Some(search) = &self.search {
+            search.discard.store(discard, Ordering::SeqCst);
+            search.control.stop();
+        }
+        self.wait()
+    }
+
+    /// lets a normal search finish. A ponder or infinite search waits for a
+    /// `stop` that did not come, the GUI has moved on, so it is discarded.
+    fn finish_search(&mut self) -> io::Result<()> {
+        match &self.search {
+            Some(search) if search.infinite || search.control.pondering() => self.stop(true),
+            _ => self.wait(),
+        }
+    }
+
+    fn set_board(&mut self, mut board: Board) {
+        board.set_network(self.network.clone());
+        self.board = board;
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_lowercase().as_str() {
+            "hash" => {
+                self.tt = Arc::new(TranspositionTable::new(parse_spin(name, value, 1, MAX_HASH_MB)? as usize))
+            }
+            "threads" => self.options.threads = parse_spin(name, value, 1, MAX_THREADS)? as usize,
+            "multipv" => self.options.multipv = parse_spin(name, value, 1, MAX_MULTIPV)? as usize,
+            "move overhead" => {
+                self.move_overhead = Duration::from_millis(parse_spin(name, value, 0, MAX_MOVE_OVERHEAD)? as u64)
+            }
+            "ponder" => self.ponder = parse_check(name, value)?,
+            "ownbook" => self.own_book = parse_check(name, value)?,
+            "bookfile" => {
+                self.book = match value {
+                    "" | "<empty>" => None,
+                    path => Some(Book::load(path)?),
+                }
+            }
+            "syzygypath" => {
+                self.options.tablebases = match value {
+                    "" | "<empty>" => None,
+                    path => Some(Arc::new(Tablebases::load(path)?)),
+                }
+            }
+            // an empty path switches back to the handcrafted evaluation
+            "evalfile" => {
+                self.network = match value {
+                    "" | "<empty>" => None,
+                    path => Some(Arc::new(Network::load(path)?)),
+                };
+                self.set_board(self.board.clone());
+            }
+            // move generation only knows castling with the king on the e file
+            "uci_chess960" => {
+                if parse_check(name, value)? {
+                    return Err("UCI_Chess960 is not supported".to_string());
+                }
+            }
+            "uci_limitstrength" => self.limit_strength = parse_check(name, value)?,
+            "uci_elo" => self.elo = parse_spin(name, value, MIN_ELO, MAX_ELO)? as u32,
+            _ => return Err(format!("unknown option '{}'", name)),
+        }
+        Ok(())
+    }
+
+    /// starts the search on its own thread. While pondering the position
+    /// already has the expected move of the opponent played, and the time
+    /// limit only counts from `ponderhit`.
+    fn go(&mut self, go: Go) -> io::Result<()> {
+        let Go {
+            mut limits,
+            ponder,
+            infinite,
+            mate,
+        } = go;
+        if !ponder && !infinite {
+            if let Some(m) = self.book_move() {
+                return self.write(|out| writeln!(out, "info string book move\nbestmove {}", m));
+            }
+        }
+
+        if let Some(t) = limits.movetime {
+            limits.movetime = Some(t.saturating_sub(self.move_overhead).max(Duration::from_millis(1)));
+        }
+        let mut options = self.options.clone();
+        let strength = self.limit_strength.then(|| Strength::from_elo(self.elo));
+        if let Some(strength) = &strength {
+            limits = strength.limit(&limits);
+            options.multipv = options.multipv.max(strength.multipv);
+            // helper threads would make the same seed play different moves
+            options.threads = 1;
+            // and the tables would leave only the best moves to choose from
+            options.tablebases = None;
+        }
+        let mut rng = Rng::new(self.rng.next_u64());
+
+        let control = Arc::new(SearchControl::new(self.clock.clone(), ponder));
+        let discard = Arc::new(AtomicBool::new(false));
+        let reporter = Reporter {
+            out: self.out.clone(),
+            tt: self.tt.clone(),
+            multipv: self.options.multipv,
+            ponder: self.ponder,
+        };
+        let (board, tt) = (self.board.clone(), self.tt.clone());
+        let (thread_control, thread_discard) = (control.clone(), discard.clone());
+        let thread = thread::spawn(move || {
+            let control = thread_control;
+            // a proven mate is played as it is, otherwise the search runs as deep as the mate would be
+            let mut proven = None;
+            if let Some(moves) = mate {
+                let mut mates = MateSearch::new(&control);
+                proven = mates.find(&board, moves).map(|line| mate_result(&line, mates.nodes(), &control));
+                if proven.is_none() {
+                    // without lifting a depth cap of a limited strength
+                    limits.depth = Some(limits.depth.map_or(2 * moves, |d| d.min(2 * moves)));
+                    if !thread_discard.load(Ordering::SeqCst) {
+                        reporter.write_string(&format!("no mate in {} found", moves))?;
+                    }
+                }
+            }
+            let mate_found = proven.is_some();
+
+            let mut written = Ok(());
+            let mut report = |info: SearchInfo| {
+                if written.is_ok() && !thread_discard.load(Ordering::SeqCst) {
+                    written = reporter.write_info(&info, &control);
+                }
+            };
+            let result = match proven {
+                Some(result) => {
+                    report(SearchInfo::Iteration(&result));
+                    result
+                }
+                None => search_with(&board, &limits, &options, &tt, &control, &mut report),
+            };
+            written?;
+
+            // the GUI waits for the result of a ponder or infinite search
+            // until it sends ponderhit or stop
+            while (infinite || control.pondering()) && !control.stopped() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            if thread_discard.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            let line = match &strength {
+                Some(_) if mate_found => result.pv.clone(),
+                Some(strength) => strength.choose(&board, &result.lines, &mut rng).unwrap_or_default(),
+                None => result.pv.clone(),
+            };
+            reporter.write_bestmove(&result, &line)
+        });
+        self.search = Some(RunningSearch {
+            control,
+            infinite,
+            discard,
+            thread,
+        });
+        Ok(())
+    }
+
+    fn book_move(&mut self) -> Option<Move> {
+        match (&self.book, self.own_book) {
+            (Some(book), true) => book.pick(&self.board, &mut self.rng),
+            _ => None,
+        }
+    }
+}
+
+/// waits for a running search like any command would
+impl Drop for Uci {
+    fn drop(&mut self) {
+        let _ = self.finish_search();
+    }
+}
+
+impl Reporter {
+    fn write_string(&self, text: &str) -> io::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        writeln!(out, "info string {}", text)?;
+        out.flush()
+    }
+
+    fn write_info(&self, info: &SearchInfo, control: &SearchControl) -> io::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        match info {
+            SearchInfo::Iteration(result) => {
+                let nps = (result.nodes as f64 / result.time.as_secs_f64().max(0.001)) as u64;
+                let tb_hits = match result.tb_hits {
+                    0 => String::new(),
+                    hits => format!(" tbhits {}", hits),
+                };
+                // a limited strength searches more lines than it shows
+                for (i, line) in result.lines.iter().take(self.multipv).enumerate() {
+                    // only named with more than one line, which keeps the usual output short
+                    let multipv = match self.multipv {
+                        1 => String::new(),
+                        _ => format!(" multipv {}", i + 1),
+                    };
+                    writeln!(
+                        out,
+                        "info depth {} seldepth {}{} score {} nodes {} nps {} hashfull {}{} time {} pv {}",
+                        result.depth,
+                        result.seldepth,
+                        multipv,
+                        score_to_uci(line.score),
+                        result.nodes,
+                        nps,
+                        self.tt.hashfull(),
+                        tb_hits,
+                        result.time.as_millis(),
+                        pv_to_uci(&line.pv)
+                    )?;
+                }
+            }
+            SearchInfo::CurrMove { depth, mov, number } => {
+                if control.elapsed() >= CURRMOVE_DELAY {
+                    writeln!(out, "info depth {} currmove {} currmovenumber {}", depth, mov, number)?;
+                }
+            }
+        }
+        out.flush()
+    }
+
+    /// `line` starts with the move to play, it is empty if there is none
+    fn write_bestmove(&self, result: &SearchResult, line: &[Move]) -> io::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        let Some(best_move) = line.first() else {
+            writeln!(out, "info depth 0 score {}", score_to_uci(result.score))?;
+            writeln!(out, "bestmove 0000")?;
+            return out.flush();
+        };
+        write!(out, "bestmove {}", best_move)?;
+        if let (true, [_, reply, ..]) = (self.ponder, line) {
+            write!(out, " ponder {}", reply)?;
+        }
+        writeln!(out)?;
+        out.flush()
+    }
+}
+
+/// reports a proven mate like an iteration of the search
+fn mate_result(line: &[Move], nodes: u64, control: &SearchControl) -> SearchResult {
+    let score = MATE_SCORE - line.len() as i32;
+    SearchResult {
+        best_move: line.first().cloned(),
+        score,
+        depth: line.len() as u8,
+        seldepth: line.len() as u8,
+        nodes,
+        tb_hits: 0,
+        pv: line.to_vec(),
+        lines: vec![PvLine {
+            score,
+            pv: line.to_vec(),
+        }],
+        time: control.elapsed(),
+    }
+}
+
+fn pv_to_uci(pv: &[Move]) -> String {
+    let moves: Vec<String> = pv.iter().map(|m| m.to_string()).collect();
+    moves.join(" ")
+}
+
+fn parse_spin(name: &str, value: &str, min: i64, max: i64) -> Result<i64, String> {
+    match value.parse() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("invalid value '{}' for {}, expected {} to {}", value, name, min, max)),
+    }
+}
+
+fn parse_check(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("invalid value '{}' for {}, expected true or false", value, name)),
+    }
+}
+
+/// `cp <centipawns>` or `mate <moves>`, negative if the engine gets mated
+pub fn score_to_uci(score: i32) -> String {
+    if is_mate_score(score) {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        return format!("mate {}", moves * score.signum());
+    }
+    format!("cp {}", score)
+}
+
+/// `name <name> [value <value>]`, both may contain spaces
+fn parse_setoption(args: &[&str]) -> Result<(String, String), String> {
+    if args.first() != Some(&"name") {
+        return Err("setoption needs a name".to_string());
+    }
+    let value_idx = args.iter().position(|&t| t == "value").unwrap_or(args.len());
+    let name = args[1..value_idx].join(" ");
+    let value = args.get(value_idx + 1..).unwrap_or_default().join(" ");
+    Ok((name, value))
+}
+
+/// `startpos | fen <fen>` followed by optional `moves <m1> <m2> ...`
+fn parse_position(args: &[&str]) -> Result<Board, String> {
+    let moves_idx = args.iter().position(|&t| t == "moves").unwrap_or(args.len());
+    let mut board = match args.first() {
+        Some(&"startpos") => Board::new(START_FEN),
+        Some(&"fen") => Board::from_fen(&args[1..moves_idx].join(" "))?,
+        _ => return Err("position needs startpos or fen".to_string()),
+    };
+    // there is no chess960, castling rights need the king on the e file
+    board.validate().map_err(|e| e.to_string())?;
+    for s in args.iter().skip(moves_idx + 1) {
+        // moves after the end of the game are ignored
+        if !matches!(board.game_state, GameState::Playing) {
+            break;
+        }
+        let m = parse_uci(&board, s)?;
+        board.make_move(&m);
+    }
+    Ok(board)
+}
+
+/// everything that can follow `go`
+const GO_PARAMETERS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
+
+/// parses the parameters of `go`. A GUI waits for `bestmove` whatever it
+/// sent, so bad values and unsupported parameters only end up in `warnings`
+/// and the search starts with the rest.
+fn parse_go(args: &[&str], us: Color, warnings: &mut Vec<String>) -> Go {
+    let mut go = Go {
+        limits: SearchLimits::default(),
+        ponder: false,
+        infinite: false,
+        mate: None,
+    };
+    let limits = &mut go.limits;
+    let (mut time_left, mut increment, mut moves_to_go) = (None, Duration::ZERO, None);
+
+    let mut iter = args.iter().peekable();
+    while let Some(&name) = iter.next() {
+        match name {
+            "infinite" => {
+                go.infinite = true;
+                continue;
+            }
+            "ponder" => {
+                go.ponder = true;
+                continue;
+            }
+            // the moves or values that follow are skipped as well
+            _ if name == "searchmoves" || !GO_PARAMETERS.contains(&name) => {
+                while iter.next_if(|t| !GO_PARAMETERS.contains(t)).is_some() {}
+                warnings.push(match name {
+                    "searchmoves" => "go searchmoves is not supported, all moves are searched".to_string(),
+                    _ => format!("unknown go parameter '{}'", name),
+                });
+                continue;
+            }
+            _ => (),
+        }
+        let Some(value) = iter.next_if(|t| !GO_PARAMETERS.contains(t)) else {
+            warnings.push(format!("go {} needs a value", name));
+            continue;
+        };
+        // some GUIs send a negative time left once the flag fell
+        let Ok(number) = value.parse::<i64>() else {
+            warnings.push(format!("invalid value '{}' for {}", value, name));
+            continue;
+        };
+        let number = number.max(0) as u64;
+        let ms = Duration::from_millis(number);
+        match (name, us) {
+            ("depth", _) => limits.depth = Some(number.min(u8::MAX as u64) as u8),
+            ("nodes", _) => limits.nodes = Some(number),
+            ("movetime", _) => limits.movetime = Some(ms),
+            ("mate", _) => {
+                let moves = number.clamp(1, MAX_PLY as u64 / 2);
+                if moves != number {
+                    warnings.push(format!("go mate {} is out of range, searching for a mate in {}", number, moves));
+                }
+                go.mate = Some(moves as u8);
+            }
+            ("movestogo", _) => moves_to_go = Some(number as u32),
+            ("wtime", Color::White) | ("btime", Color::Black) => time_left = Some(ms),
+            ("winc", Color::White) | ("binc", Color::Black) => increment = ms,
+            // the clock of the opponent
+            _ => (),
+        }
+    }
+
+    if let (None, Some(time_left)) = (limits.movetime, time_left) {
+        limits.movetime = Some(time_for_move(time_left, increment, moves_to_go));
+    }
+    go
+}
@@ -0,0 +1,178 @@
+use smogfish::board::bitboard::{BitBoard, Direction};
+use smogfish::board::helper::Position;
+use smogfish::board::square::{File, Rank, Square};
+
+#[test]
+fn square_file_rank_round_trip() {
+    for (i, sq) in Square::ALL.into_iter().enumerate() {
+        assert_eq!(sq.index() as usize, i);
+        assert_eq!(Square::from_index(i as u8), Some(sq));
+        assert_eq!(Square::new(sq.file(), sq.rank()), sq);
+        assert_eq!(sq.to_string().parse::<Square>(), Ok(sq));
+        assert_eq!(Position::from(sq), Position(i as u8));
+        assert_eq!(Square::try_from(Position(i as u8)), Ok(sq));
+        assert_eq!(sq.to_string(), Position(i as u8).to_string());
+    }
+    assert_eq!(Square::from_index(64), None);
+    assert!(Square::try_from(Position(64)).is_err());
+    assert_eq!(Square::E4.to_string(), "e4");
+    assert_eq!(Square::new(File::H, Rank::Eighth), Square::H8);
+    for s in ["", "e", "e9", "i1", "e44", "E4"] {
+        assert!(s.parse::<Square>().is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn square_checked_offsets() {
+    for sq in Square::ALL {
+        for df in -8..=8i8 {
+            for dr in -8..=8i8 {
+                let (f, r) = (sq.file() as i8 + df, sq.rank() as i8 + dr);
+                let expected = if (0..8).contains(&f) && (0..8).contains(&r) {
+                    Square::from_index((r * 8 + f) as u8)
+                } else {
+                    None
+                };
+                assert_eq!(sq.offset(df, dr), expected);
+            }
+        }
+    }
+    assert_eq!(Square::A1.left(), None);
+    assert_eq!(Square::A1.down(), None);
+    assert_eq!(Square::H8.right(), None);
+    assert_eq!(Square::H8.up(), None);
+    assert_eq!(Square::E4.up(), Some(Square::E5));
+    assert_eq!(File::A.offset(-1), None);
+    assert_eq!(File::G.offset(1), Some(File::H));
+    assert_eq!(Rank::Eighth.offset(1), None);
+    assert_eq!(Rank::First.offset(7), Some(Rank::Eighth));
+}
+
+#[test]
+fn bitboard_constants() {
+    for i in 0..8u8 {
+        let (file, rank) = (File::from_index(i).unwrap(), Rank::from_index(i).unwrap());
+        assert_eq!(BitBoard::FILES[i as usize], file.bitboard());
+        assert_eq!(BitBoard::RANKS[i as usize], rank.bitboard());
+        for sq in Square::ALL {
+            assert_eq!(file.bitboard().has(sq.into()), sq.file() == file);
+            assert_eq!(rank.bitboard().has(sq.into()), sq.rank() == rank);
+        }
+    }
+    for sq in Square::ALL {
+        let light = (sq.file() as u8 + sq.rank() as u8) % 2 == 1;
+        assert_eq!(BitBoard::LIGHT_SQUARES.has(sq.into()), light);
+        assert_eq!(BitBoard::DARK_SQUARES.has(sq.into()), !light);
+    }
+    assert_eq!(BitBoard::LIGHT_SQUARES | BitBoard::DARK_SQUARES, BitBoard::FULL);
+    assert!(BitBoard::EMPTY.is_empty());
+}
+
+#[test]
+fn bitboard_bit_operations() {
+    let mut rng = 0x9e3779b97f4a7c15u64;
+    for _ in 0..1000 {
+        rng ^= rng << 13;
+        rng ^= rng >> 7;
+        rng ^= rng << 17;
+        let bb = BitBoard(rng & (rng >> 3));
+
+        let squares: Vec<Position> = bb.into_iter().collect();
+        assert_eq!(bb.count_set_bits() as usize, squares.len());
+        assert_eq!(bb.count_set_bits() as usize, squares.len());
+        assert_eq!(bb.more_than_one(), squares.len() > 1);
+        assert_eq!(bb.lsb(), squares.iter().map(|p| p.0).min().map(Position));
+        assert_eq!(bb.msb(), squares.iter().map(|p| p.0).max().map(Position));
+
+        let mut forward = bb;
+        let mut popped = vec![];
+        while let Some(p) = forward.pop_lsb() {
+            popped.push(p.0);
+        }
+        let mut backward = bb;
+        while let Some(p) = backward.pop_msb() {
+            assert_eq!(popped.pop(), Some(p.0));
+        }
+        assert!(popped.is_empty() && forward.is_empty() && backward.is_empty());
+
+        let as_squares: Vec<u8> = bb.squares().map(|s| s.index()).collect();
+        let mut sorted: Vec<u8> = squares.iter().map(|p| p.0).collect();
+        sorted.sort();
+        assert_eq!(as_squares, sorted);
+        assert_eq!(bb ^ bb, BitBoard::EMPTY);
+    }
+}
+
+#[test]
+fn bitboard_shifts_do_not_wrap() {
+    for dir in Direction::ALL {
+        let (df, dr) = dir.offset();
+        for sq in Square::ALL {
+            let expected = match sq.offset(df, dr) {
+                Some(target) => target.bitboard(),
+                None => BitBoard::EMPTY,
+            };
+            assert_eq!(sq.bitboard().shift(dir), expected, "{:?} from {}", dir, sq);
+        }
+    }
+}
+
+#[test]
+fn bitboard_between_and_line() {
+    for a in Square::ALL {
+        for b in Square::ALL {
+            let (df, dr) = (b.file() as i8 - a.file() as i8, b.rank() as i8 - a.rank() as i8);
+            let aligned = a != b && (df == 0 || dr == 0 || df.abs() == dr.abs());
+            assert_eq!(BitBoard::between(a, b), BitBoard::between(b, a));
+            assert_eq!(BitBoard::line(a, b), BitBoard::line(b, a));
+
+            if !aligned {
+                assert!(BitBoard::between(a, b).is_empty());
+                assert!(BitBoard::line(a, b).is_empty());
+                continue;
+            }
+
+            // walk from a to b and on to the edges
+            let step = (df.signum(), dr.signum());
+            let mut between = BitBoard::EMPTY;
+            let mut sq = a.offset(step.0, step.1).unwrap();
+            while sq != b {
+                between |= sq.bitboard();
+                sq = sq.offset(step.0, step.1).unwrap();
+            }
+            assert_eq!(BitBoard::between(a, b), between);
+
+            let mut line = a.bitboard();
+            for sign in [1, -1] {
+                let mut sq = a;
+                while let Some(next) = sq.offset(step.0 * sign, step.1 * sign) {
+                    line |= next.bitboard();
+                    sq = next;
+                }
+            }
+            assert_eq!(BitBoard::line(a, b), line);
+
+            if df == dr {
+                assert_eq!(BitBoard::line(a, b), BitBoard::diagonal(a));
+            } else if df == -dr {
+                assert_eq!(BitBoard::line(a, b), BitBoard::anti_diagonal(a));
+            }
+        }
+    }
+}
+
+#[test]
+fn bitboard_display() {
+    let bb = Square::A1.bitboard() | Square::H8.bitboard() | Square::E4.bitboard();
+    assert_eq!(
+        bb.to_string(),
+        "0 0 0 0 0 0 0 1 \n\
+         0 0 0 0 0 0 0 0 \n\
+         0 0 0 0 0 0 0 0 \n\
+         0 0 0 0 0 0 0 0 \n\
+         0 0 0 0 1 0 0 0 \n\
+         0 0 0 0 0 0 0 0 \n\
+         0 0 0 0 0 0 0 0 \n\
+         1 0 0 0 0 0 0 0 \n"
+    );
+}
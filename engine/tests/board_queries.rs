@@ -0,0 +1,44 @@
+use smogfish::board::helper::{CastlingRights, Color, Piece, Position};
+use smogfish::board::Board;
+
+#[test]
+fn query_pieces_and_occupancy() {
+    let b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(b.piece_at(Position::new(0, 4)), Some((Piece::King, Color::White)));
+    assert_eq!(b.piece_at(Position::new(7, 3)), Some((Piece::Queen, Color::Black)));
+    assert_eq!(b.piece_at(Position::new(4, 4)), None);
+
+    assert_eq!(b.pieces(Color::White, Piece::Pawn).0, 0xff00);
+    assert_eq!(b.color_occupancy(Color::White).0, 0xffff);
+    assert_eq!(b.color_occupancy(Color::Black).0, 0xffff << 48);
+    assert_eq!(b.occupied().0, 0xffff_0000_0000_ffff);
+}
+
+#[test]
+fn query_state() {
+    let b = Board::new("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 42");
+    assert_eq!(
+        b.castling_rights(),
+        CastlingRights {
+            white_short: true,
+            white_long: false,
+            black_short: false,
+            black_long: true,
+        }
+    );
+    assert!(b.castling_rights().can_castle(Color::Black, true));
+    assert_eq!(b.castling_rights().to_string(), "Kq");
+    assert_eq!(b.en_passant_square(), Some(Position::new(5, 3)));
+    assert_eq!(b.halfmove_clock(), 3);
+    assert_eq!(b.fullmove_number(), 42);
+    assert!(!b.in_check());
+
+    let b = Board::new("4k3/8/8/8/8/3n4/8/R3K2r w - - 0 1");
+    assert_eq!(b.castling_rights().to_string(), "-");
+    assert_eq!(b.en_passant_square(), None);
+    assert!(b.in_check());
+    let checkers: Vec<Position> = b.checkers().into_iter().collect();
+    assert_eq!(checkers.len(), 2);
+    assert!(checkers.contains(&Position::new(2, 3)));
+    assert!(checkers.contains(&Position::new(0, 7)));
+}
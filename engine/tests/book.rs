@@ -0,0 +1,60 @@
+use smogfish::board::Board;
+use smogfish::book::{polyglot_key, Book, BookEntry};
+use smogfish::rng::Rng;
+
+/// 1. e4 and 1. e4 c5
+const TINY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.bin");
+
+#[test]
+fn keys() {
+    // the reference keys of the polyglot format
+    for (fen, key) in [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+        ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+    ] {
+        assert_eq!(polyglot_key(&Board::new(fen)), key, "{}", fen);
+    }
+}
+
+#[test]
+fn book_moves() {
+    let book = Book::load(TINY).unwrap();
+    assert_eq!(book.len(), 2);
+    let mut board = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let moves: Vec<(String, u16)> = book.moves(&board).iter().map(|(m, w)| (m.to_string(), *w)).collect();
+    assert_eq!(moves, [("e2e4".to_string(), 1)]);
+
+    let mut rng = Rng::new(1);
+    board.make_move(&book.pick(&board, &mut rng).unwrap());
+    assert_eq!(book.pick(&board, &mut rng).unwrap().to_string(), "c7c5");
+    board.make_move(&book.moves(&board)[0].0);
+    assert!(book.pick(&board, &mut rng).is_none());
+
+    assert_eq!(Book::from_bytes(&book.to_bytes()).unwrap().to_bytes(), std::fs::read(TINY).unwrap());
+    assert!(Book::from_bytes(&[0; 15]).is_err());
+    assert!(Book::load("does/not/exist.bin").is_err());
+}
+
+#[test]
+fn castling_and_promotions() {
+    let board = Board::new("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let entry = |raw_move| BookEntry {
+        key: polyglot_key(&board),
+        raw_move,
+        weight: 1,
+        learn: 0,
+    };
+    // e1h1, e1a1, b7a8 promoting to a knight and to a queen
+    for (raw_move, expected) in [(0x107, "e1g1"), (0x100, "e1c1"), (0x1c78, "b7a8n"), (0x4c78, "b7a8q")] {
+        assert_eq!(entry(raw_move).to_move(&board).unwrap().to_string(), expected);
+    }
+    // e1e8 is not legal
+    assert!(entry(0x13c).to_move(&board).is_none());
+}
@@ -0,0 +1,99 @@
+use smogfish::board::builder::BoardBuilder;
+use smogfish::board::helper::{CastlingRights, Color, GameState, Piece, Position};
+use smogfish::board::validate::BoardError;
+use smogfish::board::Board;
+use smogfish::perft::perft;
+
+#[test]
+fn builder_matches_fen() {
+    let fen_board = Board::new("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 42");
+
+    let mut builder = BoardBuilder::new();
+    builder
+        .set_piece(Position::new(0, 0), Piece::Rook, Color::White)
+        .set_piece(Position::new(0, 4), Piece::King, Color::White)
+        .set_piece(Position::new(0, 7), Piece::Rook, Color::White)
+        .set_piece(Position::new(4, 4), Piece::Pawn, Color::White)
+        .set_piece(Position::new(7, 0), Piece::Rook, Color::Black)
+        .set_piece(Position::new(7, 4), Piece::King, Color::Black)
+        .set_piece(Position::new(7, 7), Piece::Rook, Color::Black)
+        .set_piece(Position::new(4, 3), Piece::Pawn, Color::Black)
+        .castling_rights(CastlingRights {
+            white_short: true,
+            black_long: true,
+            ..Default::default()
+        })
+        .en_passant(Some(Position::new(5, 3)))
+        .halfmove_clock(3)
+        .fullmove_number(42);
+    let b = builder.build().unwrap();
+
+    assert_eq!(b.zobrist_hash(), fen_board.zobrist_hash());
+    assert_eq!(b.move_list, fen_board.move_list);
+    assert_eq!(perft(&b, 3), perft(&fen_board, 3));
+    assert_eq!(b.halfmove_clock(), 3);
+    assert_eq!(b.fullmove_number(), 42);
+}
+
+#[test]
+fn builder_edits_existing_board() {
+    let start = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let mut builder = BoardBuilder::from_board(&start);
+    assert_eq!(builder.build().unwrap().zobrist_hash(), start.zobrist_hash());
+
+    // queen odds
+    builder.remove_piece(Position::new(0, 3));
+    let b = builder.build().unwrap();
+    assert_eq!(b.piece_at(Position::new(0, 3)), None);
+    // the king can now step to d1
+    assert_eq!(b.move_list.len(), 21);
+
+    // a mate gets detected right away
+    let mut builder = BoardBuilder::new();
+    builder
+        .set_piece(Position::new(7, 6), Piece::King, Color::Black)
+        .set_piece(Position::new(6, 5), Piece::Pawn, Color::Black)
+        .set_piece(Position::new(6, 6), Piece::Pawn, Color::Black)
+        .set_piece(Position::new(6, 7), Piece::Pawn, Color::Black)
+        .set_piece(Position::new(7, 0), Piece::Rook, Color::White)
+        .set_piece(Position::new(0, 0), Piece::King, Color::White)
+        .side_to_move(Color::Black);
+    let b = builder.build().unwrap();
+    assert!(matches!(b.game_state, GameState::WhiteWins));
+}
+
+#[test]
+fn builder_rejects_illegal_positions() {
+    let mut builder = BoardBuilder::new();
+    assert_eq!(builder.build().err().unwrap(), BoardError::KingCount(Color::White, 0));
+
+    builder
+        .set_piece(Position::new(0, 4), Piece::King, Color::White)
+        .set_piece(Position::new(7, 4), Piece::King, Color::Black);
+    assert!(builder.build().is_ok());
+
+    builder.set_piece(Position::new(7, 0), Piece::Pawn, Color::White);
+    assert_eq!(builder.build().err().unwrap(), BoardError::PawnOnBackRank(Position::new(7, 0)));
+
+    builder.set_piece(Position::new(7, 0), Piece::Rook, Color::White);
+    assert_eq!(builder.build().err().unwrap(), BoardError::OpponentInCheck);
+    builder.side_to_move(Color::Black);
+    assert!(builder.build().unwrap().in_check());
+
+    builder.castling_rights(CastlingRights {
+        black_long: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        builder.build().err().unwrap(),
+        BoardError::CastlingRights {
+            color: Color::Black,
+            is_long: true
+        }
+    );
+
+    builder
+        .castling_rights(CastlingRights::default())
+        .en_passant(Some(Position::new(5, 2)));
+    assert_eq!(builder.build().err().unwrap(), BoardError::EnPassant(2));
+}
@@ -0,0 +1,62 @@
+use smogfish::board::Board;
+use smogfish::datagen::{play_game, DatagenConfig, Sample};
+use smogfish::game::GameResult;
+use smogfish::rng::Rng;
+
+fn config() -> DatagenConfig {
+    DatagenConfig {
+        depth: 2,
+        max_plies: 120,
+        seed: 42,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reproducible() {
+    let texts = |index| play_game(&config(), index).iter().map(|s| s.to_text()).collect::<Vec<_>>();
+    let first = texts(3);
+    assert!(!first.is_empty());
+    assert_eq!(first, texts(3));
+    assert_ne!(first, texts(4));
+
+    let mut a = Rng::new(1);
+    let mut b = Rng::new(1);
+    for _ in 0..100 {
+        let n = a.below(37);
+        assert!(n < 37);
+        assert_eq!(n, b.below(37));
+    }
+}
+
+#[test]
+fn only_quiet_positions() {
+    for sample in play_game(&config(), 0) {
+        assert!(!sample.board.in_check());
+        assert_ne!(sample.result, GameResult::Ongoing);
+    }
+}
+
+#[test]
+fn formats() {
+    for (fen, score, result) in [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 12, GameResult::Draw),
+        ("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3", -250, GameResult::BlackWins),
+        ("8/8/4k3/8/8/3K4/6P1/8 w - - 17 80", 999, GameResult::WhiteWins),
+    ] {
+        let sample = Sample {
+            board: Board::new(fen),
+            score,
+            result,
+        };
+        let text = sample.to_text();
+        let from_text = Sample::from_text(&text).unwrap();
+        assert_eq!(from_text.to_text(), text);
+
+        let from_bytes = Sample::from_bytes(&sample.to_bytes()).unwrap();
+        assert_eq!(from_bytes.board.to_fen(), fen);
+        assert_eq!(from_bytes.score, score);
+        assert_eq!(from_bytes.result, result);
+    }
+    assert!(Sample::from_text("8/8/8/8/8/8/8/8 w - - 0 1 | 1").is_err());
+}
@@ -0,0 +1,49 @@
+use smogfish::epd::Epd;
+use smogfish::notation::parse_san;
+use smogfish::search::{search, SearchLimits, MATE_SCORE};
+
+#[test]
+fn epd_parse_operations() {
+    let epd = Epd::parse(
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\"; c0 \"mate; in 3\"; acd 12; ce 32750;",
+    )
+    .unwrap();
+    assert_eq!(epd.id(), Some("WAC.001"));
+    assert_eq!(epd.operation("c0").unwrap(), ["mate; in 3"]);
+    assert_eq!(epd.operation("acd").unwrap(), ["12"]);
+    assert_eq!(epd.operation("ce").unwrap(), ["32750"]);
+    assert!(epd.operation("am").is_none());
+
+    let best = epd.best_moves().unwrap();
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[0].to_string(), "g3g6");
+    assert_eq!(epd.is_solved_by(&best[0]), Ok(Some(true)));
+    let other = parse_san(&epd.board, "Qh4").unwrap();
+    assert_eq!(epd.is_solved_by(&other), Ok(Some(false)));
+}
+
+#[test]
+fn epd_avoid_moves_and_counters() {
+    let epd = Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w - - am e3 e4; hmvc 7; fmvn 30").unwrap();
+    assert_eq!(epd.avoid_moves().unwrap().len(), 2);
+    assert_eq!(epd.operation("hmvc").unwrap(), ["7"]);
+    assert!(epd.best_moves().unwrap().is_empty());
+
+    assert!(Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w -").is_err());
+    assert!(Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w - - id \"unterminated;").is_err());
+}
+
+#[test]
+fn epd_search_solves_mate() {
+    // WAC.002-like back rank mate
+    let epd = Epd::parse("6k1/5ppp/8/8/8/8/8/K2R4 w - - bm Rd8#; id \"backrank\";").unwrap();
+    let result = search(
+        &epd.board,
+        &SearchLimits {
+            depth: Some(3),
+            ..Default::default()
+        },
+    );
+    assert_eq!(epd.is_solved_by(result.best_move.as_ref().unwrap()), Ok(Some(true)));
+    assert_eq!(result.score, MATE_SCORE - 1);
+}
@@ -0,0 +1,132 @@
+use smogfish::board::Board;
+use smogfish::eval::trace::EvalTrace;
+use smogfish::eval::{coefficients, evaluate, evaluate_with, phase, EvalParams, DEFAULT_PARAMS, S};
+use smogfish::notation::parse_san;
+
+/// the position with colors swapped and the board mirrored vertically
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split(' ').collect();
+    let swap = |c: char| match c.is_ascii_uppercase() {
+        true => c.to_ascii_lowercase(),
+        false => c.to_ascii_uppercase(),
+    };
+    let ranks: Vec<String> = parts[0].split('/').rev().map(|r| r.chars().map(swap).collect()).collect();
+    let side = if parts[1] == "w" { "b" } else { "w" };
+    format!("{} {} - - {} {}", ranks.join("/"), side, parts[4], parts[5])
+}
+
+#[test]
+fn pawn_structure() {
+    // white: a2 and a3 doubled and isolated, c2 backward, d3 connected,
+    // a3 passed. black: d4 and h7 isolated, h7 passed
+    let board = Board::new("4k3/7p/8/8/3p4/P2P4/P1P5/4K3 w - - 0 1");
+    let pawn_terms: Vec<(usize, i32)> = coefficients(&board)
+        .into_iter()
+        .filter(|(i, _)| (EvalParams::DOUBLED_PAWN..EvalParams::KNIGHT_MOBILITY).contains(i))
+        .collect();
+    assert_eq!(
+        pawn_terms,
+        [
+            (EvalParams::DOUBLED_PAWN, 1),
+            (EvalParams::BACKWARD_PAWN, 1),
+            (EvalParams::CONNECTED_PAWN + 2, 1),
+            (EvalParams::PASSED_PAWN + 1, -1),
+            (EvalParams::PASSED_PAWN + 2, 1),
+            // distances to a4 are 4 and 4, to h6 3 and 5
+            (EvalParams::PASSED_OWN_KING, 1),
+            (EvalParams::PASSED_ENEMY_KING, -1),
+        ]
+    );
+}
+
+#[test]
+fn pieces_and_king_safety() {
+    let board = Board::new("6k1/R7/8/8/8/8/5PPP/2B1B1K1 w - - 0 1");
+    let terms: Vec<(usize, i32)> = coefficients(&board)
+        .into_iter()
+        .filter(|(i, _)| *i >= EvalParams::KNIGHT_MOBILITY)
+        .collect();
+    assert_eq!(
+        terms,
+        [
+            (EvalParams::BISHOP_MOBILITY + 4, 1),
+            (EvalParams::BISHOP_MOBILITY + 7, 1),
+            (EvalParams::ROOK_MOBILITY + 14, 1),
+            // f7, g7 and h7
+            (EvalParams::KING_ATTACK + 3, 3),
+            (EvalParams::PAWN_SHIELD, 3),
+            (EvalParams::KING_SEMI_OPEN_FILE, -3),
+            (EvalParams::BISHOP_PAIR, 1),
+            (EvalParams::ROOK_OPEN_FILE, 1),
+            (EvalParams::ROOK_SEVENTH_RANK, 1),
+        ]
+    );
+}
+
+#[test]
+fn terms() {
+    let mut next = 0;
+    for (_, range) in EvalParams::TERMS {
+        assert_eq!(range.start, next);
+        next = range.end;
+    }
+    assert_eq!(next, EvalParams::LEN);
+
+    let board = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1");
+    let trace = EvalTrace::new(&board, &DEFAULT_PARAMS);
+    assert_eq!(trace.terms.len(), EvalParams::TERMS.len());
+    assert_eq!(trace.phase, phase(&board));
+    assert_eq!(trace.score, -evaluate(&board));
+    assert_eq!(trace.network, None);
+
+    // both sides have the bishop pair
+    let bishop_pair = trace.term("bishop pair").unwrap();
+    assert_eq!(bishop_pair.white, DEFAULT_PARAMS.bishop_pair);
+    assert_eq!(bishop_pair.black, DEFAULT_PARAMS.bishop_pair);
+    assert_eq!(bishop_pair.total(), S(0, 0));
+    assert_eq!(trace.term("material").unwrap().total(), S(0, 0));
+    assert!(trace.term("no such term").is_none());
+
+    let text = trace.to_string();
+    assert!(text.lines().any(|l| l.starts_with(" bishop pair ")));
+    assert!(text.contains(&format!("classical evaluation {:+.2} (white side)", trace.score as f64 / 100.0)));
+
+    let board = Board::new("4k3/7p/8/8/3p4/P2P4/P1P5/4K3 w - - 0 1");
+    let trace = EvalTrace::new(&board, &DEFAULT_PARAMS);
+    let passed = trace.term("passed pawns").unwrap();
+    assert_eq!(passed.white, DEFAULT_PARAMS.passed_pawn[2]);
+    assert_eq!(passed.black, DEFAULT_PARAMS.passed_pawn[1]);
+}
+
+#[test]
+fn symmetric() {
+    for fen in [
+        "4k3/7p/8/8/3p4/P2P4/P1P5/4K3 w - - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1",
+    ] {
+        let (board, mirrored) = (Board::new(fen), Board::new(&mirror(fen)));
+        assert_eq!(evaluate(&board), evaluate(&mirrored), "{}", fen);
+        // the second evaluation comes from the pawn hash table
+        assert_eq!(evaluate(&board), evaluate_with(&board, &DEFAULT_PARAMS), "{}", fen);
+    }
+}
+
+#[test]
+fn pawn_hash() {
+    let mut board = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let start = board.pawn_hash();
+    for (san, pawns_changed) in [("Nf3", false), ("d5", true), ("Nc3", false), ("d4", true), ("Nxd4", true)] {
+        let before = board.pawn_hash();
+        board.make_move(&parse_san(&board, san).unwrap());
+        assert_eq!(board.pawn_hash() != before, pawns_changed, "{}", san);
+        assert_eq!(board.pawn_hash(), Board::new(&board.to_fen()).pawn_hash(), "{}", san);
+    }
+    assert_ne!(board.pawn_hash(), start);
+
+    // only the pawns count
+    let a = Board::new("4k3/pp6/8/8/8/8/PP6/4K3 w - - 0 1");
+    let b = Board::new("r3k3/pp6/8/8/8/8/PP6/3QK3 b - - 0 1");
+    assert_eq!(a.pawn_hash(), b.pawn_hash());
+    assert_ne!(a.pawn_hash(), Board::new("4k3/pp6/8/8/8/8/P1P5/4K3 w - - 0 1").pawn_hash());
+}
@@ -0,0 +1,18 @@
+use smogfish::board::Board;
+
+#[test]
+fn fen_round_trip() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 42",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 17 60",
+    ] {
+        assert_eq!(Board::new(fen).to_fen(), fen);
+    }
+
+    assert!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K3R w - - 0 1").is_err());
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - E3 0 1").is_err());
+}
@@ -0,0 +1,105 @@
+use smogfish::game::{Game, GameResult, Termination};
+use smogfish::board::helper::Color;
+
+#[test]
+fn undo_redo() {
+    let mut game = Game::default();
+    for san in ["e4", "e5", "Nf3", "Nc6"] {
+        game.push_san(san).unwrap();
+    }
+    assert_eq!(game.ply(), 4);
+    assert_eq!(game.undo().unwrap().san, "Nc6");
+    assert_eq!(game.undo().unwrap().san, "Nf3");
+    assert_eq!(game.ply(), 2);
+    assert_eq!(game.redo().unwrap().san, "Nf3");
+    assert_eq!(game.board().to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+
+    // replaying the undone move keeps the rest of the line
+    game.undo();
+    game.push_san("Nf3").unwrap();
+    assert!(game.redo().is_some());
+
+    // a different move drops it
+    game.goto(2).unwrap();
+    game.push_san("Bc4").unwrap();
+    assert!(game.redo().is_none());
+    assert_eq!(game.moves().iter().map(|m| m.san.as_str()).collect::<Vec<_>>(), ["e4", "e5", "Bc4"]);
+
+    game.goto(0).unwrap();
+    assert_eq!(game.board().to_fen(), game.start_position().to_fen());
+    assert!(game.goto(4).is_err());
+    assert_eq!(game.iter().count(), 0);
+}
+
+#[test]
+fn result() {
+    let mut game = Game::default();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.push_san(san).unwrap();
+    }
+    assert_eq!(game.result(), GameResult::BlackWins);
+    assert_eq!(game.termination(), Some(Termination::Checkmate));
+    assert!(game.push_uci("a2a3").is_err());
+
+    game.undo();
+    assert_eq!(game.result(), GameResult::Ongoing);
+    game.resign(Color::Black);
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.termination(), Some(Termination::Resignation));
+    game.undo();
+    assert_eq!(game.result(), GameResult::Ongoing);
+}
+
+#[test]
+fn repetition() {
+    let mut game = Game::default();
+    for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+        game.push_san(san).unwrap();
+    }
+    assert_eq!(game.result(), GameResult::Draw);
+    assert_eq!(game.termination(), Some(Termination::ThreefoldRepetition));
+}
+
+#[test]
+fn pgn() {
+    let mut game = Game::default();
+    game.set_tag("White", "smogfish");
+    game.set_tag("Annotator", "a \"quoted\" name");
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.push_san(san).unwrap();
+    }
+    assert_eq!(game.tag("White"), Some("smogfish"));
+    assert_eq!(
+        game.to_pgn(),
+        "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"smogfish\"]\n[Black \"?\"]\n\
+         [Result \"0-1\"]\n[Annotator \"a \\\"quoted\\\" name\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n"
+    );
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 10").unwrap();
+    game.push_san("Kd7").unwrap();
+    game.push_san("e4").unwrap();
+    assert!(game.to_pgn().ends_with("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 10\"]\n\n10... Kd7 11. e4 *\n"));
+
+    // the king is not on its starting square, there is no chess960
+    assert!(Game::from_fen("r1k4r/8/8/8/8/8/8/R1K4R w KQkq - 0 1").is_err());
+}
+
+#[test]
+fn pgn_import() {
+    let pgn = "[Event \"Test\"]\n[White \"a \\\"quoted\\\" name\"]\n[Result \"1-0\"]\n\n\
+               1. e4 {best by test} e5 2. Nf3 $1 (2. f4 exf4) 2... Nc6 ; the main line\n\
+               3.Bb5 a6 1-0\n\n[Event \"Next\"]\n\n1. d4 *\n";
+    let game = Game::from_pgn(pgn).unwrap();
+    assert_eq!(game.tag("Event"), Some("Test"));
+    assert_eq!(game.tag("White"), Some("a \"quoted\" name"));
+    assert_eq!(game.moves().iter().map(|m| m.san.as_str()).collect::<Vec<_>>(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+    assert_eq!(game.result(), GameResult::WhiteWins);
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 10").unwrap();
+    game.push_san("Kd7").unwrap();
+    let again = Game::from_pgn(&game.to_pgn()).unwrap();
+    assert_eq!(again.board().to_fen(), game.board().to_fen());
+    assert_eq!(again.result(), GameResult::Ongoing);
+
+    assert_eq!(Game::from_pgn("1. e4 e4").err().unwrap(), "move 1: e4 is not a legal move");
+}
@@ -0,0 +1,102 @@
+use smogfish::board::helper::Color;
+use smogfish::board::Board;
+use smogfish::eval::evaluate;
+use smogfish::eval::nnue::{Accumulator, Network, MAX_HIDDEN};
+use smogfish::rng::Rng;
+use std::sync::Arc;
+
+/// a random network with 24 hidden neurons, the expected outputs were
+/// computed with an independent implementation
+const TINY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.nnue");
+
+fn tiny() -> Arc<Network> {
+    Arc::new(Network::load(TINY).unwrap())
+}
+
+#[test]
+fn bit_exact() {
+    let net = tiny();
+    for (fen, expected) in [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 808),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1", 808),
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 649),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1", 827),
+    ] {
+        let mut board = Board::new(fen);
+        board.set_network(Some(net.clone()));
+        let acc = board.accumulator().unwrap();
+        let (us, them) = (acc.values(board.current_player()), acc.values(!board.current_player()));
+        assert_eq!(net.output_scalar(us, them), expected, "{}", fen);
+        assert_eq!(evaluate(&board), expected, "{}", fen);
+
+        board.set_network(None);
+        assert!(board.accumulator().is_none());
+    }
+}
+
+#[test]
+fn incremental_updates() {
+    let net = tiny();
+    let mut rng = Rng::new(3);
+    for _ in 0..20 {
+        // kiwipete has castling, en passant and promotions close by
+        let mut board = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        board.set_network(Some(net.clone()));
+        while !board.move_list.is_empty() && board.halfmove_clock() < 100 {
+            let m = board.move_list[rng.below(board.move_list.len())].clone();
+            board.make_move(&m);
+
+            let fresh = Accumulator::new(net.clone(), &board);
+            let acc = board.accumulator().unwrap();
+            for color in [Color::White, Color::Black] {
+                assert_eq!(acc.values(color), fresh.values(color), "{}", board.to_fen());
+            }
+            let (us, them) = (acc.values(Color::White), acc.values(Color::Black));
+            assert_eq!(net.output(us, them), net.output_scalar(us, them));
+        }
+    }
+}
+
+#[test]
+fn weights_file() {
+    let bytes = std::fs::read(TINY).unwrap();
+    let net = Network::from_bytes(&bytes).unwrap();
+    assert_eq!(net.hidden_size(), 24);
+    assert_eq!(net.to_bytes(), bytes);
+
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Network::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+    assert!(Network::from_bytes(b"SNN2").is_err());
+    let too_big = [b"SNN1".as_slice(), &(MAX_HIDDEN as u32 + 1).to_le_bytes()].concat();
+    let err = Network::from_bytes(&too_big).unwrap_err();
+    assert_eq!(err, format!("unsupported hidden layer size {}", MAX_HIDDEN + 1));
+    assert!(Network::load("does/not/exist.nnue").is_err());
+}
+
+#[test]
+fn extreme_weights_wrap() {
+    // every sum overflows, 40 neurons also leave some for the scalar tail
+    let hidden = 40;
+    let mut bytes = b"SNN1".to_vec();
+    bytes.extend_from_slice(&(hidden as u32).to_le_bytes());
+    for i in 0..(768 + 1 + 2) * hidden {
+        let w: i16 = if i % 3 == 0 { i16::MIN } else { i16::MAX };
+        bytes.extend_from_slice(&w.to_le_bytes());
+    }
+    bytes.extend_from_slice(&i32::MAX.to_le_bytes());
+    let net = Arc::new(Network::from_bytes(&bytes).unwrap());
+
+    let mut board = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    board.set_network(Some(net.clone()));
+    for m in ["e2a6", "b4c3", "e1g1"] {
+        let m = board.move_list.iter().find(|x| x.to_string() == m).unwrap().clone();
+        board.make_move(&m);
+        let fresh = Accumulator::new(net.clone(), &board);
+        let acc = board.accumulator().unwrap();
+        for color in [Color::White, Color::Black] {
+            assert_eq!(acc.values(color), fresh.values(color));
+        }
+        let (us, them) = (acc.values(Color::White), acc.values(Color::Black));
+        assert_eq!(net.output(us, them), net.output_scalar(us, them));
+    }
+}
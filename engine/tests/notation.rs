@@ -0,0 +1,61 @@
+use smogfish::board::Board;
+use smogfish::notation::{parse_san, parse_uci, to_san};
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+#[test]
+fn san_round_trip() {
+    for fen in POSITIONS {
+        let b = Board::new(fen);
+        for m in &b.move_list {
+            let san = to_san(&b, m);
+            assert_eq!(&parse_san(&b, &san).unwrap(), m, "{} in {}", san, fen);
+            assert_eq!(&parse_uci(&b, &m.to_string()).unwrap(), m);
+        }
+    }
+}
+
+#[test]
+fn san_formatting() {
+    let b = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let san = |s: &str| to_san(&b, &parse_uci(&b, s).unwrap());
+    assert_eq!(san("e1g1"), "O-O");
+    assert_eq!(san("e1c1"), "O-O-O");
+    assert_eq!(san("d5e6"), "dxe6");
+    assert_eq!(san("e5f7"), "Nxf7");
+    assert_eq!(san("c3b1"), "Nb1");
+    assert_eq!(san("d2c1"), "Bc1");
+    assert_eq!(san("f3f6"), "Qxf6");
+
+    let b = Board::new("R6R/8/8/8/4k3/8/8/R6K w - - 0 1");
+    let san = |s: &str| to_san(&b, &parse_uci(&b, s).unwrap());
+    assert_eq!(san("a8d8"), "Rad8");
+    assert_eq!(san("a1a2"), "R1a2");
+    assert_eq!(san("h8b8"), "Rhb8");
+    assert_eq!(san("a8a6"), "R8a6");
+
+    let b = Board::new("7k/1P6/8/8/8/8/8/K7 w - - 0 1");
+    assert_eq!(to_san(&b, &parse_uci(&b, "b7b8q").unwrap()), "b8=Q+");
+
+    let b = Board::new("6k1/5ppp/8/8/8/8/8/K2R4 w - - 0 1");
+    assert_eq!(to_san(&b, &parse_uci(&b, "d1d8").unwrap()), "Rd8#");
+}
+
+#[test]
+fn san_errors() {
+    let b = Board::new("R6R/8/8/8/8/8/k7/K7 w - - 0 1");
+    assert!(parse_san(&b, "Rd8").unwrap_err().contains("ambiguous"));
+    assert_eq!(parse_san(&b, "Rad8").unwrap().to_string(), "a8d8");
+    assert_eq!(parse_san(&b, "Ra8d8").unwrap().to_string(), "a8d8");
+    assert!(parse_san(&b, "Nf3").unwrap_err().contains("not a legal move"));
+
+    let b = Board::new("7k/1P6/8/8/8/8/8/K7 w - - 0 1");
+    assert!(parse_san(&b, "b8").unwrap_err().contains("promotion"));
+    assert_eq!(parse_san(&b, "b8=N").unwrap().to_string(), "b7b8n");
+    assert!(parse_uci(&b, "b7b8").is_err());
+}
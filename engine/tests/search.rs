@@ -0,0 +1,222 @@
+use smogfish::board::helper::GameState;
+use smogfish::board::Board;
+use smogfish::notation::parse_uci;
+use smogfish::search::control::{SearchControl, SystemClock};
+use smogfish::search::mate::{find_mate, MateSearch};
+use smogfish::search::tt::{Bound, TranspositionTable, TtEntry};
+use smogfish::search::{search, search_with, SearchInfo, SearchLimits, SearchOptions, MATE_SCORE};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn control() -> SearchControl {
+    SearchControl::new(Arc::new(SystemClock::default()), false)
+}
+
+fn depth(d: u8) -> SearchLimits {
+    SearchLimits {
+        depth: Some(d),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn search_finds_mate_in_two() {
+    let b = Board::new("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4");
+    let result = search(&b, &depth(2));
+    assert_eq!(result.best_move.unwrap().to_string(), "h5f7");
+    assert_eq!(result.score, MATE_SCORE - 1);
+
+    let b = Board::new("6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1");
+    let result = search(&b, &depth(4));
+    assert_eq!(result.score, MATE_SCORE - 3);
+    assert_eq!(result.pv.len(), 3);
+}
+
+#[test]
+fn mate_search() {
+    let mate = |fen, moves| -> Option<Vec<String>> {
+        let line = find_mate(&Board::new(fen), moves)?;
+        Some(line.iter().map(|m| m.to_string()).collect())
+    };
+    let in_three = "r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1";
+    assert_eq!(mate(in_three, 2), None);
+    let line = mate(in_three, 4).unwrap();
+    assert_eq!(line, ["f6a6", "f7f6", "e5f6", "g8g7", "a6a8"]);
+    let mut b = Board::new(in_three);
+    for m in &line {
+        b.make_move(&parse_uci(&b, m).unwrap());
+    }
+    assert!(matches!(b.game_state, GameState::WhiteWins));
+
+    // the shortest mate, with the longest defence
+    assert_eq!(mate("6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1", 3).unwrap().len(), 3);
+    assert_eq!(mate("7k/5Q2/6K1/8/8/8/8/8 w - - 0 1", 1).unwrap(), ["f7f8"]);
+    assert_eq!(mate("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 3), None);
+    // already mated
+    assert_eq!(mate("R3k3/8/4K3/8/8/8/8/8 b - - 0 1", 3), None);
+
+    // with this history Ka2 repeats a position for the third time, that draw
+    // must not hide the mate from the same position without the history
+    let mut repeated = Board::new("8/8/8/8/8/8/k1K5/4Q3 w - - 0 1");
+    for m in ["c2d1", "a2a1", "d1c2", "a1a2", "c2d1", "a2a1"] {
+        repeated.make_move(&parse_uci(&repeated, m).unwrap());
+    }
+    let fresh = Board::new("8/8/8/8/8/8/8/k2KQ3 w - - 0 1");
+    assert_eq!(repeated.zobrist_hash(), fresh.zobrist_hash());
+    let running = control();
+    let mut search = MateSearch::new(&running);
+    assert_eq!(search.find(&repeated, 2), None);
+    let line: Vec<String> = search.find(&fresh, 2).unwrap().iter().map(|m| m.to_string()).collect();
+    assert_eq!(line[..2], ["d1c2", "a1a2"]);
+
+    let stopped = control();
+    stopped.stop();
+    let mut search = MateSearch::new(&stopped);
+    assert_eq!(search.find(&Board::new(in_three), 3), None);
+}
+
+#[test]
+fn search_wins_material() {
+    let b = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let result = search(&b, &depth(3));
+    assert_eq!(result.best_move.unwrap().to_string(), "d2d5");
+}
+
+#[test]
+fn search_respects_limits() {
+    let b = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let result = search(
+        &b,
+        &SearchLimits {
+            movetime: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+    assert!(result.best_move.is_some());
+    assert!(result.time < Duration::from_millis(500));
+
+    let result = search(
+        &b,
+        &SearchLimits {
+            nodes: Some(5_000),
+            ..Default::default()
+        },
+    );
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+
+    let mated = Board::new("R5k1/5ppp/8/8/8/8/8/K7 b - - 0 1");
+    let result = search(&mated, &depth(3));
+    assert!(result.best_move.is_none());
+    assert_eq!(result.score, -MATE_SCORE);
+}
+
+#[test]
+fn search_reports_progress() {
+    let b = Board::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let tt = TranspositionTable::new(1);
+    let options = SearchOptions {
+        threads: 2,
+        ..Default::default()
+    };
+    let (mut depths, mut root_moves) = (vec![], 0);
+    let result = search_with(&b, &depth(4), &options, &tt, &control(), |info| match info {
+        SearchInfo::Iteration(r) => depths.push((r.depth, r.seldepth >= r.depth)),
+        SearchInfo::CurrMove { number, .. } => root_moves = root_moves.max(number),
+    });
+    assert_eq!(depths, [(1, true), (2, true), (3, true), (4, true)]);
+    assert_eq!(root_moves, b.move_list.len());
+    assert!(result.best_move.is_some());
+    assert!(tt.hashfull() > 0);
+
+    // a stopped search still finishes the first iteration
+    // with this history Ka2 repeats a position for the third time, that draw
+    // must not hide the mate from the same position without the history
+    let mut repeated = Board::new("8/8/8/8/8/8/k1K5/4Q3 w - - 0 1");
+    for m in ["c2d1", "a2a1", "d1c2", "a1a2", "c2d1", "a2a1"] {
+        repeated.make_move(&parse_uci(&repeated, m).unwrap());
+    }
+    let fresh = Board::new("8/8/8/8/8/8/8/k2KQ3 w - - 0 1");
+    assert_eq!(repeated.zobrist_hash(), fresh.zobrist_hash());
+    let running = control();
+    let mut search = MateSearch::new(&running);
+    assert_eq!(search.find(&repeated, 2), None);
+    let line: Vec<String> = search.find(&fresh, 2).unwrap().iter().map(|m| m.to_string()).collect();
+    assert_eq!(line[..2], ["d1c2", "a1a2"]);
+
+    let stopped = control();
+    stopped.stop();
+    let result = search_with(&b, &depth(4), &SearchOptions::default(), &tt, &stopped, |_| {});
+    assert_eq!(result.depth, 1);
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn transposition_table() {
+    let b = Board::new("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let tt = TranspositionTable::new(1);
+    let entry = TtEntry {
+        best_move: Some(parse_uci(&b, "a1a8").unwrap()),
+        // mate in one seen from two plies below the root
+        score: MATE_SCORE - 3,
+        depth: 5,
+        bound: Bound::Exact,
+    };
+    tt.store(b.zobrist_hash(), 2, &entry);
+    assert_eq!(tt.probe(b.zobrist_hash(), 2), Some(entry.clone()));
+    // and from the root
+    assert_eq!(tt.probe(b.zobrist_hash(), 0).unwrap().score, MATE_SCORE - 1);
+    assert_eq!(tt.probe(b.zobrist_hash() ^ 1, 2), None);
+
+    // a shallow entry without a move keeps the move of the deeper one
+    tt.new_search();
+    let shallow = TtEntry {
+        best_move: None,
+        score: -50,
+        depth: 1,
+        bound: Bound::Upper,
+    };
+    tt.store(b.zobrist_hash(), 0, &shallow);
+    assert_eq!(tt.probe(b.zobrist_hash(), 0).unwrap().best_move, entry.best_move);
+
+    tt.clear();
+    assert_eq!(tt.probe(b.zobrist_hash(), 0), None);
+    assert_eq!(tt.hashfull(), 0);
+}
+
+#[test]
+fn multipv() {
+    let b = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let options = SearchOptions {
+        multipv: 3,
+        ..Default::default()
+    };
+    let tt = TranspositionTable::new(1);
+    let result = search_with(&b, &depth(3), &options, &tt, &control(), |_| {});
+    assert_eq!(result.lines.len(), 3);
+    assert_eq!((result.lines[0].score, &result.lines[0].pv), (result.score, &result.pv));
+    assert_eq!(result.best_move.unwrap().to_string(), "d2d5");
+
+    let mut first_moves: Vec<String> = result.lines.iter().map(|l| l.pv[0].to_string()).collect();
+    for (line, next) in result.lines.iter().zip(&result.lines[1..]) {
+        assert!(line.score >= next.score);
+    }
+    // every line has the score of searching its first move
+    for line in &result.lines {
+        let mut child = b.clone();
+        child.make_move(&line.pv[0]);
+        assert_eq!(search(&child, &depth(2)).score, -line.score, "{}", line.pv[0]);
+    }
+    first_moves.sort();
+    first_moves.dedup();
+    assert_eq!(first_moves.len(), 3);
+
+    // taking the queen is the only legal move
+    let b = Board::new("7k/8/8/8/8/8/1q6/K7 w - - 0 1");
+    let options = SearchOptions {
+        multipv: 5,
+        ..Default::default()
+    };
+    let result = search_with(&b, &depth(2), &options, &tt, &control(), |_| {});
+    assert_eq!(result.lines.len(), 1);
+}
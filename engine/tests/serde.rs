@@ -0,0 +1,99 @@
+#![cfg(feature = "serde")]
+
+use smogfish::board::helper::{Color, GameState, Piece, Position};
+use smogfish::board::{Board, Move};
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for m in moves {
+        let mov = board.move_list.iter().find(|x| x.to_string() == *m).unwrap().clone();
+        board.make_move(&mov);
+    }
+}
+
+#[test]
+fn serde_round_trip_small_types() {
+    let m = Move::new_with_flags(Position::new(6, 4), Position::new(7, 4), 5);
+    assert_eq!(round_trip(&m), m);
+    assert_eq!(round_trip(&Position(42)), Position(42));
+    for p in Piece::ALL_NONEMPTY {
+        assert_eq!(round_trip(&p), p);
+    }
+    assert_eq!(round_trip(&Color::Black), Color::Black);
+    assert!(matches!(round_trip(&GameState::WhiteWins), GameState::WhiteWins));
+}
+
+#[test]
+fn serde_board_as_fen() {
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    play(&mut b, &["e2e4", "c7c5", "g1f3"]);
+
+    let json = serde_json::to_value(&b).unwrap();
+    assert_eq!(json["fen"], "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    assert_eq!(json["history"].as_array().unwrap().len(), 2);
+
+    let loaded: Board = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.to_fen(), b.to_fen());
+    assert_eq!(loaded.zobrist_hash(), b.zobrist_hash());
+    assert_eq!(loaded.move_list, b.move_list);
+}
+
+#[test]
+fn serde_board_keeps_repetitions() {
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    play(&mut b, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"]);
+
+    let mut loaded = round_trip(&b);
+    play(&mut b, &["f6g8"]);
+    play(&mut loaded, &["f6g8"]);
+    assert!(matches!(b.game_state, GameState::Draw));
+    assert!(matches!(loaded.game_state, GameState::Draw));
+}
+
+#[test]
+fn serde_rejects_invalid_boards() {
+    let bad = r#"{"fen":"8/8/8/8/8/8/8/8 w - - 0 1","history":[0]}"#;
+    assert!(serde_json::from_str::<Board>(bad).is_err());
+    let bad = r#"{"fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"}"#;
+    assert!(serde_json::from_str::<Board>(bad).is_err());
+}
+
+#[test]
+fn serde_board_game_state_follows_the_position() {
+    // fool's mate, whatever the input claims about the game
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    play(&mut b, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let mut json = serde_json::to_value(&b).unwrap();
+    json["game_state"] = "Playing".into();
+    let loaded: Board = serde_json::from_value(json).unwrap();
+    assert!(matches!(loaded.game_state, GameState::BlackWins));
+
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    play(&mut b, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert!(matches!(round_trip(&b).game_state, GameState::Draw));
+    let b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert!(matches!(round_trip(&b).game_state, GameState::Playing));
+}
+
+#[test]
+fn serde_rejects_history_of_another_position() {
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    play(&mut b, &["g1f3", "g8f6"]);
+    let mut json = serde_json::to_value(&b).unwrap();
+    json["history"][2] = 1.into();
+    assert!(serde_json::from_value::<Board>(json.clone()).is_err());
+    json["history"].as_array_mut().unwrap().pop();
+    assert!(serde_json::from_value::<Board>(json).is_err());
+}
+
+#[test]
+fn serde_rejects_squares_and_flags_out_of_range() {
+    assert!(serde_json::from_str::<Position>("63").is_ok());
+    assert!(serde_json::from_str::<Position>("64").is_err());
+    assert!(serde_json::from_str::<Move>(r#"{"from":200,"to":4,"flag":0}"#).is_err());
+    assert!(serde_json::from_str::<Move>(r#"{"from":12,"to":28,"flag":9}"#).is_err());
+    assert!(serde_json::from_str::<Move>(r#"{"from":12,"to":28,"flag":2}"#).is_ok());
+}
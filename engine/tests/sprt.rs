@@ -0,0 +1,63 @@
+use smogfish::sprt::{MatchScore, Sprt, SprtStatus};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+#[test]
+fn elo_and_los() {
+    let score = MatchScore {
+        wins: 60,
+        losses: 40,
+        draws: 100,
+    };
+    assert!(close(score.score(), 0.55));
+    assert!(close(score.elo(), 34.860));
+    assert!(close(score.elo_margin(), 34.160));
+    assert!(close(score.los(), 0.97725));
+
+    let even = MatchScore {
+        wins: 10,
+        losses: 10,
+        draws: 5,
+    };
+    assert!(close(even.elo(), 0.0));
+    assert!(close(even.los(), 0.5));
+
+    let perfect = MatchScore {
+        wins: 3,
+        losses: 0,
+        draws: 0,
+    };
+    assert!(perfect.elo().is_infinite());
+    assert!(perfect.elo_margin().is_infinite());
+}
+
+#[test]
+fn sprt() {
+    let sprt = Sprt::new(0.0, 10.0);
+    let (lower, upper) = sprt.bounds();
+    assert!(close(lower, -2.944) && close(upper, 2.944));
+
+    let score = MatchScore {
+        wins: 60,
+        losses: 40,
+        draws: 100,
+    };
+    assert!(close(sprt.llr(&score), 1.00549));
+    assert_eq!(sprt.status(&score), SprtStatus::Continue);
+
+    let better = MatchScore {
+        wins: 600,
+        losses: 400,
+        draws: 1000,
+    };
+    assert_eq!(sprt.status(&better), SprtStatus::AcceptH1);
+    let worse = MatchScore {
+        wins: 400,
+        losses: 600,
+        draws: 1000,
+    };
+    assert_eq!(sprt.status(&worse), SprtStatus::AcceptH0);
+    assert_eq!(sprt.llr(&MatchScore::default()), 0.0);
+}
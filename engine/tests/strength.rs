@@ -0,0 +1,102 @@
+use smogfish::board::{Board, Move};
+use smogfish::rng::Rng;
+use smogfish::search::{PvLine, SearchLimits};
+use smogfish::strength::{play, Strength};
+
+fn line(board: &Board, m: &str, score: i32) -> PvLine {
+    let m = board.move_list.iter().find(|l| l.to_string() == m).unwrap().clone();
+    PvLine { score, pv: vec![m] }
+}
+
+fn first(line: Option<Vec<Move>>) -> String {
+    line.unwrap()[0].to_string()
+}
+
+#[test]
+fn levels() {
+    let (low, mid, high) = (Strength::from_elo(800), Strength::from_elo(1600), Strength::from_elo(2800));
+    assert!(low.nodes < mid.nodes && mid.nodes < high.nodes);
+    assert!(low.depth < mid.depth && mid.depth < high.depth);
+    assert!(low.temperature > mid.temperature && high.temperature == 0.0);
+    assert!(low.blunder_chance > mid.blunder_chance && high.blunder_chance == 0.0);
+    assert_eq!(high.multipv, 1);
+    assert_eq!(Strength::from_elo(0), low);
+    assert_eq!(Strength::from_elo(5000), high);
+
+    let limits = low.limit(&SearchLimits {
+        depth: Some(20),
+        nodes: Some(10),
+        ..Default::default()
+    });
+    assert_eq!((limits.depth, limits.nodes), (Some(low.depth), Some(10)));
+}
+
+#[test]
+fn softmax() {
+    let board = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let lines = [line(&board, "e2e4", 0), line(&board, "d2d4", -100)];
+    let strength = Strength {
+        temperature: 100.0,
+        blunder_chance: 0.0,
+        ..Strength::from_elo(800)
+    };
+    let mut rng = Rng::new(7);
+    let picks = (0..10_000)
+        .filter(|_| first(strength.choose(&board, &lines, &mut rng)) == "e2e4")
+        .count();
+    // 1 / (1 + e^-1) of the picks
+    assert!((7000..7600).contains(&picks), "{}", picks);
+
+    let best = Strength {
+        temperature: 0.0,
+        ..strength.clone()
+    };
+    assert!((0..100).all(|_| first(best.choose(&board, &lines, &mut rng)) == "e2e4"));
+    assert!(best.choose(&board, &[], &mut rng).is_none());
+
+    // the same seed makes the same choices
+    let choices = |seed| {
+        let mut rng = Rng::new(seed);
+        (0..20).map(|_| first(strength.choose(&board, &lines, &mut rng))).collect::<Vec<_>>()
+    };
+    assert_eq!(choices(3), choices(3));
+}
+
+#[test]
+fn blunders() {
+    let board = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let lines = [line(&board, "d2d5", 900)];
+    let strength = Strength {
+        blunder_chance: 1.0,
+        ..Strength::from_elo(800)
+    };
+    let mut rng = Rng::new(1);
+    for _ in 0..20 {
+        let blunder = strength.choose(&board, &lines, &mut rng).unwrap();
+        assert_eq!(blunder.len(), 1);
+        assert_ne!(blunder[0].to_string(), "d2d5");
+        assert!(board.move_list.contains(&blunder[0]));
+    }
+}
+
+#[test]
+fn playing() {
+    let board = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let limits = SearchLimits::default();
+    let (line, result) = play(&board, &limits, &Strength::from_elo(2800), &mut Rng::new(1)).unwrap();
+    assert_eq!(line[0].to_string(), "d2d5");
+    assert!(result.depth <= 11 && result.nodes <= Strength::from_elo(2800).nodes + 1000);
+
+    let weak = Strength::from_elo(800);
+    let (line, result) = play(&board, &limits, &weak, &mut Rng::new(5)).unwrap();
+    assert!(board.move_list.contains(&line[0]));
+    assert_eq!(result.depth, 1);
+    assert_eq!(result.lines.len(), weak.multipv);
+    for seed in 0..5 {
+        let moves = || play(&board, &limits, &weak, &mut Rng::new(seed)).unwrap().0;
+        assert_eq!(moves(), moves());
+    }
+
+    let mated = Board::new("R3k3/8/4K3/8/8/8/8/8 b - - 0 1");
+    assert!(play(&mated, &limits, &weak, &mut Rng::new(1)).is_none());
+}
@@ -0,0 +1,251 @@
+use smogfish::board::Board;
+use smogfish::search::control::{SearchControl, SystemClock};
+use smogfish::search::tt::TranspositionTable;
+use smogfish::search::{search, search_with, SearchLimits, SearchOptions, MATE_SCORE, TB_WIN_SCORE};
+use smogfish::syzygy::generate::generate;
+use smogfish::syzygy::{Tablebases, Wdl};
+use std::sync::Arc;
+
+/// KQvK, KRvK, KBvK, KNvK and KPvK, made with `tbgen`
+const TABLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy");
+
+fn tablebases() -> Tablebases {
+    Tablebases::load(TABLES).unwrap()
+}
+
+fn depth(d: u8) -> SearchLimits {
+    SearchLimits {
+        depth: Some(d),
+        ..Default::default()
+    }
+}
+
+fn search_with_tables(board: &Board, d: u8) -> smogfish::search::SearchResult {
+    let options = SearchOptions {
+        tablebases: Some(Arc::new(tablebases())),
+        ..Default::default()
+    };
+    let tt = TranspositionTable::new(1);
+    let control = SearchControl::new(Arc::new(SystemClock::default()), false);
+    search_with(board, &depth(d), &options, &tt, &control, |_| {})
+}
+
+/// a position with the pieces on the squares, 0 being a1
+fn fen(pieces: &[(char, u8)], side: char) -> String {
+    let mut ranks = vec![];
+    for rank in (0..8).rev() {
+        let mut fen = String::new();
+        let mut empty = 0;
+        for sq in rank * 8..rank * 8 + 8 {
+            match pieces.iter().find(|p| p.1 == sq) {
+                Some(&(piece, _)) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    fen.push(piece);
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        ranks.push(fen);
+    }
+    format!("{} {} - - 0 1", ranks.join("/"), side)
+}
+
+#[test]
+fn load() {
+    let tb = tablebases();
+    assert_eq!(tb.len(), 5);
+    assert_eq!(tb.max_pieces(), 3);
+    assert!(Tablebases::load("").unwrap().is_empty());
+    assert!(Tablebases::load("missing/dir").is_err());
+    let dir = std::env::temp_dir().join(format!("smogfish-syzygy-load-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("KQQQQQQvK.rtbw"), []).unwrap();
+    let err = Tablebases::load(dir.to_str().unwrap()).unwrap_err();
+    assert!(
+        err.ends_with("KQQQQQQvK.rtbw: not a table of up to 7 pieces"),
+        "{}",
+        err
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+
+    // too many pieces, or castling rights the tables do not know about
+    let b = Board::new("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1");
+    assert!(!tb.covers(&b));
+    assert!(tb.rank_root_moves(&b).is_none());
+    let b = Board::new("4k3/8/8/8/8/8/8/RR2K3 w - - 0 1");
+    assert_eq!(tb.probe_wdl(&b), None);
+}
+
+#[test]
+fn probe() {
+    let tb = tablebases();
+    for (fen, wdl, dtz) in [
+        // the king in front of its pawn on the sixth rank always wins
+        ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Wdl::Win, 3),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Wdl::Loss, -4),
+        // the same with the colors swapped
+        ("8/8/8/8/4p3/4k3/8/4K3 b - - 0 1", Wdl::Win, 3),
+        ("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1", Wdl::Loss, -4),
+        ("k7/8/K7/P7/8/8/8/8 w - - 0 1", Wdl::Draw, 0),
+        ("8/8/8/4k3/8/8/4P3/4K3 b - - 0 1", Wdl::Draw, 0),
+        ("2k5/4P3/8/8/8/8/8/K7 w - - 0 1", Wdl::Win, 1),
+        // the queen gets taken
+        ("8/8/8/8/8/2k5/2Q5/K7 b - - 0 1", Wdl::Draw, 0),
+        ("k7/8/8/8/8/8/8/6qK w - - 0 1", Wdl::Draw, 0),
+        ("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1", Wdl::Win, 1),
+        ("8/8/8/8/8/2k5/1R6/K7 b - - 0 1", Wdl::Loss, -32),
+        ("8/8/8/3k4/8/8/8/3BK3 w - - 0 1", Wdl::Draw, 0),
+        ("8/8/8/3k4/8/8/8/3NK3 b - - 0 1", Wdl::Draw, 0),
+        ("8/8/8/3k4/8/8/8/4K3 w - - 0 1", Wdl::Draw, 0),
+    ] {
+        let b = Board::new(fen);
+        assert_eq!(tb.probe_wdl(&b), Some(wdl), "{}", fen);
+        assert_eq!(tb.probe_dtz(&b), Some(dtz), "{}", fen);
+    }
+}
+
+/// the longest distance to zeroing of white to move with a piece against
+/// the bare king
+fn longest_win(tb: &Tablebases, piece: char) -> i32 {
+    let mut longest = 0;
+    // every position can be turned so that the white king is in the
+    // a1-d1-d4 triangle
+    for king in (0..64).filter(|sq| sq % 8 <= 3 && sq / 8 <= sq % 8) {
+        for other in (0..64).filter(|&sq| sq != king) {
+            for black in (0..64).filter(|&sq| sq != king && sq != other) {
+                let fen = |side| fen(&[('K', king), (piece, other), ('k', black)], side);
+                // the kings can not stand next to each other, and black can
+                // not be in check with white to move
+                let adjacent =
+                    (king / 8).abs_diff(black / 8) <= 1 && (king % 8).abs_diff(black % 8) <= 1;
+                if !adjacent && !Board::new(&fen('b')).in_check() {
+                    longest = longest.max(tb.probe_dtz(&Board::new(&fen('w'))).unwrap());
+                }
+            }
+        }
+    }
+    longest
+}
+
+#[test]
+fn longest_wins() {
+    // without pawns or captures the next zeroing move is the mate, which
+    // takes at most 10 moves with a queen and 16 with a rook
+    let tb = tablebases();
+    assert_eq!(longest_win(&tb, 'Q'), 19);
+    assert_eq!(longest_win(&tb, 'R'), 31);
+}
+
+#[test]
+fn distance_is_the_mate_the_search_finds() {
+    let tb = tablebases();
+    for fen in ["6Q1/8/7k/8/8/8/8/2K5 w - - 0 1", "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"] {
+        let b = Board::new(fen);
+        let dtz = tb.probe_dtz(&b).unwrap();
+        assert_eq!(search(&b, &depth(dtz as u8)).score, MATE_SCORE - dtz, "{}", fen);
+    }
+}
+
+#[test]
+fn root_moves() {
+    let tb = tablebases();
+    let ranking = tb
+        .rank_root_moves(&Board::new("2k5/4P3/8/8/8/8/8/K7 w - - 0 1"))
+        .unwrap();
+    assert!(ranking.by_dtz);
+    let best: Vec<String> = ranking.moves.iter().take(2).map(|m| m.mov.to_string()).collect();
+    assert_eq!(best, ["e7e8q", "e7e8r"]);
+    assert_eq!(ranking.moves[0].score, TB_WIN_SCORE);
+    assert!(ranking.moves[2].rank < ranking.moves[1].rank);
+    let bishop = ranking
+        .moves
+        .iter()
+        .find(|m| m.mov.to_string() == "e7e8b")
+        .unwrap();
+    assert_eq!((bishop.rank, bishop.score), (0, 0));
+
+    // taking the queen is the only move that does not lose
+    let ranking = tb
+        .rank_root_moves(&Board::new("8/8/8/8/8/2k5/2Q5/K7 b - - 0 1"))
+        .unwrap();
+    assert_eq!(ranking.moves[0].mov.to_string(), "c3c2");
+    assert_eq!((ranking.moves[0].rank, ranking.moves[0].score), (0, 0));
+    assert!(ranking.moves[1..].iter().all(|m| m.score == -TB_WIN_SCORE));
+}
+
+#[test]
+fn halfmove_clock() {
+    // the win takes 31 plies, so with 80 of the 100 gone it is a draw
+    let tb = tablebases();
+    let fresh = tb
+        .rank_root_moves(&Board::new("8/8/8/8/8/2k5/1R6/K7 w - - 0 1"))
+        .unwrap();
+    assert_eq!(fresh.moves[0].score, TB_WIN_SCORE);
+    let late = tb
+        .rank_root_moves(&Board::new("8/8/8/8/8/2k5/1R6/K7 w - - 80 60"))
+        .unwrap();
+    assert!(late.moves[0].rank < fresh.moves[0].rank);
+    assert!((1..100).contains(&late.moves[0].score), "{}", late.moves[0].score);
+    // but the tables themselves do not know about the clock
+    assert_eq!(
+        tb.probe_wdl(&Board::new("8/8/8/8/8/2k5/1R6/K7 w - - 80 60")),
+        Some(Wdl::Win)
+    );
+}
+
+#[test]
+fn search_at_the_root() {
+    let b = Board::new("8/8/8/8/8/2k5/1R6/K7 w - - 0 1");
+    let ranking = tablebases().rank_root_moves(&b).unwrap();
+    let result = search_with_tables(&b, 3);
+    let best = ranking
+        .moves
+        .iter()
+        .find(|m| Some(&m.mov) == result.best_move.as_ref())
+        .unwrap();
+    assert_eq!(best.rank, ranking.moves[0].rank);
+    assert_eq!(result.score, TB_WIN_SCORE);
+    assert!(result.tb_hits > 0);
+}
+
+#[test]
+fn search_below_the_root() {
+    // taking the rook leaves a won KQvK
+    let b = Board::new("8/8/8/3k4/8/8/3r4/3QK3 w - - 0 1");
+    let result = search_with_tables(&b, 3);
+    assert!(["d1d2", "e1d2"].contains(&result.best_move.unwrap().to_string().as_str()));
+    assert_eq!(result.score, TB_WIN_SCORE - 1);
+    assert!(result.tb_hits > 0);
+    let result = search(&b, &depth(3));
+    assert!(result.score < TB_WIN_SCORE - 64);
+    assert_eq!(result.tb_hits, 0);
+}
+
+#[test]
+fn generate_tables() {
+    let dir = std::env::temp_dir().join(format!("smogfish-syzygy-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+    generate(dir, "KQvK").unwrap();
+    for ext in ["rtbw", "rtbz"] {
+        let made = std::fs::read(format!("{}/KQvK.{}", dir, ext)).unwrap();
+        let fixture = std::fs::read(format!("{}/KQvK.{}", TABLES, ext)).unwrap();
+        assert!(made == fixture, "KQvK.{} differs", ext);
+    }
+    assert!(generate(dir, "KRvKR").unwrap_err().contains("symmetric"));
+    assert!(generate(dir, "KPPvKP")
+        .unwrap_err()
+        .contains("pawns on both sides"));
+    assert!(generate(dir, "KQvK2").is_err());
+    // the tables the promotions lead to are missing
+    assert!(generate(dir, "KPvK")
+        .unwrap_err()
+        .starts_with("no table for"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,64 @@
+use smogfish::board::helper::Color;
+use smogfish::board::Board;
+use smogfish::datagen::{play_game, DatagenConfig};
+use smogfish::eval::{coefficients, evaluate, evaluate_with, phase, EvalParams, DEFAULT_PARAMS, S};
+use smogfish::tune::{params_to_rust, Tuner};
+
+const FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 99 50",
+    "4k3/8/8/8/8/8/3q4/R3K2R b KQ - 0 1",
+];
+
+#[test]
+fn coefficients_match_evaluation() {
+    let values = DEFAULT_PARAMS.to_vec();
+    for fen in FENS {
+        let board = Board::new(fen);
+        let sum = coefficients(&board)
+            .into_iter()
+            .fold(S::default(), |acc, (i, c)| acc + values[i] * c);
+        let white = match board.current_player() {
+            Color::White => evaluate_with(&board, &DEFAULT_PARAMS),
+            Color::Black => -evaluate_with(&board, &DEFAULT_PARAMS),
+        };
+        assert_eq!(sum.taper(phase(&board)), white, "{}", fen);
+        assert_eq!(evaluate(&board), evaluate_with(&board, &DEFAULT_PARAMS));
+    }
+    assert_eq!(evaluate(&Board::new(FENS[0])), 0);
+}
+
+#[test]
+fn params_round_trip() {
+    let values = DEFAULT_PARAMS.to_vec();
+    assert_eq!(values.len(), EvalParams::LEN);
+    assert_eq!(EvalParams::from_slice(&values), DEFAULT_PARAMS);
+    assert_eq!(Tuner::new(&DEFAULT_PARAMS).params(), DEFAULT_PARAMS);
+    // the committed defaults are exactly what the tuner writes
+    assert_eq!(params_to_rust(&DEFAULT_PARAMS), include_str!("../src/eval/params.rs"));
+}
+
+#[test]
+fn tuning_lowers_the_error() {
+    let config = DatagenConfig {
+        depth: 1,
+        max_plies: 200,
+        seed: 7,
+        ..Default::default()
+    };
+    let mut tuner = Tuner::new(&DEFAULT_PARAMS);
+    for index in 0..8 {
+        for sample in play_game(&config, index) {
+            tuner.add(&sample);
+        }
+    }
+    assert!(!tuner.is_empty());
+
+    tuner.fit_k();
+    let before = tuner.error();
+    for _ in 0..20 {
+        tuner.step(1.0);
+    }
+    assert!(tuner.error() < before);
+}
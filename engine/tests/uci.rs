@@ -0,0 +1,490 @@
+use smogfish::board::Board;
+use smogfish::search::control::ManualClock;
+use smogfish::uci::Uci;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// collects what the engine writes, also from its search thread
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn engine() -> (Uci, Output) {
+    let out = Output::default();
+    (Uci::new(out.clone()), out)
+}
+
+/// feeds the lines to a fresh engine and returns everything it wrote once
+/// the last search is done
+fn transcript(input: &[&str]) -> String {
+    let (mut uci, out) = engine();
+    for line in input {
+        if !uci.handle(line).unwrap() {
+            break;
+        }
+    }
+    uci.wait().unwrap();
+    out.text()
+}
+
+#[test]
+fn handshake() {
+    let out = transcript(&["uci", "isready", "quit", "isready"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("id name smogfish"));
+    for option in [
+        "option name Hash type spin default 16 min 1 max 65536",
+        "option name Threads type spin default 1 min 1 max 256",
+        "option name MultiPV type spin default 1 min 1 max 256",
+        "option name Move Overhead type spin default 10 min 0 max 5000",
+        "option name Ponder type check default false",
+        "option name OwnBook type check default false",
+        "option name BookFile type string default <empty>",
+        "option name SyzygyPath type string default <empty>",
+        "option name EvalFile type string default <empty>",
+        "option name UCI_Chess960 type check default false",
+        "option name UCI_LimitStrength type check default false",
+        "option name UCI_Elo type spin default 2800 min 800 max 2800",
+    ] {
+        assert!(lines.contains(&option), "{}", option);
+    }
+    assert_eq!(lines[lines.len() - 2..], ["uciok", "readyok"]);
+}
+
+#[test]
+fn eval_file() {
+    let (mut uci, out) = engine();
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.nnue");
+    uci.handle(&format!("setoption name EvalFile value {}", path)).unwrap();
+    assert!(uci.board().accumulator().is_some());
+    uci.handle("position startpos moves e2e4").unwrap();
+    assert!(uci.board().accumulator().is_some());
+    uci.handle("setoption name EvalFile value <empty>").unwrap();
+    assert!(uci.board().accumulator().is_none());
+    assert_eq!(out.text(), "");
+
+    let out = transcript(&["setoption name EvalFile value missing.nnue", "setoption name Foo value 1"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("info string missing.nnue: "));
+    assert_eq!(lines[1], "info string unknown option 'Foo'");
+}
+
+#[test]
+fn options() {
+    let out = transcript(&[
+        "setoption name Hash value 1",
+        "setoption name Threads value 2",
+        "setoption name MultiPV value 1",
+        "setoption name Move Overhead value 0",
+        "setoption name Ponder value true",
+        "setoption name UCI_LimitStrength value true",
+        "setoption name UCI_Elo value 1200",
+        "setoption name SyzygyPath value <empty>",
+        "setoption name OwnBook value false",
+        "setoption name UCI_Chess960 value false",
+    ]);
+    assert_eq!(out, "");
+
+    let out = transcript(&[
+        "setoption name Hash value 0",
+        "setoption name Threads value many",
+        "setoption name OwnBook value yes",
+        "setoption name UCI_Elo value 3000",
+    ]);
+    assert_eq!(
+        out,
+        "info string invalid value '0' for Hash, expected 1 to 65536\n\
+         info string invalid value 'many' for Threads, expected 1 to 256\n\
+         info string invalid value 'yes' for OwnBook, expected true or false\n\
+         info string invalid value '3000' for UCI_Elo, expected 800 to 2800\n"
+    );
+
+    let out = transcript(&["setoption name SyzygyPath value missing/dir"]);
+    assert!(out.starts_with("info string missing/dir: "), "{}", out);
+}
+
+#[test]
+fn syzygy_path() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy");
+    let out = transcript(&[
+        &format!("setoption name SyzygyPath value {}", dir),
+        "position fen 8/8/8/8/8/2k5/2Q5/K7 b - - 0 1",
+        "go depth 2",
+    ]);
+    let lines: Vec<&str> = out.lines().collect();
+    // only taking the queen holds the draw
+    assert!(lines[0].contains(" score cp 0 "), "{}", lines[0]);
+    assert!(lines[0].contains(" tbhits "), "{}", lines[0]);
+    assert_eq!(lines.last(), Some(&"bestmove c3c2"));
+}
+
+#[test]
+fn info_lines() {
+    let out = transcript(&[
+        "setoption name Threads value 2",
+        "setoption name Ponder value true",
+        "position startpos",
+        "go depth 4",
+    ]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 5);
+    let mut pv = vec![];
+    for (i, line) in lines[..4].iter().enumerate() {
+        // info depth 1 seldepth 3 score cp 20 nodes 42 nps 1000 hashfull 0 time 0 pv e2e4
+        let tokens: Vec<&str> = line.split(' ').collect();
+        let names: Vec<&str> = [0, 1, 3, 5, 8, 10, 12, 14, 16].iter().map(|&i| tokens[i]).collect();
+        assert_eq!(names, ["info", "depth", "seldepth", "score", "nodes", "nps", "hashfull", "time", "pv"]);
+        assert!(["cp", "mate"].contains(&tokens[6]), "{}", line);
+        for i in [2, 4, 7, 9, 11, 13, 15] {
+            assert!(tokens[i].parse::<i64>().is_ok(), "{}", line);
+        }
+        assert_eq!(tokens[2], (i + 1).to_string());
+        pv = tokens[17..].to_vec();
+        assert!(!pv.is_empty());
+    }
+    assert_eq!(lines[4], format!("bestmove {} ponder {}", pv[0], pv[1]));
+}
+
+#[test]
+fn multipv() {
+    let out = transcript(&[
+        "setoption name MultiPV value 3",
+        "position fen 4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1",
+        "go depth 3",
+    ]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 10);
+    for (i, line) in lines[..9].iter().enumerate() {
+        let tokens: Vec<&str> = line.split(' ').collect();
+        assert_eq!(tokens[1..3], ["depth", &(i / 3 + 1).to_string()]);
+        assert_eq!(tokens[5..7], ["multipv", &(i % 3 + 1).to_string()]);
+        assert_eq!(tokens[7], "score");
+    }
+    assert!(lines[6].contains(" pv d2d5"));
+    assert_eq!(lines[9], "bestmove d2d5");
+
+    let out = transcript(&["setoption name MultiPV value 0"]);
+    assert_eq!(out, "info string invalid value '0' for MultiPV, expected 1 to 256\n");
+}
+
+#[test]
+fn limit_strength() {
+    // searches one ply over four lines but only shows the best
+    let out = transcript(&[
+        "setoption name UCI_LimitStrength value true",
+        "setoption name UCI_Elo value 800",
+        "position fen 4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1",
+        "go depth 10",
+    ]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", out);
+    assert!(lines[0].starts_with("info depth 1 ") && !lines[0].contains(" multipv "));
+    assert!(lines[1].starts_with("bestmove "));
+
+    let out = transcript(&[
+        "setoption name UCI_LimitStrength value true",
+        "setoption name UCI_Elo value 2800",
+        "position fen 4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1",
+        "go infinite",
+        "stop",
+    ]);
+    assert!(out.ends_with("bestmove d2d5\n"), "{}", out);
+
+    // the same seed plays the same move, also with helper threads set
+    let play = |seed| {
+        let (mut uci, out) = engine();
+        uci.set_seed(seed);
+        for line in [
+            "setoption name Threads value 4",
+            "setoption name UCI_LimitStrength value true",
+            "setoption name UCI_Elo value 1400",
+            "position fen r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+            "go wtime 60000 btime 60000",
+        ] {
+            uci.handle(line).unwrap();
+        }
+        uci.wait().unwrap();
+        // everything but the timing, helper threads would change the nodes
+        let text = out.text();
+        let mut tokens = text.split_whitespace();
+        let mut kept = vec![];
+        while let Some(t) = tokens.next() {
+            match t {
+                "nps" | "time" => _ = tokens.next(),
+                _ => kept.push(t),
+            }
+        }
+        kept.join(" ")
+    };
+    for seed in [1, 2, 3] {
+        let out = play(seed);
+        assert!(out.contains(" bestmove "), "{}", out);
+        assert_eq!(out, play(seed));
+    }
+}
+
+#[test]
+fn go_mate() {
+    let out = transcript(&["position fen r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1", "go mate 3"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("info depth 5 seldepth 5 score mate 3 nodes "), "{}", lines[0]);
+    assert!(lines[0].ends_with(" pv f6a6 f7f6 e5f6 g8g7 a6a8"), "{}", lines[0]);
+    assert_eq!(lines[1], "bestmove f6a6");
+
+    // without a mate it searches as deep as the mate would be
+    let out = transcript(&["position startpos", "go mate 1"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "info string no mate in 1 found");
+    assert!(lines[2].starts_with("info depth 2 "));
+    assert!(lines[3].starts_with("bestmove "));
+    let out = transcript(&["position startpos", "go mate 0"]);
+    assert!(out.starts_with("info string go mate 0 is out of range, searching for a mate in 1\n"), "{}", out);
+    assert!(out.contains("\nbestmove "), "{}", out);
+
+    // but not deeper than a limited strength allows, two plies at 1000 Elo
+    let out = transcript(&[
+        "setoption name UCI_LimitStrength value true",
+        "setoption name UCI_Elo value 1000",
+        "position fen k7/P7/K7/8/8/8/8/8 w - - 0 1",
+        "go mate 3",
+    ]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", out);
+    assert!(lines[2].starts_with("info depth 2 "), "{}", lines[2]);
+}
+
+#[test]
+fn currmove() {
+    // root moves are only reported after the first second
+    let out = transcript(&[
+        "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "go movetime 2500",
+    ]);
+    let currmoves: Vec<Vec<&str>> = out
+        .lines()
+        .filter(|l| l.contains(" currmove "))
+        .map(|l| l.split(' ').collect())
+        .collect();
+    assert!(!currmoves.is_empty());
+    for tokens in currmoves {
+        assert_eq!([tokens[0], tokens[1], tokens[3], tokens[5]], ["info", "depth", "currmove", "currmovenumber"]);
+        assert!((1..=48).contains(&tokens[6].parse::<usize>().unwrap()));
+    }
+}
+
+#[test]
+fn book() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tiny.bin");
+    let out = transcript(&[
+        &format!("setoption name BookFile value {}", path),
+        "go depth 1",
+        "setoption name OwnBook value true",
+        "go depth 1",
+        "position startpos moves e2e4",
+        "go depth 1",
+    ]);
+    let lines: Vec<&str> = out.lines().filter(|l| !l.starts_with("info depth")).collect();
+    assert_eq!(lines[1..], ["info string book move", "bestmove e2e4", "info string book move", "bestmove c7c5"]);
+
+    // chess960 is not supported, so castling rights need the king on the e file
+    let (mut uci, out) = engine();
+    uci.handle("setoption name UCI_Chess960 value true").unwrap();
+    uci.handle("position fen r1k4r/8/8/8/8/8/8/R1K4R w KQkq - 0 1 moves c1g1").unwrap();
+    assert_eq!(
+        out.text(),
+        "info string UCI_Chess960 is not supported\n\
+         info string White can castle short, but king or rook have moved\n"
+    );
+    assert_eq!(uci.board().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+}
+
+#[test]
+fn position_and_go() {
+    let (mut uci, out) = engine();
+    uci.handle("position startpos moves e2e4 e7e5 g1f3").unwrap();
+    assert_eq!(uci.board().to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    uci.handle("position fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1 moves e1g1").unwrap();
+    assert_eq!(uci.board().to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+    assert_eq!(out.text(), "");
+
+    // mate in one
+    let out = transcript(&["position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "go depth 3"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("info depth "));
+    assert!(lines[0].contains(" score mate 1 "));
+    assert_eq!(lines[1], "bestmove a1a8");
+
+    let out = transcript(&["go wtime 1000 btime 1000 winc 0 binc 0"]);
+    assert!(out.lines().last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn halfmove_clock_past_fifty_moves() {
+    let (mut uci, out) = engine();
+    uci.handle("position fen 4k3/8/8/8/8/8/8/4K2R w - - 100 80 moves h1h2").unwrap();
+    // the game is drawn already, so the move is not played
+    assert_eq!(uci.board().to_fen(), "4k3/8/8/8/8/8/8/4K2R w - - 100 80");
+    // the clock reaches 100 with the first move, the others are ignored
+    uci.handle("position fen 4k3/8/8/8/8/8/8/4K2R w - - 99 80 moves h1h2 e8d8 h2h3").unwrap();
+    assert_eq!(uci.board().to_fen(), "4k3/8/8/8/8/8/7R/4K3 b - - 100 80");
+    uci.handle("go depth 2").unwrap();
+    uci.wait().unwrap();
+    assert!(out.text().contains("bestmove "), "{}", out.text());
+
+    // the board itself keeps playing past the end of the history
+    let mut b = Board::new("4k3/8/8/8/8/8/8/4K2R w - - 100 80");
+    for m in ["h1h2", "e8d8", "h2h1", "d8e8", "h1h2"] {
+        let m = b.move_list.iter().find(|l| l.to_string() == m).unwrap().clone();
+        b.make_move(&m);
+    }
+    assert_eq!(b.to_fen(), "4k3/8/8/8/8/8/7R/4K3 b - - 105 82");
+}
+
+#[test]
+fn errors() {
+    let out = transcript(&["position startpos moves e2e5", "foo"]);
+    assert_eq!(
+        out,
+        "info string e2e5 is not a legal move\n\
+         info string unknown command 'foo'\n"
+    );
+
+    // a GUI waits for bestmove, so go always searches with what it can use
+    let out = transcript(&["go depth x nodes 2000"]);
+    assert!(out.starts_with("info string invalid value 'x' for depth\n"), "{}", out);
+    assert!(out.contains("\nbestmove "), "{}", out);
+    let out = transcript(&["go wtime -20 btime 1000 searchmoves e2e4 d2d4 depth 1 foo 3"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[..2],
+        [
+            "info string go searchmoves is not supported, all moves are searched",
+            "info string unknown go parameter 'foo'",
+        ]
+    );
+    assert!(lines.last().unwrap().starts_with("bestmove "), "{}", out);
+    let out = transcript(&["go nodes 2000 depth"]);
+    assert!(out.starts_with("info string go depth needs a value\n"), "{}", out);
+    assert!(out.contains("\nbestmove "), "{}", out);
+
+    // black is mated, there is nothing to play
+    let out = transcript(&["position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", "go depth 2"]);
+    assert!(out.ends_with("bestmove 0000\n"));
+}
+
+#[test]
+fn eval() {
+    let out = transcript(&["position fen 4k3/8/8/8/8/8/8/3QK3 b - - 0 1", "eval"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with(" term"));
+    assert!(lines.iter().any(|l| l.starts_with(" material ")));
+    assert!(lines.last().unwrap().starts_with("classical evaluation +"));
+}
+
+/// output lines starting with `prefix`
+fn lines_starting(out: &Output, prefix: &str) -> Vec<String> {
+    out.text().lines().filter(|l| l.starts_with(prefix)).map(|l| l.to_string()).collect()
+}
+
+/// gives a search thread time to run on
+fn pause() {
+    std::thread::sleep(Duration::from_millis(50));
+}
+
+#[test]
+fn ponderhit() {
+    let clock = Arc::new(ManualClock::default());
+    let out = Output::default();
+    let mut uci = Uci::with_clock(out.clone(), clock.clone());
+    // 1. e4 e5 was played, the engine expects 2. Nf3 and ponders on it
+    uci.handle("setoption name Ponder value true").unwrap();
+    uci.handle("position startpos moves e2e4 e7e5 g1f3").unwrap();
+    uci.handle("go ponder wtime 10000 btime 10000").unwrap();
+
+    // pondering ignores the clock, black has about a third of a second per move
+    clock.advance(Duration::from_secs(60));
+    pause();
+    assert!(uci.searching());
+    assert!(lines_starting(&out, "bestmove").is_empty());
+    uci.handle("isready").unwrap();
+    assert_eq!(lines_starting(&out, "readyok").len(), 1);
+
+    // the time counts from the ponderhit
+    uci.handle("ponderhit").unwrap();
+    pause();
+    assert!(lines_starting(&out, "bestmove").is_empty());
+    clock.advance(Duration::from_secs(1));
+    uci.wait().unwrap();
+
+    let bestmove = lines_starting(&out, "bestmove");
+    assert_eq!(bestmove.len(), 1);
+    let tokens: Vec<&str> = bestmove[0].split(' ').collect();
+    assert_eq!([tokens[0], tokens[2]], ["bestmove", "ponder"]);
+    assert!(!uci.searching());
+}
+
+#[test]
+fn ponder_miss() {
+    let clock = Arc::new(ManualClock::default());
+    let out = Output::default();
+    let mut uci = Uci::with_clock(out.clone(), clock.clone());
+    uci.handle("position startpos moves e2e4 e7e5 g1f3").unwrap();
+    uci.handle("go ponder wtime 10000 btime 10000").unwrap();
+    pause();
+
+    // the opponent played something else, the GUI stops the search and
+    // ignores its bestmove
+    uci.handle("stop").unwrap();
+    assert_eq!(lines_starting(&out, "bestmove").len(), 1);
+    uci.handle("position startpos moves e2e4 e7e5 d2d4").unwrap();
+    uci.handle("go depth 2").unwrap();
+    uci.wait().unwrap();
+    assert_eq!(lines_starting(&out, "bestmove").len(), 2);
+
+    // a new position without a stop throws the ponder search away
+    uci.handle("position startpos moves e2e4 e7e5 g1f3").unwrap();
+    uci.handle("go ponder wtime 10000 btime 10000").unwrap();
+    pause();
+    uci.handle("position startpos moves e2e4 e7e5 b1c3").unwrap();
+    assert!(!uci.searching());
+    assert_eq!(lines_starting(&out, "bestmove").len(), 2);
+    uci.handle("go depth 1").unwrap();
+    uci.wait().unwrap();
+    assert_eq!(lines_starting(&out, "bestmove").len(), 3);
+}
+
+#[test]
+fn infinite() {
+    let (mut uci, out) = engine();
+    // the mate is found at once, but the bestmove has to wait for stop
+    uci.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    uci.handle("go infinite").unwrap();
+    pause();
+    assert!(uci.searching());
+    assert!(lines_starting(&out, "bestmove").is_empty());
+    uci.handle("stop").unwrap();
+    assert_eq!(lines_starting(&out, "bestmove"), ["bestmove a1a8"]);
+
+    // quit ends the search as well
+    uci.handle("go infinite").unwrap();
+    assert!(!uci.handle("quit").unwrap());
+    assert!(!uci.searching());
+}
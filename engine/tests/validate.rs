@@ -0,0 +1,69 @@
+use smogfish::board::helper::{Color, Position};
+use smogfish::board::validate::BoardError;
+use smogfish::board::Board;
+
+fn validate_tree(b: &Board, depth: u8) {
+    assert_eq!(b.validate(), Ok(()));
+    if depth == 0 {
+        return;
+    }
+    for m in &b.move_list {
+        let mut b_ = b.clone();
+        b_.make_move(m);
+        validate_tree(&b_, depth - 1);
+    }
+}
+
+#[test]
+fn validate_move_tree() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        validate_tree(&Board::new(fen), 3);
+    }
+}
+
+#[test]
+fn validate_hash_is_incremental() {
+    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        let mov = b.move_list.iter().find(|x| x.to_string() == m).unwrap().clone();
+        b.make_move(&mov);
+    }
+    assert_eq!(
+        b.zobrist_hash(),
+        Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3").zobrist_hash()
+    );
+
+    let e3 = Board::new("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    let no_ep = Board::new("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_ne!(e3.zobrist_hash(), no_ep.zobrist_hash());
+}
+
+#[test]
+fn validate_detects_errors() {
+    let err = |fen: &str| Board::new(fen).validate().unwrap_err();
+
+    assert_eq!(err("4k3/8/8/8/8/8/8/4KK2 b - - 0 1"), BoardError::KingCount(Color::White, 2));
+    assert_eq!(err("P3k3/8/8/8/8/8/8/4K3 b - - 0 1"), BoardError::PawnOnBackRank(Position::new(7, 0)));
+    assert_eq!(err("R3k3/8/8/8/8/8/8/4K3 w - - 0 1"), BoardError::OpponentInCheck);
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+        BoardError::CastlingRights {
+            color: Color::White,
+            is_long: false
+        }
+    );
+    assert_eq!(
+        err("r3k3/8/8/8/8/8/8/4K3 w k - 0 1"),
+        BoardError::CastlingRights {
+            color: Color::Black,
+            is_long: false
+        }
+    );
+    assert_eq!(err("4k3/8/8/8/8/8/8/4K3 w - d6 0 1"), BoardError::EnPassant(3));
+    assert_eq!(Board::new("r3k3/8/8/3pP3/8/8/8/4K3 w q d6 0 1").validate(), Ok(()));
+}
@@ -0,0 +1,303 @@
+use crate::engine::{think, Players};
+use crate::render;
+use smogfish::board::helper::{Color, Position};
+use smogfish::board::square::Square;
+use smogfish::board::Move;
+use smogfish::game::{Game, GameResult, Termination};
+use smogfish::notation::to_san;
+use smogfish::rng::Rng;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// the keys the full screen interface reacts to, independent of the terminal library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Char(char),
+}
+
+pub const KEY_HELP: &str = "arrows move  enter select  u undo  r redo  f flip  g go  h hint  n new  q quit";
+
+/// thinking time of both sides
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    pub used: [Duration; 2],
+    /// time per side for the whole game, without one the clocks count up
+    pub limit: Option<Duration>,
+}
+
+impl Clock {
+    /// remaining time with a limit, used time without
+    pub fn shown(&self, color: Color) -> Duration {
+        let used = self.used[color as usize];
+        match self.limit {
+            Some(limit) => limit.saturating_sub(used),
+            None => used,
+        }
+    }
+
+    pub fn flagged(&self, color: Color) -> bool {
+        self.limit.is_some_and(|limit| self.used[color as usize] >= limit)
+    }
+}
+
+/// an engine search running on its own thread, so the interface stays responsive
+struct Search {
+    /// the rng of the engine's copy of the players, and its move
+    handle: JoinHandle<(Rng, Option<(Move, String)>)>,
+    /// only shows the move instead of playing it
+    hint: bool,
+}
+
+/// state of the full screen interface
+pub struct App {
+    pub game: Game,
+    pub players: Players,
+    pub cursor: Square,
+    /// square of the piece that is about to be moved
+    pub selected: Option<Square>,
+    /// promotion waiting for the piece to be chosen
+    pub promotion: Option<(Position, Position)>,
+    pub flipped: bool,
+    pub clock: Clock,
+    /// shown in the status line until the next key press
+    pub message: String,
+    pub quit: bool,
+    search: Option<Search>,
+}
+
+impl App {
+    pub fn new(game: Game, players: Players) -> Self {
+        App {
+            game,
+            players,
+            cursor: Square::E2,
+            selected: None,
+            promotion: None,
+            flipped: false,
+            clock: Clock::default(),
+            message: String::new(),
+            quit: false,
+            search: None,
+        }
+    }
+
+    /// legal moves of the selected piece
+    pub fn targets(&self) -> Vec<&Move> {
+        match self.selected {
+            Some(sq) => self.game.board().move_list.iter().filter(|m| m.from == sq.into()).collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) {
+        self.message.clear();
+        // the position may not change under the search
+        if self.thinking() && !matches!(key, Key::Up | Key::Down | Key::Left | Key::Right | Key::Char('f' | 'q')) {
+            self.message = "smogfish is thinking...".to_string();
+            return;
+        }
+        if self.promotion.is_some() {
+            self.choose_promotion(key);
+            return;
+        }
+
+        // up is always towards the top of the screen
+        let (up, right) = if self.flipped { (-1, -1) } else { (1, 1) };
+        match key {
+            Key::Up => self.move_cursor(0, up),
+            Key::Down => self.move_cursor(0, -up),
+            Key::Right => self.move_cursor(right, 0),
+            Key::Left => self.move_cursor(-right, 0),
+            Key::Enter | Key::Char(' ') => self.select(),
+            Key::Esc => self.selected = None,
+            Key::Char('u') => {
+                self.selected = None;
+                if let Err(e) = self.players.undo(&mut self.game) {
+                    self.message = e;
+                }
+            }
+            Key::Char('r') => {
+                self.selected = None;
+                if self.game.redo().is_none() {
+                    self.message = "there is no move to redo".to_string();
+                }
+            }
+            Key::Char('f') => self.flipped = !self.flipped,
+            Key::Char('n') => {
+                self.game = Game::default();
+                self.clock.used = Default::default();
+                self.selected = None;
+            }
+            Key::Char('g') => self.engine_move(),
+            Key::Char('h') => self.hint(),
+            Key::Char('q') => self.quit = true,
+            _ => (),
+        }
+    }
+
+    /// a mouse click on the screen, selects or drops on the clicked square
+    pub fn click(&mut self, x: u16, y: u16) {
+        if let Some(sq) = render::square_at(self.flipped, x, y) {
+            self.message.clear();
+            self.cursor = sq;
+            if self.thinking() {
+                self.message = "smogfish is thinking...".to_string();
+            } else if self.promotion.is_none() {
+                self.select();
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, files: i8, ranks: i8) {
+        // stay on the edge instead of leaving the board
+        self.cursor = self.cursor.offset(files, ranks).unwrap_or(self.cursor);
+    }
+
+    fn select(&mut self) {
+        if self.game.result() != GameResult::Ongoing {
+            self.message = "the game is over, press n for a new one".to_string();
+            return;
+        }
+        let board = self.game.board();
+        if let Some(from) = self.selected {
+            let moves: Vec<Move> = self.targets().into_iter().filter(|m| m.to == self.cursor.into()).cloned().collect();
+            match moves.len() {
+                0 => (),
+                1 => {
+                    self.play(&moves[0]);
+                    return;
+                }
+                // the same squares with different promotion pieces
+                _ => {
+                    self.promotion = Some((from.into(), self.cursor.into()));
+                    self.message = "promote to (q)ueen, (r)ook, (b)ishop or k(n)ight".to_string();
+                    return;
+                }
+            }
+        }
+
+        if board.piece_at(self.cursor).map(|p| p.1) == Some(board.current_player())
+            && self.selected != Some(self.cursor)
+        {
+            self.selected = Some(self.cursor);
+        } else {
+            self.selected = None;
+        }
+    }
+
+    fn hint(&mut self) {
+        self.start_search(true);
+    }
+
+    fn choose_promotion(&mut self, key: Key) {
+        let Some((from, to)) = self.promotion.take() else {
+            return;
+        };
+        let flag = match key {
+            Key::Char('q') => 5,
+            Key::Char('r') => 6,
+            Key::Char('b') => 7,
+            Key::Char('n') => 8,
+            _ => {
+                self.selected = None;
+                return;
+            }
+        };
+        self.play(&Move::new_with_flags(from, to, flag));
+    }
+
+    fn play(&mut self, m: &Move) {
+        self.selected = None;
+        if let Err(e) = self.game.push(m) {
+            self.message = e;
+        }
+    }
+
+    pub fn engine_to_move(&self) -> bool {
+        self.game.result() == GameResult::Ongoing && self.players.engine_plays(self.game.board().current_player())
+    }
+
+    /// lets the engine play a move for the side to move. The search runs in
+    /// the background, `poll` plays the move once it is done.
+    pub fn engine_move(&mut self) {
+        self.start_search(false);
+    }
+
+    pub fn thinking(&self) -> bool {
+        self.search.is_some()
+    }
+
+    fn start_search(&mut self, hint: bool) {
+        if self.thinking() || self.game.result() != GameResult::Ongoing {
+            return;
+        }
+        let board = self.game.board().clone();
+        let mut players = self.players.clone();
+        // leave enough time on the clock for the rest of the game
+        if let Some(remaining) = self.clock.limit.map(|_| self.clock.shown(board.current_player())) {
+            let budget = remaining / 30;
+            players.limits.movetime = Some(players.limits.movetime.map_or(budget, |t| t.min(budget)));
+        }
+        let handle = thread::spawn(move || {
+            let reply = match hint {
+                true => think(&board, &players.limits),
+                false => players.think(&board),
+            };
+            (players.rng, reply)
+        });
+        self.search = Some(Search { handle, hint });
+        self.message = "smogfish is thinking...".to_string();
+    }
+
+    /// plays the engine's move or shows the hint once the search is done
+    pub fn poll(&mut self) {
+        if self.search.as_ref().is_some_and(|s| s.handle.is_finished()) {
+            self.wait();
+        }
+    }
+
+    /// like `poll`, but waits for the search to finish
+    pub fn wait(&mut self) {
+        let Some(search) = self.search.take() else {
+            return;
+        };
+        let Ok((rng, reply)) = search.handle.join() else {
+            self.message = "the search failed".to_string();
+            return;
+        };
+        self.message.clear();
+        // the engine lost on time while it was thinking
+        let Some((m, info)) = reply.filter(|_| self.game.result() == GameResult::Ongoing) else {
+            return;
+        };
+        if search.hint {
+            self.message = format!("hint: {}", to_san(self.game.board(), &m));
+        } else {
+            self.players.rng = rng;
+            self.play(&m);
+            self.message = info;
+        }
+    }
+
+    /// adds the time that passed to the clock of the side to move
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.game.result() != GameResult::Ongoing {
+            return;
+        }
+        let color = self.game.board().current_player();
+        self.clock.used[color as usize] += elapsed;
+        if self.clock.flagged(color) {
+            let result = match color {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            };
+            self.game.set_result(result, Termination::TimeForfeit);
+        }
+    }
+}
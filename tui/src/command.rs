@@ -0,0 +1,184 @@
+use smogfish::board::square::Square;
+use smogfish::board::{Board, Move};
+use smogfish::notation::{parse_san, parse_uci};
+use smogfish::search::MAX_PLY;
+use smogfish::strength::Strength;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// most lines `analyze` shows
+pub const MAX_LINES: usize = 20;
+
+pub const HELP: &str = "\
+moves are entered in SAN or UCI notation, e.g. Nf3, exd5, O-O, e2e4 or e7e8=N
+
+commands:
+  undo              take back the last move
+  redo              play the last undone move again
+  new               start a new game
+  fen [fen]         show the current FEN or set up a position
+  flip              turn the board around
+  set <option> <on|off>
+                    change how the board is shown, the options are unicode,
+                    color, info and autoflip
+  moves [square]    list the legal moves, optionally only from one square
+  hint              let the engine suggest a move
+  eval              show the evaluation of the position term by term
+  analyze [n]       show the n best moves with their lines, 3 by default
+  go                let the engine play a move for the side to move
+  play <side>       choose the side you play: white, black, both or none
+  depth <n>         let the engine search n plies deep per move
+  movetime <ms>     let the engine think ms milliseconds per move
+  elo <n|max>       let the engine play at about n Elo, 800 to 2800, or at
+                    full strength
+  seed <n>          make the moves at a limited strength repeatable
+  save <file.pgn>   save the game as PGN
+  load <file>       load a game from a PGN or FEN file
+  help              show this help
+  quit              leave";
+
+/// the sides played by a human, the engine plays the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sides {
+    White,
+    Black,
+    Both,
+    None,
+}
+
+impl FromStr for Sides {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "white" | "w" => Ok(Sides::White),
+            "black" | "b" => Ok(Sides::Black),
+            "both" => Ok(Sides::Both),
+            "none" => Ok(Sides::None),
+            _ => Err(format!("'{}' is not a side, use white, black, both or none", s.trim())),
+        }
+    }
+}
+
+/// display options that can be turned on and off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Unicode,
+    Color,
+    Info,
+    AutoFlip,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Move(String),
+    Undo,
+    Redo,
+    New,
+    Fen(Option<String>),
+    Flip,
+    Set(Setting, bool),
+    Moves(Option<Square>),
+    Hint,
+    Eval,
+    /// the number of lines
+    Analyze(usize),
+    Go,
+    Play(Sides),
+    Depth(u8),
+    MoveTime(Duration),
+    /// none for full strength
+    Elo(Option<u32>),
+    Seed(u64),
+    Save(String),
+    Load(String),
+    Help,
+    Quit,
+}
+
+/// splits a line of input into a command, anything that is not a command is
+/// taken as a move
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, arg) = match input.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, Some(arg.trim())),
+        None => (input, None),
+    };
+
+    let no_arg = |cmd: Command| match arg {
+        Some(_) => Err(format!("'{}' takes no arguments", name)),
+        None => Ok(cmd),
+    };
+    let needs_arg = |usage: &str| arg.map(|a| a.to_string()).ok_or(format!("usage: {}", usage));
+
+    match name {
+        "" => Err("enter a move or a command, 'help' lists them".to_string()),
+        "undo" => no_arg(Command::Undo),
+        "redo" => no_arg(Command::Redo),
+        "new" => no_arg(Command::New),
+        "fen" => Ok(Command::Fen(arg.map(|a| a.to_string()))),
+        "flip" => no_arg(Command::Flip),
+        "set" => {
+            let usage = "usage: set <unicode|color|info|autoflip> <on|off>";
+            let (setting, value) = arg.and_then(|a| a.split_once(char::is_whitespace)).ok_or(usage)?;
+            let setting = match setting {
+                "unicode" => Setting::Unicode,
+                "color" => Setting::Color,
+                "info" => Setting::Info,
+                "autoflip" => Setting::AutoFlip,
+                _ => return Err(format!("unknown option '{}'", setting)),
+            };
+            match value.trim() {
+                "on" => Ok(Command::Set(setting, true)),
+                "off" => Ok(Command::Set(setting, false)),
+                _ => Err(usage.to_string()),
+            }
+        }
+        "moves" => match arg {
+            Some(s) => s.parse::<Square>().map(|sq| Command::Moves(Some(sq))),
+            None => Ok(Command::Moves(None)),
+        },
+        "hint" => no_arg(Command::Hint),
+        "eval" => no_arg(Command::Eval),
+        "analyze" => match arg.map(|a| a.parse::<usize>()) {
+            None => Ok(Command::Analyze(3)),
+            Some(Ok(n)) if (1..=MAX_LINES).contains(&n) => Ok(Command::Analyze(n)),
+            Some(_) => Err(format!("the number of lines has to be between 1 and {}", MAX_LINES)),
+        },
+        "go" => no_arg(Command::Go),
+        "play" => needs_arg("play <white|black|both|none>")?.parse().map(Command::Play),
+        "depth" => match needs_arg("depth <n>")?.parse::<u8>() {
+            Ok(d) if (1..=MAX_PLY).contains(&d) => Ok(Command::Depth(d)),
+            _ => Err(format!("the depth has to be between 1 and {}", MAX_PLY)),
+        },
+        "movetime" => needs_arg("movetime <ms>")?
+            .parse::<u64>()
+            .map(|ms| Command::MoveTime(Duration::from_millis(ms)))
+            .map_err(|_| "the move time has to be a number of milliseconds".to_string()),
+        "elo" => match needs_arg("elo <n|max>")?.as_str() {
+            "max" => Ok(Command::Elo(None)),
+            n => match n.parse::<u32>() {
+                Ok(elo) if (Strength::MIN_ELO..=Strength::MAX_ELO).contains(&elo) => Ok(Command::Elo(Some(elo))),
+                _ => Err(format!(
+                    "the Elo has to be between {} and {} or max",
+                    Strength::MIN_ELO,
+                    Strength::MAX_ELO
+                )),
+            },
+        },
+        "seed" => needs_arg("seed <n>")?
+            .parse()
+            .map(Command::Seed)
+            .map_err(|_| "the seed has to be a number".to_string()),
+        "save" => needs_arg("save <file.pgn>").map(Command::Save),
+        "load" => needs_arg("load <file>").map(Command::Load),
+        "help" | "?" => no_arg(Command::Help),
+        "quit" | "exit" => no_arg(Command::Quit),
+        _ if arg.is_none() => Ok(Command::Move(name.to_string())),
+        _ => Err(format!("unknown command '{}', 'help' lists the commands", name)),
+    }
+}
+
+/// parses a move in UCI or SAN notation
+pub fn parse_move(board: &Board, s: &str) -> Result<Move, String> {
+    parse_uci(board, s).or_else(|_| parse_san(board, s))
+}
@@ -0,0 +1,175 @@
+use crate::command::Sides;
+use smogfish::board::helper::Color;
+use smogfish::board::{Board, Move};
+use smogfish::game::{Game, GameResult};
+use smogfish::notation::to_san;
+use smogfish::rng::Rng;
+use smogfish::search::control::{SearchControl, SystemClock};
+use smogfish::search::tt::TranspositionTable;
+use smogfish::search::{is_mate_score, search, search_with, SearchLimits, SearchOptions, MATE_SCORE};
+use smogfish::strength::{self, Strength};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// who plays which side, how long the engine thinks and how well it plays
+#[derive(Clone)]
+pub struct Players {
+    pub human: Sides,
+    pub limits: SearchLimits,
+    /// none plays at full strength
+    pub strength: Option<Strength>,
+    /// picks the moves at a limited strength
+    pub rng: Rng,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Players {
+            human: Sides::White,
+            limits: SearchLimits {
+                movetime: Some(Duration::from_secs(1)),
+                ..Default::default()
+            },
+            strength: None,
+            rng: Rng::new(
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+impl Players {
+    pub fn engine_plays(&self, color: Color) -> bool {
+        match self.human {
+            Sides::White => color == Color::Black,
+            Sides::Black => color == Color::White,
+            Sides::Both => false,
+            Sides::None => true,
+        }
+    }
+
+    /// takes back the last move, and the engine's reply before it so the
+    /// engine does not just play it again
+    pub fn undo(&self, game: &mut Game) -> Result<(), String> {
+        game.undo().ok_or("there is no move to take back")?;
+        if self.engine_plays(game.board().current_player()) && self.human != Sides::None {
+            game.undo();
+        }
+        Ok(())
+    }
+
+    /// searches `depth` plies per move, without a time limit
+    pub fn set_depth(&mut self, depth: u8) {
+        self.limits.depth = Some(depth);
+        self.limits.movetime = None;
+    }
+
+    /// thinks `time` per move, without a depth limit
+    pub fn set_movetime(&mut self, time: Duration) {
+        self.limits.movetime = Some(time);
+        self.limits.depth = None;
+    }
+
+    /// lets the engine play a move for the side to move, returns it with
+    /// the line describing it
+    pub fn play(&mut self, game: &mut Game) -> Option<(Move, String)> {
+        if game.result() != GameResult::Ongoing {
+            return None;
+        }
+        let (m, info) = self.think(game.board())?;
+        game.push(&m).ok()?;
+        Some((m, info))
+    }
+
+    /// like `play`, but only if the engine plays the side to move
+    pub fn reply(&mut self, game: &mut Game) -> Option<(Move, String)> {
+        if !self.engine_plays(game.board().current_player()) {
+            return None;
+        }
+        self.play(game)
+    }
+
+    /// like `think`, but plays at the chosen strength
+    pub fn think(&mut self, board: &Board) -> Option<(Move, String)> {
+        let Some(strength) = &self.strength else {
+            return think(board, &self.limits);
+        };
+        let (line, result) = strength::play(board, &self.limits, strength, &mut self.rng)?;
+        let m = line.first()?.clone();
+        let info = match result.lines.iter().find(|l| l.pv.first() == Some(&m)) {
+            Some(l) => format!(
+                "smogfish plays {} at {} Elo (score {}, depth {}, pv {})",
+                to_san(board, &m),
+                strength.elo,
+                format_score(l.score),
+                result.depth,
+                line_to_san(board, &l.pv)
+            ),
+            None => format!("smogfish plays {} at {} Elo (a blunder)", to_san(board, &m), strength.elo),
+        };
+        Some((m, info))
+    }
+}
+
+/// searches the position, returns the best move and a line describing it
+pub fn think(board: &Board, limits: &SearchLimits) -> Option<(Move, String)> {
+    let result = search(board, limits);
+    let m = result.best_move?;
+    let info = format!(
+        "smogfish plays {} (score {}, depth {}, pv {})",
+        to_san(board, &m),
+        format_score(result.score),
+        result.depth,
+        line_to_san(board, &result.pv)
+    );
+    Some((m, info))
+}
+
+/// the `lines` best moves with their scores and principal variations, one
+/// line of text each, best first
+pub fn analyze(board: &Board, limits: &SearchLimits, lines: usize) -> Vec<String> {
+    let options = SearchOptions {
+        multipv: lines,
+        ..Default::default()
+    };
+    let tt = TranspositionTable::new(TranspositionTable::DEFAULT_MB);
+    let control = SearchControl::new(Arc::new(SystemClock::default()), false);
+    let result = search_with(board, limits, &options, &tt, &control, |_| {});
+    result
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                "{}. {:>6}  depth {}  {}",
+                i + 1,
+                format_score(line.score),
+                result.depth,
+                line_to_san(board, &line.pv)
+            )
+        })
+        .collect()
+}
+
+/// score from the view of the side to move, in pawns or as `#n` for a mate in n moves
+pub fn format_score(score: i32) -> String {
+    if is_mate_score(score) {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        return format!("#{}", moves * score.signum());
+    }
+    format!("{:+.2}", score as f32 / 100.0)
+}
+
+/// SAN of a sequence of moves played from `board`
+pub fn line_to_san(board: &Board, line: &[Move]) -> String {
+    let mut board = board.clone();
+    let mut moves = vec![];
+    for m in line {
+        moves.push(to_san(&board, m));
+        board.make_move(m);
+    }
+    moves.join(" ")
+}
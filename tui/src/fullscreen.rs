@@ -0,0 +1,111 @@
+use crate::app::{App, Key};
+use crate::engine::Players;
+use crate::render::{draw, Screen, Tone};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use crossterm::style::{self, Attribute, Color};
+use crossterm::{cursor, execute, queue, terminal};
+use smogfish::game::Game;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// runs the full screen interface until the user quits, `time` is the time
+/// per side for the whole game
+pub fn run(game: Game, players: Players, flipped: bool, time: Option<Duration>) -> io::Result<()> {
+    let mut app = App::new(game, players);
+    app.flipped = flipped;
+    app.clock.limit = time;
+
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide, event::EnableMouseCapture)?;
+    let result = event_loop(&mut app, &mut out);
+    // restore the terminal even if drawing failed
+    execute!(out, event::DisableMouseCapture, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn event_loop(app: &mut App, out: &mut impl Write) -> io::Result<()> {
+    queue!(out, terminal::Clear(terminal::ClearType::All))?;
+    let mut last_tick = Instant::now();
+    while !app.quit {
+        app.tick(last_tick.elapsed());
+        last_tick = Instant::now();
+
+        app.poll();
+        if app.engine_to_move() {
+            app.engine_move();
+        }
+        present(&draw(app), out)?;
+
+        // wake up regularly to update the clocks, and more often to play
+        // the engine's move as soon as it is found
+        let wait = if app.thinking() { 20 } else { 200 };
+        if !event::poll(Duration::from_millis(wait))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(k) if k.kind == KeyEventKind::Press => {
+                let key = match k.code {
+                    KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => Some(Key::Char('q')),
+                    KeyCode::Up => Some(Key::Up),
+                    KeyCode::Down => Some(Key::Down),
+                    KeyCode::Left => Some(Key::Left),
+                    KeyCode::Right => Some(Key::Right),
+                    KeyCode::Enter => Some(Key::Enter),
+                    KeyCode::Esc => Some(Key::Esc),
+                    KeyCode::Char(c) => Some(Key::Char(c)),
+                    _ => None,
+                };
+                if let Some(key) = key {
+                    app.handle_key(key);
+                }
+            }
+            Event::Mouse(m) if m.kind == MouseEventKind::Down(MouseButton::Left) => app.click(m.column, m.row),
+            Event::Resize(..) => queue!(out, terminal::Clear(terminal::ClearType::All))?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn color_of(tone: Tone) -> Color {
+    match tone {
+        Tone::Default => Color::Reset,
+        Tone::Dim => Color::DarkGrey,
+        Tone::WhitePiece => Color::Rgb { r: 255, g: 255, b: 255 },
+        Tone::BlackPiece => Color::Rgb { r: 0, g: 0, b: 0 },
+        Tone::LightSquare => Color::Rgb { r: 240, g: 217, b: 181 },
+        Tone::DarkSquare => Color::Rgb { r: 181, g: 136, b: 99 },
+        Tone::Cursor => Color::Rgb { r: 100, g: 150, b: 230 },
+        Tone::Selected => Color::Rgb { r: 120, g: 170, b: 90 },
+        Tone::Target => Color::Rgb { r: 205, g: 210, b: 106 },
+        Tone::LastMove => Color::Rgb { r: 170, g: 162, b: 58 },
+        Tone::Check => Color::Rgb { r: 220, g: 80, b: 80 },
+    }
+}
+
+/// writes a frame to the terminal
+fn present(screen: &Screen, out: &mut impl Write) -> io::Result<()> {
+    for (y, row) in screen.rows().enumerate() {
+        queue!(out, cursor::MoveTo(0, y as u16))?;
+        let mut current = None;
+        for cell in row {
+            if current != Some(cell.style) {
+                queue!(
+                    out,
+                    style::SetAttribute(Attribute::Reset),
+                    style::SetForegroundColor(color_of(cell.style.fg)),
+                    style::SetBackgroundColor(color_of(cell.style.bg)),
+                )?;
+                if cell.style.bold {
+                    queue!(out, style::SetAttribute(Attribute::Bold))?;
+                }
+                current = Some(cell.style);
+            }
+            queue!(out, style::Print(cell.ch))?;
+        }
+    }
+    queue!(out, style::SetAttribute(Attribute::Reset), style::ResetColor)?;
+    out.flush()
+}
@@ -0,0 +1,15 @@
+use smogfish::board::helper::Color;
+
+pub mod app;
+pub mod command;
+pub mod engine;
+pub mod fullscreen;
+pub mod render;
+pub mod text;
+
+pub fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
@@ -1,170 +1,228 @@
 use colored::Colorize;
-use smogfish::board::helper::{Color, GameState, Piece, Position};
-use smogfish::board::{Board, Move};
-use std::io;
+use smogfish::board::Move;
+use smogfish::eval::trace::EvalTrace;
+use smogfish::eval::DEFAULT_PARAMS;
+use smogfish::game::{Game, GameResult};
+use smogfish::notation::to_san;
+use smogfish::rng::Rng;
+use smogfish::search::search;
+use smogfish::strength::Strength;
+use smogfish_tui::command::{parse_command, parse_move, Command, Setting, Sides, HELP};
+use smogfish_tui::engine::{analyze, Players};
+use smogfish_tui::fullscreen;
+use smogfish_tui::text::{render_board, Orientation, RenderOptions};
+use std::io::{self, Write};
+use std::time::Duration;
+use std::{env, fs, process};
+
+const USAGE: &str = "usage: smogfish-tui [--fullscreen [--clock <minutes>]] [--play <white|black|both|none>] [--depth <n>] [--movetime <ms>] [--elo <n|max>] [--seed <n>]";
+
+/// everything that is shown besides the board itself
+struct Ui {
+    options: RenderOptions,
+    /// moves whose target squares get highlighted on the next print
+    highlighted: Vec<Move>,
+}
 
 pub fn main() {
-    let mut board_buffer: [String; 8] = Default::default();
-    let mut cursor_pos: Position = Position::new(3, 1);
-
-    let mut b = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
-    // let mut b = Board::new("R1K5/PP3q1r/2B5/8/8/5Q2/pp6/r5k1 b - - 0 1");
-    // let mut b = Board::new("2k2Q2/8/8/8/1Q6/8/8/2K5 w - - 0 1");
-
-    loop {
-        match b.game_state {
-            GameState::Draw => {
-                println!("The game ended by draw!");
-                break;
-            }
-            GameState::BlackWins => {
-                println!("Black wins!");
-                break;
+    let mut game = Game::default();
+    let mut ui = Ui {
+        options: RenderOptions::default(),
+        highlighted: vec![],
+    };
+    let mut players = Players::default();
+    // error messages follow the same setting as the board
+    colored::control::set_override(ui.options.color);
+
+    // the options are the same as the commands with the same name
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let full_screen = args.iter().any(|a| a == "--fullscreen");
+    args.retain(|a| a != "--fullscreen");
+    // minutes per side for the whole game, only the full screen interface has clocks
+    let mut time = None;
+    if let Some(i) = args.iter().position(|a| a == "--clock") {
+        let minutes = args.get(i + 1).and_then(|m| m.parse::<u64>().ok()).filter(|&m| m > 0);
+        match minutes {
+            Some(m) if full_screen => time = Some(Duration::from_secs(60 * m)),
+            Some(_) => exit_with("--clock needs --fullscreen"),
+            None => exit_with("the clock has to be a number of minutes"),
+        }
+        args.drain(i..i + 2);
+    }
+    let mut side_chosen = false;
+    for pair in args.chunks(2) {
+        let cmd = match pair {
+            [name, value] if name.starts_with("--") => parse_command(&format!("{} {}", &name[2..], value)),
+            _ => Err(USAGE.to_string()),
+        };
+        match cmd {
+            Ok(cmd @ (Command::Play(_) | Command::Depth(_) | Command::MoveTime(_) | Command::Elo(_) | Command::Seed(_))) => {
+                side_chosen |= matches!(cmd, Command::Play(_));
+                handle_command(&mut game, &mut ui, &mut players, cmd).unwrap();
             }
-            GameState::WhiteWins => {
-                println!("White wins!");
-                break;
+            Ok(_) => exit_with(USAGE),
+            Err(e) => exit_with(&e),
+        }
+    }
+
+    if !side_chosen {
+        println!("Which side do you want to play? (white/black/both/none) [white]");
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).ok();
+        if !input.trim().is_empty() {
+            match input.parse() {
+                Ok(sides) => players.human = sides,
+                Err(e) => println!("{}", e),
             }
-            _ => (),
         }
+        if players.human == Sides::Black {
+            ui.options.orientation = Orientation::Black;
+        }
+    }
+
+    if full_screen {
+        if let Err(e) = fullscreen::run(game, players, ui.options.orientation == Orientation::Black, time) {
+            exit_with(&format!("error: {}", e));
+        }
+        return;
+    }
 
-        fill_board_buffer(&b, &mut board_buffer);
+    println!("type 'help' for the list of commands");
+    loop {
+        print!("{}", render_board(game.board(), &ui.options, &ui.highlighted));
+        ui.highlighted.clear();
 
-        let mut possible_moves: Vec<Move> = vec![];
-        if b.get_by_idx(cursor_pos).1 == b.current_player() {
-            get_possible_moves(&b, cursor_pos, &mut possible_moves);
+        if let Some((m, info)) = players.reply(&mut game) {
+            println!("{}", info);
+            ui.highlighted = vec![m];
+            continue;
         }
 
-        print_board_buffer(&board_buffer, cursor_pos, &possible_moves);
+        match game.result() {
+            GameResult::Ongoing => println!("{} to move", smogfish_tui::color_name(game.board().current_player())),
+            GameResult::Draw => println!("The game ended by draw!"),
+            GameResult::BlackWins => println!("Black wins!"),
+            GameResult::WhiteWins => println!("White wins!"),
+        }
+        print!("> ");
+        io::stdout().flush().ok();
 
         let mut input = String::new();
         match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                handle_input(&mut b, &input, &mut cursor_pos, &possible_moves);
-            }
+            // end of input
+            Ok(0) => break,
+            Ok(_) => match parse_command(&input) {
+                Ok(cmd) => {
+                    if let Err(e) = handle_command(&mut game, &mut ui, &mut players, cmd) {
+                        println!("{}", e.red());
+                    }
+                }
+                Err(e) => println!("{}", e.red()),
+            },
             Err(error) => println!("error: {}", error),
         }
     }
 }
 
-fn fill_board_buffer(b: &Board, bb: &mut [String; 8]) {
-    for row in (0..8).rev() {
-        bb[row as usize] = "".to_string();
-        for col in 0..8 {
-            let p = b.get_by_idx(Position::new(row, col));
-            let c = match p.0 {
-                Piece::Pawn => "p",
-                Piece::Knight => "n",
-                Piece::Bishop => "b",
-                Piece::Rook => "r",
-                Piece::Queen => "q",
-                Piece::King => "k",
-                _ => ".",
-            };
-            if p.1 == Color::White {
-                bb[row as usize].push_str(c.to_uppercase().as_str());
-            } else {
-                bb[row as usize].push_str(c);
-            }
-        }
-    }
+fn exit_with(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    process::exit(1);
 }
 
-fn print_board_buffer(bb: &[String; 8], cursor_pos: Position, possible_moves: &Vec<Move>) {
-    println!("   a b c d e f g h");
-    println!("   ―――――――――――――――");
-    for row in (0..8).rev() {
-        print!("{}| ", row + 1);
-        let mut col = 0;
-        for c in bb[row as usize].chars() {
-            let mut to_print = c.to_string().normal();
-            if Position::new(row, col) == cursor_pos {
-                to_print = to_print.bold().blue();
+fn handle_command(game: &mut Game, ui: &mut Ui, players: &mut Players, cmd: Command) -> Result<(), String> {
+    match cmd {
+        Command::Move(s) => {
+            let m = parse_move(game.board(), &s)?;
+            game.push(&m)?;
+        }
+        Command::Undo => players.undo(game)?,
+        Command::Redo => {
+            game.redo().ok_or("there is no move to redo")?;
+        }
+        Command::New => *game = Game::default(),
+        Command::Fen(None) => println!("{}", game.board().to_fen()),
+        Command::Fen(Some(fen)) => *game = Game::from_fen(&fen)?,
+        Command::Flip => {
+            ui.options.orientation = match ui.options.flipped(game.board()) {
+                true => Orientation::White,
+                false => Orientation::Black,
             }
-
-            if possible_moves
+        }
+        Command::Set(setting, on) => match setting {
+            Setting::Unicode => ui.options.unicode = on,
+            Setting::Color => {
+                ui.options.color = on;
+                colored::control::set_override(on);
+            }
+            Setting::Info => ui.options.info = on,
+            Setting::AutoFlip if on => ui.options.orientation = Orientation::SideToMove,
+            Setting::AutoFlip => {
+                ui.options.orientation = match ui.options.flipped(game.board()) {
+                    true => Orientation::Black,
+                    false => Orientation::White,
+                }
+            }
+        },
+        Command::Moves(square) => {
+            let board = game.board();
+            ui.highlighted = board
+                .move_list
                 .iter()
-                .position(|m| m.to == Position::new(row, col))
-                .is_some()
-            {
-                to_print = to_print.red();
+                .filter(|m| square.is_none_or(|sq| m.from == sq.into()))
+                .cloned()
+                .collect();
+            if ui.highlighted.is_empty() {
+                return Err("no legal moves".to_string());
             }
-
-            print!("{} ", to_print);
-            col += 1;
+            let mut moves: Vec<String> = ui.highlighted.iter().map(|m| to_san(board, m)).collect();
+            moves.sort();
+            println!("{}", moves.join(" "));
         }
-        println!(" |{}", row + 1);
-    }
-    println!("   ―――――――――――――――");
-    println!("   a b c d e f g h");
-}
-
-fn handle_input(
-    board: &mut Board,
-    input: &String,
-    cursor_pos: &mut Position,
-    possible_moves: &Vec<Move>,
-) {
-    match input.trim() {
-        "w" => cursor_pos.0 += 8,
-        "s" => cursor_pos.0 -= 8,
-        "d" => cursor_pos.0 += 1,
-        "a" => cursor_pos.0 -= 1,
-        cmd => {
-            let mut chars = cmd.chars().into_iter();
-            let col = chars.next();
-            let row = chars.next();
-            if row.is_some()
-                && chars.next().is_none()
-                && (col.unwrap().is_ascii())
-                && (row.unwrap().is_digit(10))
-            {
-                // position got entered
-                let pos = (col.unwrap() as u32 - 97) as u8
-                    + (row.unwrap().to_digit(10).unwrap() - 1) as u8 * 8;
-                let mov = possible_moves
-                    .iter()
-                    .find(|&m| &m.from == cursor_pos && m.to == Position(pos));
-                match mov {
-                    Some(m) => {
-                        if m.flag >= 5 && m.flag <= 8 {
-                            make_promotion_move(board, m);
-                        } else {
-                            board.make_move(m);
-                        }
-
-                        return;
-                    }
-                    None => {}
-                }
+        Command::Hint => {
+            if game.result() != GameResult::Ongoing {
+                return Err("the game is already over".to_string());
             }
-
-            println!("Invalid command!");
+            let result = search(game.board(), &players.limits);
+            let m = result.best_move.ok_or("no move found")?;
+            println!("hint: {}", to_san(game.board(), &m));
+            ui.highlighted = vec![m];
         }
+        Command::Eval => print!("{}", EvalTrace::new(game.board(), &DEFAULT_PARAMS)),
+        Command::Analyze(lines) => {
+            if game.result() != GameResult::Ongoing {
+                return Err("the game is already over".to_string());
+            }
+            for line in analyze(game.board(), &players.limits, lines) {
+                println!("{}", line);
+            }
+        }
+        Command::Go => {
+            if game.result() != GameResult::Ongoing {
+                return Err("the game is already over".to_string());
+            }
+            let (m, info) = players.play(game).ok_or("no move found")?;
+            println!("{}", info);
+            ui.highlighted = vec![m];
+        }
+        Command::Play(sides) => players.human = sides,
+        Command::Depth(depth) => players.set_depth(depth),
+        Command::MoveTime(time) => players.set_movetime(time),
+        Command::Elo(elo) => players.strength = elo.map(Strength::from_elo),
+        Command::Seed(seed) => players.rng = Rng::new(seed),
+        Command::Save(path) => {
+            fs::write(&path, game.to_pgn()).map_err(|e| format!("could not write {}: {}", path, e))?;
+            println!("saved to {}", path);
+        }
+        Command::Load(path) => {
+            let content = fs::read_to_string(&path).map_err(|e| format!("could not read {}: {}", path, e))?;
+            // a file with a single line that is a valid FEN is a position, everything else a PGN
+            *game = match Game::from_fen(content.trim()) {
+                Ok(g) if content.trim().lines().count() == 1 => g,
+                _ => Game::from_pgn(&content)?,
+            };
+        }
+        Command::Help => println!("{}", HELP),
+        Command::Quit => process::exit(0),
     }
-}
-
-fn make_promotion_move(board: &mut Board, mov: &Move) {
-    println!("To which piece do you want to promote? (Q/R/B/K)");
-    let mut input = String::new();
-    match io::stdin().read_line(&mut input) {
-        Ok(_) => match input.chars().nth(0) {
-            Some('Q') => board.make_move(&Move::new_with_flags(mov.from, mov.to, 5)),
-            Some('R') => board.make_move(&Move::new_with_flags(mov.from, mov.to, 6)),
-            Some('B') => board.make_move(&Move::new_with_flags(mov.from, mov.to, 7)),
-            Some('K') => board.make_move(&Move::new_with_flags(mov.from, mov.to, 8)),
-            _ => println!("Not a valid promotion"),
-        },
-        Err(error) => println!("error: {}", error),
-    }
-}
-
-fn get_possible_moves(b: &Board, cursor_pos: Position, possible_moves: &mut Vec<Move>) {
-    let mut a: Vec<Move> = b
-        .move_list
-        .iter()
-        .filter(|m| m.from == cursor_pos)
-        .map(|m| m.clone())
-        .collect();
-    possible_moves.append(&mut a);
+    Ok(())
 }
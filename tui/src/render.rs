@@ -0,0 +1,301 @@
+use crate::app::{App, KEY_HELP};
+use crate::color_name;
+use smogfish::board::helper::{Color, Piece};
+use smogfish::board::square::{File, Rank, Square};
+use smogfish::game::{GameResult, Termination};
+use std::fmt;
+use std::time::Duration;
+
+pub const WIDTH: u16 = 80;
+pub const HEIGHT: u16 = 13;
+
+const BOARD_X: u16 = 2;
+const BOARD_Y: u16 = 1;
+const SQUARE_WIDTH: u16 = 3;
+const PANE_X: u16 = 30;
+const MOVE_ROWS: usize = 5;
+
+/// what a cell is used for, the terminal decides on the actual colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Default,
+    Dim,
+    WhitePiece,
+    BlackPiece,
+    LightSquare,
+    DarkSquare,
+    Cursor,
+    Selected,
+    Target,
+    LastMove,
+    Check,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Tone,
+    pub bg: Tone,
+    pub bold: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            fg: Tone::Default,
+            bg: Tone::Default,
+            bold: false,
+        }
+    }
+}
+
+impl Style {
+    fn fg(fg: Tone) -> Self {
+        Style { fg, ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+/// a drawn frame, independent of the terminal it ends up on
+pub struct Screen {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        let blank = Cell {
+            ch: ' ',
+            style: Style::default(),
+        };
+        Screen {
+            width,
+            height,
+            cells: vec![blank; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn cell(&self, x: u16, y: u16) -> Cell {
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// writes text starting at (x, y), everything outside of the screen is cut off
+    pub fn put(&mut self, x: u16, y: u16, text: &str, style: Style) {
+        if y >= self.height {
+            return;
+        }
+        for (i, ch) in text.chars().enumerate() {
+            let x = x as usize + i;
+            if x >= self.width as usize {
+                break;
+            }
+            self.cells[y as usize * self.width as usize + x] = Cell { ch, style };
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width as usize)
+    }
+}
+
+/// the text of the screen without styles, trailing spaces are left out
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row.iter().map(|c| c.ch).collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn glyph(piece: Piece, color: Color) -> char {
+    let glyphs = match color {
+        Color::White => ['♙', '♘', '♗', '♖', '♕', '♔'],
+        Color::Black => ['♟', '♞', '♝', '♜', '♛', '♚'],
+    };
+    glyphs[piece as usize]
+}
+
+/// column and row of a square on the board, counted from the top left
+fn board_coords(flipped: bool, sq: Square) -> (u16, u16) {
+    let (file, rank) = (sq.file() as u16, sq.rank() as u16);
+    match flipped {
+        false => (file, 7 - rank),
+        true => (7 - file, rank),
+    }
+}
+
+/// the square drawn at a position on the screen
+pub fn square_at(flipped: bool, x: u16, y: u16) -> Option<Square> {
+    if x < BOARD_X || y < BOARD_Y {
+        return None;
+    }
+    let (col, row) = ((x - BOARD_X) / SQUARE_WIDTH, y - BOARD_Y);
+    if col >= 8 || row >= 8 {
+        return None;
+    }
+    let (file, rank) = match flipped {
+        false => (col, 7 - row),
+        true => (7 - col, row),
+    };
+    Some(Square::new(File::ALL[file as usize], Rank::ALL[rank as usize]))
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn termination_name(termination: Termination) -> &'static str {
+    match termination {
+        Termination::Checkmate => "checkmate",
+        Termination::Stalemate => "stalemate",
+        Termination::FiftyMoveRule => "the fifty move rule",
+        Termination::ThreefoldRepetition => "threefold repetition",
+        Termination::Resignation => "resignation",
+        Termination::DrawAgreement => "agreement",
+        Termination::TimeForfeit => "time",
+        Termination::Adjudication => "adjudication",
+        Termination::Abandoned => "abandonment",
+        Termination::RulesInfraction => "a rules infraction",
+    }
+}
+
+/// the move list as lines like ` 1. e4      e5`
+fn move_rows(app: &App) -> Vec<String> {
+    let start = app.game.start_position();
+    let mut rows = vec![];
+    let mut number = start.fullmove_number();
+    let mut color = start.current_player();
+    let mut row = String::new();
+    for played in app.game.moves() {
+        match color {
+            Color::White => row = format!("{:>3}. {:<8}", number, played.san),
+            Color::Black => {
+                if row.is_empty() {
+                    row = format!("{:>3}. {:<8}", number, "...");
+                }
+                rows.push(format!("{}{}", row, played.san));
+                row.clear();
+                number += 1;
+            }
+        }
+        color = !color;
+    }
+    if !row.is_empty() {
+        rows.push(row.trim_end().to_string());
+    }
+    rows
+}
+
+fn status_line(app: &App) -> String {
+    if !app.message.is_empty() {
+        return app.message.clone();
+    }
+    let board = app.game.board();
+    let reason = app.game.termination().map(termination_name).unwrap_or("");
+    match app.game.result() {
+        GameResult::Ongoing if board.in_check() => format!("{} to move, check", color_name(board.current_player())),
+        GameResult::Ongoing => format!("{} to move", color_name(board.current_player())),
+        GameResult::WhiteWins => format!("White wins by {}", reason),
+        GameResult::BlackWins => format!("Black wins by {}", reason),
+        GameResult::Draw => format!("Draw by {}", reason),
+    }
+}
+
+/// draws the whole interface
+pub fn draw(app: &App) -> Screen {
+    let mut screen = Screen::new(WIDTH, HEIGHT);
+    let board = app.game.board();
+
+    // coordinates around the board
+    for i in 0..8 {
+        let file = File::ALL[if app.flipped { 7 - i } else { i }];
+        let x = BOARD_X + i as u16 * SQUARE_WIDTH + 1;
+        screen.put(x, BOARD_Y - 1, &file.to_string(), Style::fg(Tone::Dim));
+        screen.put(x, BOARD_Y + 8, &file.to_string(), Style::fg(Tone::Dim));
+
+        let rank = Rank::ALL[if app.flipped { i } else { 7 - i }];
+        let y = BOARD_Y + i as u16;
+        screen.put(0, y, &rank.to_string(), Style::fg(Tone::Dim));
+        screen.put(BOARD_X + 8 * SQUARE_WIDTH + 1, y, &rank.to_string(), Style::fg(Tone::Dim));
+    }
+
+    let targets = app.targets();
+    let last_move = app.game.last_move().map(|p| &p.mov);
+    let checked_king = match board.in_check() {
+        true => board.pieces(board.current_player(), Piece::King).lsb(),
+        false => None,
+    };
+    for sq in Square::ALL {
+        let pos = sq.into();
+        let bg = if sq == app.cursor {
+            Tone::Cursor
+        } else if Some(sq) == app.selected {
+            Tone::Selected
+        } else if targets.iter().any(|m| m.to == pos) {
+            Tone::Target
+        } else if checked_king == Some(pos) {
+            Tone::Check
+        } else if last_move.is_some_and(|m| m.from == pos || m.to == pos) {
+            Tone::LastMove
+        } else if (sq.file() as u8 + sq.rank() as u8) % 2 == 1 {
+            Tone::LightSquare
+        } else {
+            Tone::DarkSquare
+        };
+        let (text, fg) = match board.piece_at(sq) {
+            Some((piece, color)) => {
+                let fg = match color {
+                    Color::White => Tone::WhitePiece,
+                    Color::Black => Tone::BlackPiece,
+                };
+                (format!(" {} ", glyph(piece, color)), fg)
+            }
+            None => ("   ".to_string(), Tone::Default),
+        };
+        let (col, row) = board_coords(app.flipped, sq);
+        let style = Style { fg, bg, bold: false };
+        screen.put(BOARD_X + col * SQUARE_WIDTH, BOARD_Y + row, &text, style);
+    }
+
+    // clocks, the side to move is marked
+    for (i, color) in [Color::White, Color::Black].into_iter().enumerate() {
+        let to_move = app.game.result() == GameResult::Ongoing && board.current_player() == color;
+        let line = format!(
+            "{} {:<6}{}",
+            if to_move { '▶' } else { ' ' },
+            color_name(color),
+            format_duration(app.clock.shown(color))
+        );
+        let style = Style {
+            bold: to_move,
+            ..Default::default()
+        };
+        screen.put(PANE_X, BOARD_Y + i as u16, &line, style);
+    }
+
+    screen.put(PANE_X, BOARD_Y + 3, "Moves", Style { bold: true, ..Default::default() });
+    let rows = move_rows(app);
+    for (i, row) in rows[rows.len().saturating_sub(MOVE_ROWS)..].iter().enumerate() {
+        screen.put(PANE_X, BOARD_Y + 4 + i as u16, row, Style::default());
+    }
+
+    screen.put(0, BOARD_Y + 10, &status_line(app), Style::default());
+    screen.put(0, BOARD_Y + 11, KEY_HELP, Style::fg(Tone::Dim));
+    screen
+}
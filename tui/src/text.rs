@@ -0,0 +1,110 @@
+use crate::color_name;
+use crate::render::glyph;
+use colored::Colorize;
+use smogfish::board::helper::{Color, Piece, Position};
+use smogfish::board::{Board, Move};
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// which side is shown at the bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    White,
+    Black,
+    SideToMove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub orientation: Orientation,
+    /// chess glyphs instead of letters
+    pub unicode: bool,
+    /// ANSI colors for highlighted squares
+    pub color: bool,
+    /// FEN, side to move, castling rights and clocks beneath the board
+    pub info: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            orientation: Orientation::White,
+            unicode: false,
+            color: color_supported(),
+            info: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// whether black is at the bottom for this board
+    pub fn flipped(&self, board: &Board) -> bool {
+        match self.orientation {
+            Orientation::White => false,
+            Orientation::Black => true,
+            Orientation::SideToMove => board.current_player() == Color::Black,
+        }
+    }
+}
+
+/// colors are only used on a terminal and when `NO_COLOR` is not set
+pub fn color_supported() -> bool {
+    env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stdout().is_terminal()
+}
+
+fn piece_char(piece: Piece, color: Color, unicode: bool) -> char {
+    if unicode {
+        return glyph(piece, color);
+    }
+    let c = ['p', 'n', 'b', 'r', 'q', 'k'][piece as usize];
+    match color {
+        Color::White => c.to_ascii_uppercase(),
+        Color::Black => c,
+    }
+}
+
+/// draws the board as text. The target squares of `highlighted` are shown in
+/// red and their origins in blue, if colors are on.
+pub fn render_board(board: &Board, options: &RenderOptions, highlighted: &[Move]) -> String {
+    let flipped = options.flipped(board);
+    let files = if flipped { "h g f e d c b a" } else { "a b c d e f g h" };
+    let line = if options.unicode { "―" } else { "-" }.repeat(15);
+    let ranks: Vec<u8> = if flipped { (0..8).collect() } else { (0..8).rev().collect() };
+
+    let mut out = format!("   {}\n   {}\n", files, line);
+    for rank in ranks {
+        out.push_str(&format!("{}| ", rank + 1));
+        for i in 0..8 {
+            let file = if flipped { 7 - i } else { i };
+            let pos = Position::new(rank, file);
+            let c = match board.piece_at(pos) {
+                Some((piece, color)) => piece_char(piece, color, options.unicode),
+                None => '.',
+            };
+            let mut square = c.to_string().normal();
+            if options.color {
+                if highlighted.iter().any(|m| m.to == pos) {
+                    square = square.red();
+                }
+                if highlighted.iter().any(|m| m.from == pos) {
+                    square = square.bold().blue();
+                }
+            }
+            out.push_str(&format!("{} ", square));
+        }
+        out.push_str(&format!(" |{}\n", rank + 1));
+    }
+    out.push_str(&format!("   {}\n   {}\n", line, files));
+
+    if options.info {
+        out.push_str(&format!("fen: {}\n", board.to_fen()));
+        out.push_str(&format!(
+            "{} to move, castling {}, halfmove clock {}, move {}\n",
+            color_name(board.current_player()),
+            board.castling_rights(),
+            board.halfmove_clock(),
+            board.fullmove_number()
+        ));
+    }
+    out
+}
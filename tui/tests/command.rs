@@ -0,0 +1,116 @@
+use smogfish::board::square::Square;
+use smogfish::board::Board;
+use smogfish_tui::command::{parse_command, parse_move, Command, Setting, Sides};
+use std::time::Duration;
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+fn err(input: &str) -> String {
+    parse_command(input).unwrap_err()
+}
+
+#[test]
+fn commands() {
+    assert_eq!(parse_command("undo"), Ok(Command::Undo));
+    assert_eq!(parse_command("redo"), Ok(Command::Redo));
+    assert_eq!(parse_command("new"), Ok(Command::New));
+    assert_eq!(parse_command("fen"), Ok(Command::Fen(None)));
+    assert_eq!(parse_command(&format!("fen {}", START)), Ok(Command::Fen(Some(START.to_string()))));
+    assert_eq!(parse_command("flip"), Ok(Command::Flip));
+    assert_eq!(parse_command("set unicode off"), Ok(Command::Set(Setting::Unicode, false)));
+    assert_eq!(parse_command("set color on"), Ok(Command::Set(Setting::Color, true)));
+    assert_eq!(parse_command("set info on"), Ok(Command::Set(Setting::Info, true)));
+    assert_eq!(parse_command("set autoflip off"), Ok(Command::Set(Setting::AutoFlip, false)));
+    assert_eq!(parse_command("moves"), Ok(Command::Moves(None)));
+    assert_eq!(parse_command("moves e2"), Ok(Command::Moves(Some("e2".parse::<Square>().unwrap()))));
+    assert_eq!(parse_command("hint"), Ok(Command::Hint));
+    assert_eq!(parse_command("eval"), Ok(Command::Eval));
+    assert_eq!(parse_command("go"), Ok(Command::Go));
+    assert_eq!(parse_command("play black"), Ok(Command::Play(Sides::Black)));
+    assert_eq!(parse_command("play w"), Ok(Command::Play(Sides::White)));
+    assert_eq!(parse_command("play both"), Ok(Command::Play(Sides::Both)));
+    assert_eq!(parse_command("play none"), Ok(Command::Play(Sides::None)));
+    assert_eq!(parse_command("depth 6"), Ok(Command::Depth(6)));
+    assert_eq!(parse_command("movetime 250"), Ok(Command::MoveTime(Duration::from_millis(250))));
+    assert_eq!(parse_command("elo 1500"), Ok(Command::Elo(Some(1500))));
+    assert_eq!(parse_command("elo max"), Ok(Command::Elo(None)));
+    assert_eq!(parse_command("seed 42"), Ok(Command::Seed(42)));
+    assert_eq!(parse_command("save game.pgn"), Ok(Command::Save("game.pgn".to_string())));
+    assert_eq!(parse_command("load game.pgn"), Ok(Command::Load("game.pgn".to_string())));
+    assert_eq!(parse_command("help"), Ok(Command::Help));
+    assert_eq!(parse_command("?"), Ok(Command::Help));
+    assert_eq!(parse_command("quit"), Ok(Command::Quit));
+    assert_eq!(parse_command("exit"), Ok(Command::Quit));
+    assert_eq!(parse_command("  undo  "), Ok(Command::Undo));
+}
+
+#[test]
+fn anything_else_is_a_move() {
+    assert_eq!(parse_command("e4"), Ok(Command::Move("e4".to_string())));
+    assert_eq!(parse_command("e7e8q"), Ok(Command::Move("e7e8q".to_string())));
+    assert_eq!(parse_command("O-O"), Ok(Command::Move("O-O".to_string())));
+    assert_eq!(parse_command("xyz"), Ok(Command::Move("xyz".to_string())));
+}
+
+#[test]
+fn command_errors() {
+    assert_eq!(err(""), "enter a move or a command, 'help' lists them");
+    assert_eq!(err("   "), "enter a move or a command, 'help' lists them");
+
+    for name in ["undo", "redo", "new", "flip", "hint", "eval", "go", "help", "quit"] {
+        assert_eq!(err(&format!("{} now", name)), format!("'{}' takes no arguments", name));
+    }
+
+    assert_eq!(err("play"), "usage: play <white|black|both|none>");
+    assert_eq!(err("depth"), "usage: depth <n>");
+    assert_eq!(err("movetime"), "usage: movetime <ms>");
+    assert_eq!(err("elo"), "usage: elo <n|max>");
+    assert_eq!(err("seed"), "usage: seed <n>");
+    assert_eq!(err("save"), "usage: save <file.pgn>");
+    assert_eq!(err("load"), "usage: load <file>");
+
+    let set_usage = "usage: set <unicode|color|info|autoflip> <on|off>";
+    assert_eq!(err("set"), set_usage);
+    assert_eq!(err("set unicode"), set_usage);
+    assert_eq!(err("set unicode maybe"), set_usage);
+    assert_eq!(err("set sound on"), "unknown option 'sound'");
+
+    assert_eq!(err("moves e9"), "'e9' is not a square");
+    assert_eq!(err("play red"), "'red' is not a side, use white, black, both or none");
+    assert_eq!(err("analyze 0"), "the number of lines has to be between 1 and 20");
+    assert_eq!(err("depth 0"), "the depth has to be between 1 and 64");
+    assert_eq!(err("depth deep"), "the depth has to be between 1 and 64");
+    assert_eq!(err("movetime soon"), "the move time has to be a number of milliseconds");
+    assert_eq!(err("elo 100"), "the Elo has to be between 800 and 2800 or max");
+    assert_eq!(err("elo 3000"), "the Elo has to be between 800 and 2800 or max");
+    assert_eq!(err("seed -1"), "the seed has to be a number");
+    assert_eq!(err("castle kingside"), "unknown command 'castle', 'help' lists the commands");
+}
+
+#[test]
+fn moves() {
+    let board = Board::new(START);
+    assert_eq!(parse_move(&board, "e2e4").unwrap().to_string(), "e2e4");
+    assert_eq!(parse_move(&board, "e4").unwrap().to_string(), "e2e4");
+    assert_eq!(parse_move(&board, "Nf3").unwrap().to_string(), "g1f3");
+
+    let board = Board::new("4k3/P7/8/8/8/8/4K3/R6R w - - 0 1");
+    assert_eq!(parse_move(&board, "a8=N").unwrap().to_string(), "a7a8n");
+    assert_eq!(parse_move(&board, "a7a8q").unwrap().to_string(), "a7a8q");
+    assert_eq!(parse_move(&board, "Rad1").unwrap().to_string(), "a1d1");
+    assert_eq!(parse_move(&board, "Rhd1").unwrap().to_string(), "h1d1");
+}
+
+#[test]
+fn move_errors() {
+    let board = Board::new(START);
+    assert_eq!(parse_move(&board, "e2e5").unwrap_err(), "e2e5 is not a legal move");
+    assert_eq!(parse_move(&board, "Nd2").unwrap_err(), "Nd2 is not a legal move");
+    assert_eq!(parse_move(&board, "e9").unwrap_err(), "'e9' is not a square");
+    assert_eq!(parse_move(&board, "Zf3").unwrap_err(), "'Z' is not a piece");
+
+    let board = Board::new("4k3/P7/8/8/8/8/4K3/R6R w - - 0 1");
+    assert_eq!(parse_move(&board, "Rd1").unwrap_err(), "Rd1 is ambiguous");
+    assert_eq!(parse_move(&board, "a8").unwrap_err(), "a8 is missing the promotion piece");
+    assert_eq!(parse_move(&board, "a7a8").unwrap_err(), "a7a8 is missing the promotion piece");
+}
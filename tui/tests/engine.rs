@@ -0,0 +1,146 @@
+use smogfish::board::helper::Color;
+use smogfish::board::Board;
+use smogfish::game::Game;
+use smogfish::rng::Rng;
+use smogfish::search::SearchLimits;
+use smogfish::strength::Strength;
+use std::time::Duration;
+use smogfish_tui::command::{parse_command, Command, Sides};
+use smogfish_tui::engine::{analyze, Players};
+
+#[test]
+fn analysis() {
+    let board = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let limits = SearchLimits {
+        depth: Some(3),
+        ..Default::default()
+    };
+    let lines = analyze(&board, &limits, 3);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("1. "));
+    assert!(lines[0].contains("  depth 3  Rxd5 "), "{}", lines[0]);
+    assert!(lines[2].starts_with("3. "));
+
+    assert_eq!(parse_command("analyze"), Ok(Command::Analyze(3)));
+    assert_eq!(parse_command("analyze 5"), Ok(Command::Analyze(5)));
+    assert!(parse_command("analyze 0").is_err());
+    assert!(parse_command("analyze many").is_err());
+}
+
+#[test]
+fn strength() {
+    let board = Board::new("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1");
+    let mut players = Players {
+        strength: Some(Strength::from_elo(800)),
+        ..Default::default()
+    };
+    let play = |players: &mut Players, seed| {
+        players.rng = Rng::new(seed);
+        players.think(&board).unwrap()
+    };
+    let (m, info) = play(&mut players, 1);
+    assert_eq!(play(&mut players, 1), (m, info.clone()));
+    assert!(info.contains(" at 800 Elo "), "{}", info);
+
+    players.strength = Some(Strength::from_elo(2800));
+    assert_eq!(play(&mut players, 1).0.to_string(), "d2d5");
+
+    assert_eq!(parse_command("elo 1500"), Ok(Command::Elo(Some(1500))));
+    assert_eq!(parse_command("elo max"), Ok(Command::Elo(None)));
+    assert!(parse_command("elo 100").is_err());
+    assert_eq!(parse_command("seed 42"), Ok(Command::Seed(42)));
+    assert!(parse_command("seed").is_err());
+}
+
+fn depth(n: u8) -> Players {
+    let mut players = Players::default();
+    players.set_depth(n);
+    players
+}
+
+#[test]
+fn sides() {
+    let plays = |human| {
+        let players = Players {
+            human,
+            ..Default::default()
+        };
+        [players.engine_plays(Color::White), players.engine_plays(Color::Black)]
+    };
+    assert_eq!(plays(Sides::White), [false, true]);
+    assert_eq!(plays(Sides::Black), [true, false]);
+    assert_eq!(plays(Sides::Both), [false, false]);
+    assert_eq!(plays(Sides::None), [true, true]);
+    assert_eq!(Players::default().human, Sides::White);
+}
+
+#[test]
+fn limits() {
+    let mut players = Players::default();
+    assert_eq!(players.limits.movetime, Some(Duration::from_secs(1)));
+    assert_eq!(players.limits.depth, None);
+
+    players.set_depth(3);
+    assert_eq!((players.limits.depth, players.limits.movetime), (Some(3), None));
+    let (_, info) = players.think(Game::default().board()).unwrap();
+    assert!(info.contains(", depth 3, "), "{}", info);
+
+    players.set_movetime(Duration::from_millis(50));
+    assert_eq!((players.limits.depth, players.limits.movetime), (None, Some(Duration::from_millis(50))));
+}
+
+#[test]
+fn engine_replies() {
+    let mut players = depth(2);
+    let mut game = Game::default();
+    // white is the human's side
+    assert_eq!(players.reply(&mut game), None);
+    assert_eq!(game.moves().len(), 0);
+
+    game.push_uci("e2e4").unwrap();
+    let (m, info) = players.reply(&mut game).unwrap();
+    assert!(info.starts_with("smogfish plays "), "{}", info);
+    assert_eq!(game.last_move().map(|p| &p.mov), Some(&m));
+    assert_eq!(game.board().current_player(), Color::White);
+
+    // go plays for the human's side too
+    let (m, _) = players.play(&mut game).unwrap();
+    assert_eq!(game.last_move().map(|p| &p.mov), Some(&m));
+
+    players.human = Sides::Both;
+    assert_eq!(players.reply(&mut game), None);
+
+    let mut mated = Game::from_fen("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+    players.human = Sides::None;
+    assert_eq!(players.play(&mut mated), None);
+}
+
+#[test]
+fn undo_takes_back_the_reply() {
+    let mut players = depth(1);
+    let mut game = Game::default();
+    game.push_uci("e2e4").unwrap();
+    players.reply(&mut game).unwrap();
+    assert_eq!(game.moves().len(), 2);
+
+    // the human's move and the engine's reply, so it is the human's turn again
+    players.undo(&mut game).unwrap();
+    assert_eq!(game.moves().len(), 0);
+    assert_eq!(players.undo(&mut game), Err("there is no move to take back".to_string()));
+
+    // playing both sides takes back one move at a time
+    players.human = Sides::Both;
+    game.push_uci("e2e4").unwrap();
+    game.push_uci("e7e5").unwrap();
+    players.undo(&mut game).unwrap();
+    assert_eq!(game.moves().len(), 1);
+
+    // back to the human's previous turn as black
+    players.human = Sides::Black;
+    game.push_uci("e7e5").unwrap();
+    game.push_uci("g1f3").unwrap();
+    players.undo(&mut game).unwrap();
+    assert_eq!(game.moves().len(), 1);
+    players.undo(&mut game).unwrap();
+    assert_eq!(game.moves().len(), 0);
+}
@@ -0,0 +1,190 @@
+use smogfish::board::square::Square;
+use smogfish::game::{Game, GameResult, Termination};
+use smogfish_tui::app::{App, Key};
+use smogfish_tui::command::Sides;
+use smogfish_tui::engine::Players;
+use smogfish_tui::render::{draw, square_at, Screen, Tone};
+use std::time::Duration;
+
+fn two_players(game: Game) -> App {
+    let players = Players {
+        human: Sides::Both,
+        ..Default::default()
+    };
+    App::new(game, players)
+}
+
+/// background of a square when the board is not flipped
+fn background(screen: &Screen, sq: Square) -> Tone {
+    let (x, y) = (2 + 3 * sq.file() as u16 + 1, 1 + 7 - sq.rank() as u16);
+    screen.cell(x, y).style.bg
+}
+
+#[test]
+fn snapshot() {
+    let mut app = two_players(Game::default());
+    for san in ["e4", "e5", "Nf3"] {
+        app.game.push_san(san).unwrap();
+    }
+    assert_eq!(
+        draw(&app).to_string(),
+        "   a  b  c  d  e  f  g  h
+8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜  8    White 00:00
+7  ♟  ♟  ♟  ♟     ♟  ♟  ♟  7  ▶ Black 00:00
+6                          6
+5              ♟           5  Moves
+4              ♙           4    1. e4      e5
+3                 ♘        3    2. Nf3
+2  ♙  ♙  ♙  ♙     ♙  ♙  ♙  2
+1  ♖  ♘  ♗  ♕  ♔  ♗     ♖  1
+   a  b  c  d  e  f  g  h
+
+Black to move
+arrows move  enter select  u undo  r redo  f flip  g go  h hint  n new  q quit
+"
+    );
+
+    app.flipped = true;
+    let screen = draw(&app).to_string();
+    let lines: Vec<&str> = screen.lines().collect();
+    assert_eq!(lines[0], "   h  g  f  e  d  c  b  a");
+    assert_eq!(lines[1], "1  ♖     ♗  ♔  ♕  ♗  ♘  ♖  1    White 00:00");
+    assert_eq!(lines[8], "8  ♜  ♞  ♝  ♚  ♛  ♝  ♞  ♜  8");
+}
+
+#[test]
+fn highlights() {
+    let mut app = two_players(Game::default());
+    app.game.push_san("Nf3").unwrap();
+    let screen = draw(&app);
+    assert_eq!(background(&screen, Square::E2), Tone::Cursor);
+    assert_eq!(background(&screen, Square::G1), Tone::LastMove);
+    assert_eq!(background(&screen, Square::F3), Tone::LastMove);
+    assert_eq!(background(&screen, Square::A1), Tone::DarkSquare);
+    assert_eq!(background(&screen, Square::H1), Tone::LightSquare);
+
+    // select the e pawn and look at its targets
+    app.cursor = Square::E7;
+    app.handle_key(Key::Enter);
+    assert_eq!(app.selected, Some(Square::E7));
+    let screen = draw(&app);
+    assert_eq!(background(&screen, Square::E7), Tone::Cursor);
+    assert_eq!(background(&screen, Square::E6), Tone::Target);
+    assert_eq!(background(&screen, Square::E5), Tone::Target);
+    assert_eq!(background(&screen, Square::E4), Tone::LightSquare);
+
+    let app = two_players(Game::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").unwrap());
+    let screen = draw(&app);
+    assert_eq!(background(&screen, Square::E8), Tone::Check);
+    assert!(screen.to_string().contains("Black to move, check"));
+}
+
+#[test]
+fn keys_and_mouse() {
+    let mut app = two_players(Game::default());
+    // e2 to e4 with the arrow keys
+    app.handle_key(Key::Enter);
+    app.handle_key(Key::Up);
+    app.handle_key(Key::Up);
+    app.handle_key(Key::Enter);
+    assert_eq!(app.game.moves()[0].san, "e4");
+
+    // the cursor stays on the board
+    for _ in 0..10 {
+        app.handle_key(Key::Left);
+    }
+    assert_eq!(app.cursor, Square::A4);
+
+    // flipped, up goes towards rank 1
+    app.flipped = true;
+    app.handle_key(Key::Up);
+    assert_eq!(app.cursor, Square::A3);
+
+    assert_eq!(square_at(false, 3, 8), Some(Square::A1));
+    assert_eq!(square_at(false, 25, 1), Some(Square::H8));
+    assert_eq!(square_at(true, 3, 8), Some(Square::H8));
+    assert_eq!(square_at(false, 26, 1), None);
+    assert_eq!(square_at(false, 3, 9), None);
+
+    // e7 to e5 with the mouse on the flipped board
+    app.click(12, 7);
+    assert_eq!(app.selected, Some(Square::E7));
+    app.click(12, 5);
+    assert_eq!(app.game.moves()[1].san, "e5");
+
+    app.handle_key(Key::Char('u'));
+    assert_eq!(app.game.ply(), 1);
+    app.handle_key(Key::Char('q'));
+    assert!(app.quit);
+}
+
+#[test]
+fn promotion() {
+    let mut app = two_players(Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+    app.cursor = Square::B7;
+    app.handle_key(Key::Enter);
+    app.handle_key(Key::Up);
+    app.handle_key(Key::Enter);
+    assert!(app.promotion.is_some());
+    assert!(draw(&app).to_string().contains("promote to"));
+    app.handle_key(Key::Char('n'));
+    assert_eq!(app.game.moves()[0].san, "b8=N");
+}
+
+#[test]
+fn clocks() {
+    let mut app = two_players(Game::default());
+    app.clock.limit = Some(Duration::from_secs(60));
+    app.tick(Duration::from_secs(15));
+    assert!(draw(&app).to_string().contains("▶ White 00:45"));
+    app.game.push_san("e4").unwrap();
+    app.tick(Duration::from_secs(61));
+    assert_eq!(app.game.result(), GameResult::WhiteWins);
+    assert_eq!(app.game.termination(), Some(Termination::TimeForfeit));
+    assert!(draw(&app).to_string().contains("White wins by time"));
+}
+
+fn against_engine() -> App {
+    let mut players = Players::default();
+    players.set_depth(2);
+    let mut app = App::new(Game::default(), players);
+    app.game.push_san("e4").unwrap();
+    app
+}
+
+#[test]
+fn engine_thinks_in_the_background() {
+    let mut app = against_engine();
+    assert!(app.engine_to_move());
+    app.engine_move();
+    assert!(app.thinking());
+    assert!(draw(&app).to_string().contains("smogfish is thinking..."));
+
+    // the cursor moves, but the game waits for the engine
+    app.handle_key(Key::Up);
+    assert_eq!(app.cursor, Square::E3);
+    app.handle_key(Key::Char('u'));
+    assert_eq!(app.game.ply(), 1);
+
+    app.wait();
+    assert!(!app.thinking());
+    assert_eq!(app.game.ply(), 2);
+    assert!(app.message.starts_with("smogfish plays "), "{}", app.message);
+
+    app.handle_key(Key::Char('h'));
+    app.wait();
+    assert_eq!(app.game.ply(), 2);
+    assert!(app.message.starts_with("hint: "), "{}", app.message);
+}
+
+#[test]
+fn engine_loses_on_time() {
+    let mut app = against_engine();
+    app.clock.limit = Some(Duration::from_secs(60));
+    app.engine_move();
+    app.tick(Duration::from_secs(61));
+    app.wait();
+    assert_eq!(app.game.ply(), 1);
+    assert_eq!(app.game.result(), GameResult::WhiteWins);
+    assert_eq!(app.game.termination(), Some(Termination::TimeForfeit));
+}
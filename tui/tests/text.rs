@@ -0,0 +1,80 @@
+use smogfish::board::Board;
+use smogfish::notation::parse_uci;
+use smogfish_tui::text::{render_board, Orientation, RenderOptions};
+
+fn plain() -> RenderOptions {
+    RenderOptions {
+        orientation: Orientation::White,
+        unicode: false,
+        color: false,
+        info: false,
+    }
+}
+
+#[test]
+fn ascii() {
+    let board = Board::new("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+    assert_eq!(
+        render_board(&board, &plain(), &[]),
+        "   a b c d e f g h
+   ---------------
+8| r n b q k b n r  |8
+7| p p p p . p p p  |7
+6| . . . . . . . .  |6
+5| . . . . p . . .  |5
+4| . . . . P . . .  |4
+3| . . . . . . . .  |3
+2| P P P P . P P P  |2
+1| R N B Q K B N R  |1
+   ---------------
+   a b c d e f g h
+"
+    );
+}
+
+#[test]
+fn options() {
+    let board = Board::new("4k3/8/8/8/8/8/8/R3K2R b KQ - 3 40");
+    let options = RenderOptions {
+        orientation: Orientation::SideToMove,
+        unicode: true,
+        info: true,
+        ..plain()
+    };
+    assert_eq!(
+        render_board(&board, &options, &[]),
+        "   h g f e d c b a
+   ―――――――――――――――
+1| ♖ . . ♔ . . . ♖  |1
+2| . . . . . . . .  |2
+3| . . . . . . . .  |3
+4| . . . . . . . .  |4
+5| . . . . . . . .  |5
+6| . . . . . . . .  |6
+7| . . . . . . . .  |7
+8| . . . ♚ . . . .  |8
+   ―――――――――――――――
+   h g f e d c b a
+fen: 4k3/8/8/8/8/8/8/R3K2R b KQ - 3 40
+Black to move, castling KQ, halfmove clock 3, move 40
+"
+    );
+
+    // white to move is shown from white's side
+    let board = Board::new("4k3/8/8/8/8/8/8/R3K2R w KQ - 3 40");
+    assert!(render_board(&board, &options, &[]).starts_with("   a b"));
+}
+
+#[test]
+fn highlights() {
+    colored::control::set_override(true);
+    let board = Board::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let m = parse_uci(&board, "e2e4").unwrap();
+
+    let colored = RenderOptions { color: true, ..plain() };
+    let text = render_board(&board, &colored, std::slice::from_ref(&m));
+    assert!(text.contains("\x1b[31m.\x1b[0m"));
+    assert!(text.contains("\x1b[1;34mP\x1b[0m"));
+
+    assert!(!render_board(&board, &plain(), &[m]).contains('\x1b'));
+}